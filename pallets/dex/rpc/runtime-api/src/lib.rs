@@ -1,7 +1,18 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::Codec;
+use pallet_dex::{MarketDetails, OrderType, PoolHealth};
+use sp_runtime::Perbill;
+use sp_std::vec::Vec;
+
 sp_api::decl_runtime_apis! {
-	pub trait DexRuntimeApi {
+	/// The runtime API of the DEX pallet.
+	/// Version 2 added the AccountId and every method but current_price,
+	/// so clients check the version of a runtime before calling them
+	#[api_version(2)]
+	pub trait DexRuntimeApi<AccountId> where
+		AccountId: Codec,
+	{
 		/// Gets the current price for a market
 		///
 		/// # Arguments:
@@ -11,5 +22,244 @@ sp_api::decl_runtime_apis! {
 		/// The current price of the market
 		/// represented as (numerator, denominator)
 		fn current_price(market: (u8, u8)) -> (u128, u128);
+
+		/// Gets the current prices of many markets at once
+		///
+		/// # Arguments:
+		/// markets: The markets to query
+		///
+		/// # Returns:
+		/// The current price of every market in the same order as requested,
+		/// represented as (numerator, denominator) or None if the market does not exist
+		fn current_prices(markets: Vec<(u8, u8)>) -> Vec<Option<(u128, u128)>>;
+
+		/// Gets the price of one asset denominated in another,
+		/// using the market of the two assets in either orientation
+		///
+		/// # Arguments:
+		/// asset: The AssetId to price
+		/// denomination: The AssetId the price is denominated in
+		///
+		/// # Returns:
+		/// The price represented as (numerator, denominator),
+		/// None if there is no market of the two assets
+		fn price_any_direction(asset: u8, denomination: u8) -> Option<(u128, u128)>;
+
+		/// Checks whether the reserves of a market are within a maximum ratio of each other
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// max_ratio: The maximum ratio between the larger and the smaller reserve
+		///
+		/// # Returns:
+		/// Whether the ratio is at most max_ratio, false if either reserve is empty,
+		/// None if the market does not exist
+		fn is_ratio_sane(market: (u8, u8), max_ratio: u128) -> Option<bool>;
+
+		/// Computes the price a market would have after a deposit of liquidity,
+		/// so clients can check that a deposit is balanced
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// base_add: The amount of BASE asset to deposit
+		/// quote_add: The amount of QUOTE asset to deposit
+		///
+		/// # Returns:
+		/// The price after the deposit represented as (numerator, denominator),
+		/// None if the market does not exist
+		fn price_after_deposit(
+			market: (u8, u8),
+			base_add: u128,
+			quote_add: u128,
+		) -> Option<(u128, u128)>;
+
+		/// Computes the trade required to move the price of a market to a target price
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// target_price_num: The numerator of the target price
+		/// target_price_den: The denominator of the target price
+		///
+		/// # Returns:
+		/// The direction and the fee inclusive input amount of the trade,
+		/// None if the market does not exist or the target cannot be reached
+		fn amount_to_reach_price(
+			market: (u8, u8),
+			target_price_num: u128,
+			target_price_den: u128,
+		) -> Option<(OrderType, u128)>;
+
+		/// Lists every market in which an account provides liquidity
+		///
+		/// # Arguments:
+		/// account: The liquidity provider
+		///
+		/// # Returns:
+		/// The markets and the (BASE, QUOTE) liquidity provided in each of them
+		fn positions_of(account: AccountId) -> Vec<((u8, u8), (u128, u128))>;
+
+		/// Lists every liquidity provider of a market
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The accounts and the (BASE, QUOTE) liquidity each of them provides
+		fn providers(market: (u8, u8)) -> Vec<(AccountId, (u128, u128))>;
+
+		/// Lists a page of the liquidity providers of a market,
+		/// so large markets can be enumerated over multiple calls
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// offset: The number of liquidity providers to skip
+		/// limit: The maximum number of liquidity providers to return
+		///
+		/// # Returns:
+		/// The accounts and the (BASE, QUOTE) liquidity each of them provides
+		fn providers_paged(
+			market: (u8, u8),
+			offset: u32,
+			limit: u32,
+		) -> Vec<(AccountId, (u128, u128))>;
+
+		/// Lists all markets an asset is traded in
+		///
+		/// # Arguments:
+		/// asset: The AssetId to look up
+		///
+		/// # Returns:
+		/// Every market which has the asset as either BASE or QUOTE
+		fn markets_for_asset(asset: u8) -> Vec<(u8, u8)>;
+
+		/// Reports whether a market is in a healthy state
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The health report of the market, None if it does not exist
+		fn pool_health(market: (u8, u8)) -> Option<PoolHealth<u128>>;
+
+		/// Gets all information about a market in a single call
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The reserves, collected fees and fee asset of the market, None if it does not exist
+		fn market_info(market: (u8, u8)) -> Option<MarketDetails<u128>>;
+
+		/// Gets the fees of a market which the next payout distributes
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The collected (BASE, QUOTE) fees of the market, None if it does not exist
+		fn pending_payout_total(market: (u8, u8)) -> Option<(u128, u128)>;
+
+		/// Estimates the annual yield of providing liquidity to a market
+		/// from the fees collected since the last payout
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The estimated APR saturating at 100%, None if the market does not exist
+		fn estimated_apr(market: (u8, u8)) -> Option<Perbill>;
+
+		/// Gets the display name of a market
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// The display name, None if the market has not been named
+		fn market_metadata(market: (u8, u8)) -> Option<Vec<u8>>;
+
+		/// Computes the minimum output to accept for a trade, given a slippage tolerance
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// account: The trader, whose taker fee is quoted
+		/// order_type: Whether the BASE asset is bought or sold
+		/// amount_in: The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+		/// slippage: The tolerated relative deviation from the quoted output
+		///
+		/// # Returns:
+		/// The quoted output reduced by the slippage, None if the market does not exist
+		fn min_out_for_slippage(
+			market: (u8, u8),
+			account: AccountId,
+			order_type: OrderType,
+			amount_in: u128,
+			slippage: Perbill,
+		) -> Option<u128>;
+
+		/// Computes the input a trade requires to receive exactly a given output
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// order_type: Whether the BASE asset is bought or sold
+		/// amount_out: The amount of BASE asset to buy, or QUOTE asset to receive when selling
+		///
+		/// # Returns:
+		/// The fee-inclusive input,
+		/// None if the market does not exist or amount_out exceeds its reserves
+		fn amount_in_for_exact_out(
+			market: (u8, u8),
+			order_type: OrderType,
+			amount_out: u128,
+		) -> Option<u128>;
+
+		/// Computes the impermanent loss of a liquidity provider versus holding his deposit.
+		/// The entry price is implied by the deposited amounts, so none is stored
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// account: The liquidity provider
+		///
+		/// # Returns:
+		/// The relative loss at the current price,
+		/// None if the market does not exist or the account provides no liquidity in it
+		fn impermanent_loss(market: (u8, u8), account: AccountId) -> Option<Perbill>;
+
+		/// Gets the fees ever paid out to a liquidity provider of a market
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		/// account: The liquidity provider
+		///
+		/// # Returns:
+		/// The (BASE, QUOTE) fees paid out to the account
+		fn lifetime_fees(market: (u8, u8), account: AccountId) -> (u128, u128);
+
+		/// Compares the cross price implied by two markets sharing an asset
+		/// against the price of the market directly trading the other two assets
+		///
+		/// # Arguments:
+		/// market_a: (BASE AssetId, QUOTE AssetId) of the first market
+		/// market_b: (BASE AssetId, QUOTE AssetId) of the second market
+		///
+		/// # Returns:
+		/// The relative spread between the implied and the direct price,
+		/// None if the markets share no asset or there is no direct market
+		fn arbitrage_spread(market_a: (u8, u8), market_b: (u8, u8)) -> Option<Perbill>;
+
+		/// Gets the accounts holding the reserves and the fees of all markets
+		///
+		/// # Returns:
+		/// (pool account, pool fee account)
+		fn pool_accounts() -> (AccountId, AccountId);
+
+		/// Gets who created a market and when
+		///
+		/// # Arguments:
+		/// market: (BASE AssetId, QUOTE AssetId)
+		///
+		/// # Returns:
+		/// (creator, block of creation), None if the market does not exist
+		fn market_origin(market: (u8, u8)) -> Option<(AccountId, u64)>;
 	}
 }
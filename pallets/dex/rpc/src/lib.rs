@@ -1,16 +1,26 @@
+use codec::Codec;
+use futures::{future, FutureExt, Stream, StreamExt};
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
 	types::error::{CallError, ErrorObject},
+	PendingSubscription,
 };
+use pallet_dex::{MarketDetails, OrderType, PoolHealth};
 use pallet_dex_runtime_api::DexRuntimeApi;
-use sp_api::{BlockId, ProvideRuntimeApi};
+use sc_client_api::{BlockImportNotification, BlockchainEvents};
+use sc_rpc::SubscriptionTaskExecutor;
+use sp_api::{ApiExt, BlockId, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
-use sp_runtime::traits::Block as BlockT;
+use sp_core::U256;
+use sp_runtime::{traits::Block as BlockT, PerThing, Perbill};
 use std::sync::Arc;
 
+/// The maximum number of decimal places of a decimal price
+pub const MAX_PRICE_PRECISION: u32 = 38;
+
 #[rpc(client, server)]
-pub trait DexApi {
+pub trait DexApi<AccountId> {
 	/// Get the current price of a market
 	///
 	/// # Arguments:
@@ -21,36 +31,498 @@ pub trait DexApi {
 	/// Else some error
 	#[method(name = "dex_currentPrice")]
 	async fn current_price(&self, market: (u8, u8)) -> RpcResult<f64>;
+
+	/// Get the current prices of many markets in a single call
+	///
+	/// # Arguments:
+	/// markets: The markets to query
+	///
+	/// # Returns:
+	/// If Ok, the current price of every market in the requested order,
+	/// None for markets which do not exist
+	/// Else some error
+	#[method(name = "dex_currentPrices")]
+	async fn current_prices(&self, markets: Vec<(u8, u8)>) -> RpcResult<Vec<Option<f64>>>;
+
+	/// Get the exact price of a market as a decimal string,
+	/// which does not suffer from the rounding of f64 for large reserves
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	/// precision: The number of decimal places, at most MAX_PRICE_PRECISION
+	///
+	/// # Returns:
+	/// If Ok, the ratio of the reserves truncated to the precision,
+	/// None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_currentPriceDecimal")]
+	async fn current_price_decimal(
+		&self,
+		market: (u8, u8),
+		precision: u32,
+	) -> RpcResult<Option<String>>;
+
+	/// Get all markets in which an account provides liquidity
+	///
+	/// # Arguments:
+	/// account: The liquidity provider
+	///
+	/// # Returns:
+	/// If Ok, the markets and the (BASE, QUOTE) liquidity provided in each
+	/// Else some error
+	#[method(name = "dex_positionsOf")]
+	async fn positions_of(&self, account: AccountId) -> RpcResult<Vec<((u8, u8), (u128, u128))>>;
+
+	/// Get all liquidity providers of a market
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// If Ok, the accounts and the (BASE, QUOTE) liquidity each of them provides
+	/// Else some error
+	#[method(name = "dex_providers")]
+	async fn providers(&self, market: (u8, u8)) -> RpcResult<Vec<(AccountId, (u128, u128))>>;
+
+	/// Get a page of the liquidity providers of a market
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	/// offset: The number of liquidity providers to skip
+	/// limit: The maximum number of liquidity providers to return
+	///
+	/// # Returns:
+	/// If Ok, the accounts and the (BASE, QUOTE) liquidity each of them provides
+	/// Else some error
+	#[method(name = "dex_providersPaged")]
+	async fn providers_paged(
+		&self,
+		market: (u8, u8),
+		offset: u32,
+		limit: u32,
+	) -> RpcResult<Vec<(AccountId, (u128, u128))>>;
+
+	/// Get the health report of a market
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// If Ok, the health report or None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_poolHealth")]
+	async fn pool_health(&self, market: (u8, u8)) -> RpcResult<Option<PoolHealth<u128>>>;
+
+	/// Get all information about a market
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// If Ok, the reserves, collected fees and fee asset or None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_marketInfo")]
+	async fn market_info(&self, market: (u8, u8)) -> RpcResult<Option<MarketDetails<u128>>>;
+
+	/// Get the fees of a market which the next payout distributes
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// If Ok, the collected (BASE, QUOTE) fees or None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_pendingPayoutTotal")]
+	async fn pending_payout_total(&self, market: (u8, u8)) -> RpcResult<Option<(u128, u128)>>;
+
+	/// Get the estimated annual yield of providing liquidity to a market.
+	/// Annualizes the fees collected since the last payout, assuming the runtimes BlocksPerYear
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// If Ok, the APR as a fraction, where 1.0 is 100%, or None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_estimatedApr")]
+	async fn estimated_apr(&self, market: (u8, u8)) -> RpcResult<Option<f64>>;
+
+	/// Get the minimum output to pass as slippage guard for a trade
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	/// account: The trader, whose taker fee is quoted
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+	/// slippage: The tolerated deviation from the quoted output as a fraction, where 1.0 is 100%
+	///
+	/// # Returns:
+	/// If Ok, the quoted output reduced by the slippage or None if the market does not exist
+	/// Else some error
+	#[method(name = "dex_minOutForSlippage")]
+	async fn min_out_for_slippage(
+		&self,
+		market: (u8, u8),
+		account: AccountId,
+		order_type: OrderType,
+		amount_in: u128,
+		slippage: f64,
+	) -> RpcResult<Option<u128>>;
+
+	/// Get the input a trade requires to receive exactly a given output
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_out: The amount of BASE asset to buy, or QUOTE asset to receive when selling
+	///
+	/// # Returns:
+	/// If Ok, the fee-inclusive input,
+	/// or None if the market does not exist or amount_out exceeds its reserves
+	/// Else some error
+	#[method(name = "dex_amountInForExactOut")]
+	async fn amount_in_for_exact_out(
+		&self,
+		market: (u8, u8),
+		order_type: OrderType,
+		amount_out: u128,
+	) -> RpcResult<Option<u128>>;
+
+	/// Get the spread between the cross price implied by two markets sharing an asset
+	/// and the price of the market directly trading the other two assets
+	///
+	/// # Arguments:
+	/// market_a: (BASE AssetId, QUOTE AssetId) of the first market
+	/// market_b: (BASE AssetId, QUOTE AssetId) of the second market
+	///
+	/// # Returns:
+	/// If Ok, the spread as a fraction, where 1.0 is 100%,
+	/// or None if the markets share no asset or there is no direct market
+	/// Else some error
+	#[method(name = "dex_arbitrageSpread")]
+	async fn arbitrage_spread(
+		&self,
+		market_a: (u8, u8),
+		market_b: (u8, u8),
+	) -> RpcResult<Option<f64>>;
+
+	/// Subscribe to the price of a market.
+	/// A new price is pushed whenever a new best block changed the price dex_currentPrice returns,
+	/// e.g. with a trade
+	///
+	/// # Arguments:
+	/// market: (BASE AssetId, QUOTE AssetId)
+	///
+	/// # Returns:
+	/// The stream of prices of the market, as returned by dex_currentPrice
+	#[subscription(
+		name = "dex_subscribePrice",
+		unsubscribe = "dex_unsubscribePrice",
+		item = f64
+	)]
+	fn subscribe_price(&self, market: (u8, u8));
 }
 
 pub struct Dex<C, Block> {
 	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
 	_market: std::marker::PhantomData<Block>,
 }
 
 impl<C, Block> Dex<C, Block> {
-	pub fn new(client: Arc<C>) -> Self {
-		Self { client, _market: Default::default() }
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _market: Default::default() }
 	}
 }
 
 #[async_trait]
-impl<C, Block> DexApiServer for Dex<C, Block>
+impl<C, Block, AccountId> DexApiServer<AccountId> for Dex<C, Block>
 where
 	Block: BlockT,
-	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-	C::Api: DexRuntimeApi<Block>,
+	AccountId: Codec + Send + Sync + 'static,
+	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync,
+	C::Api: DexRuntimeApi<Block, AccountId>,
 {
 	async fn current_price(&self, market: (u8, u8)) -> RpcResult<f64> {
 		let api = self.client.runtime_api();
 
 		// Just take the latest best block
 		let at = BlockId::hash(self.client.info().best_hash);
-		let (numerator, denominator) =
-			api.current_price(&at, market).map_err(|_e| Error::RuntimeCall)?;
+		let price = api.current_price(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(price_to_f64(price))
+	}
+
+	async fn current_prices(&self, markets: Vec<(u8, u8)>) -> RpcResult<Vec<Option<f64>>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let prices = api.current_prices(&at, markets).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(prices.into_iter().map(|opt_price| opt_price.map(price_to_f64)).collect())
+	}
+
+	async fn current_price_decimal(
+		&self,
+		market: (u8, u8),
+		precision: u32,
+	) -> RpcResult<Option<String>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let market_info = api.market_info(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(market_info.and_then(|market_info| {
+			ratio_to_decimal(market_info.quote_balance, market_info.base_balance, precision)
+		}))
+	}
+
+	async fn positions_of(&self, account: AccountId) -> RpcResult<Vec<((u8, u8), (u128, u128))>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let positions = api.positions_of(&at, account).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(positions)
+	}
+
+	async fn providers(&self, market: (u8, u8)) -> RpcResult<Vec<(AccountId, (u128, u128))>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let providers = api.providers(&at, market).map_err(|_e| Error::RuntimeCall)?;
 
-		Ok(numerator as f64 / denominator as f64)
+		Ok(providers)
 	}
+
+	async fn providers_paged(
+		&self,
+		market: (u8, u8),
+		offset: u32,
+		limit: u32,
+	) -> RpcResult<Vec<(AccountId, (u128, u128))>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let providers = api
+			.providers_paged(&at, market, offset, limit)
+			.map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(providers)
+	}
+
+	async fn pool_health(&self, market: (u8, u8)) -> RpcResult<Option<PoolHealth<u128>>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let health = api.pool_health(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(health)
+	}
+
+	async fn market_info(&self, market: (u8, u8)) -> RpcResult<Option<MarketDetails<u128>>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let market_info = api.market_info(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(market_info)
+	}
+
+	async fn pending_payout_total(&self, market: (u8, u8)) -> RpcResult<Option<(u128, u128)>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let total = api.pending_payout_total(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(total)
+	}
+
+	async fn estimated_apr(&self, market: (u8, u8)) -> RpcResult<Option<f64>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let apr = api.estimated_apr(&at, market).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(apr.map(|apr: Perbill| apr.deconstruct() as f64 / Perbill::ACCURACY as f64))
+	}
+
+	async fn min_out_for_slippage(
+		&self,
+		market: (u8, u8),
+		account: AccountId,
+		order_type: OrderType,
+		amount_in: u128,
+		slippage: f64,
+	) -> RpcResult<Option<u128>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let min_out = api
+			.min_out_for_slippage(
+				&at,
+				market,
+				account,
+				order_type,
+				amount_in,
+				Perbill::from_float(slippage),
+			)
+			.map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(min_out)
+	}
+
+	async fn amount_in_for_exact_out(
+		&self,
+		market: (u8, u8),
+		order_type: OrderType,
+		amount_out: u128,
+	) -> RpcResult<Option<u128>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let amount_in = api
+			.amount_in_for_exact_out(&at, market, order_type, amount_out)
+			.map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(amount_in)
+	}
+
+	async fn arbitrage_spread(
+		&self,
+		market_a: (u8, u8),
+		market_b: (u8, u8),
+	) -> RpcResult<Option<f64>> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::hash(self.client.info().best_hash);
+		ensure_api_version(&*api, &at)?;
+		let spread =
+			api.arbitrage_spread(&at, market_a, market_b).map_err(|_e| Error::RuntimeCall)?;
+
+		Ok(spread.map(|spread: Perbill| spread.deconstruct() as f64 / Perbill::ACCURACY as f64))
+	}
+
+	fn subscribe_price(&self, pending: PendingSubscription, market: (u8, u8)) {
+		let client = self.client.clone();
+		let prices = price_updates(self.client.import_notification_stream(), move |hash| {
+			let api = client.runtime_api();
+			let at = BlockId::hash(hash);
+			ensure_api_version(&*api, &at).ok()?;
+
+			// The same price dex_currentPrice returns, None if the market does not exist
+			api.current_prices(&at, vec![market]).ok()?.pop().flatten()
+		});
+
+		let fut = async move {
+			if let Some(mut sink) = pending.accept() {
+				sink.pipe_from_stream(prices).await;
+			}
+		};
+		self.executor.spawn("dex-price-subscription", Some("rpc"), fut.boxed());
+	}
+}
+
+/// Formats a ratio as a decimal string, using exact integer arithmetic
+///
+/// # Arguments:
+/// numerator: The numerator of the ratio
+/// denominator: The denominator of the ratio
+/// precision: The number of decimal places, capped at MAX_PRICE_PRECISION
+///
+/// # Returns:
+/// If Some, the ratio truncated to the precision
+/// Else None if the denominator is zero
+pub fn ratio_to_decimal(numerator: u128, denominator: u128, precision: u32) -> Option<String> {
+	if denominator == 0 {
+		return None
+	}
+
+	let mut decimal = (numerator / denominator).to_string();
+	let precision = precision.min(MAX_PRICE_PRECISION);
+	if precision > 0 {
+		decimal.push('.');
+	}
+
+	// Long division, the remainder times 10 may exceed 128 bits
+	let mut remainder = U256::from(numerator % denominator);
+	let denominator = U256::from(denominator);
+	for _ in 0..precision {
+		remainder = remainder * 10;
+		decimal.push_str(&(remainder / denominator).to_string());
+		remainder = remainder % denominator;
+	}
+
+	Some(decimal)
+}
+
+/// Checks that the runtime at a block implements version 2 of the DexRuntimeApi,
+/// as runtimes before it only provide current_price
+fn ensure_api_version<Block, Api, AccountId>(api: &Api, at: &BlockId<Block>) -> Result<(), Error>
+where
+	Block: BlockT,
+	Api: ApiExt<Block> + DexRuntimeApi<Block, AccountId>,
+	AccountId: Codec,
+{
+	let is_supported = api
+		.has_api_with::<dyn DexRuntimeApi<Block, AccountId>, _>(at, |version| version >= 2)
+		.map_err(|_e| Error::RuntimeCall)?;
+	if !is_supported {
+		return Err(Error::UnsupportedRuntimeApi)
+	}
+
+	Ok(())
+}
+
+/// Converts a (numerator, denominator) price of the runtime API into the price of dex_currentPrice
+fn price_to_f64((numerator, denominator): (u128, u128)) -> f64 {
+	numerator as f64 / denominator as f64
+}
+
+/// Turns the block import notifications into the price updates of a market.
+/// The prices are those of dex_currentPrice, so a subscriber and a caller
+/// see the same price for the same block
+///
+/// # Arguments:
+/// notifications: The block import notifications of the client
+/// price_at: Gets the (numerator, denominator) price of the market at a block,
+/// None if the market does not exist there
+///
+/// # Returns:
+/// The stream of prices, with an item for every new best block which changed the price
+fn price_updates<Block, S, F>(notifications: S, mut price_at: F) -> impl Stream<Item = f64> + Unpin
+where
+	Block: BlockT,
+	S: Stream<Item = BlockImportNotification<Block>> + Unpin,
+	F: FnMut(Block::Hash) -> Option<(u128, u128)>,
+{
+	let mut last_price = None;
+
+	notifications.filter_map(move |notification| {
+		// Blocks imported on a fork do not change what dex_currentPrice returns
+		if !notification.is_new_best {
+			return future::ready(None)
+		}
+		let price = price_at(notification.hash);
+		if price.is_none() || price == last_price {
+			return future::ready(None)
+		}
+		last_price = price;
+
+		future::ready(price.map(price_to_f64))
+	})
 }
 
 /// Just a quick error type
@@ -58,6 +530,8 @@ where
 pub enum Error {
 	#[error("Runtime call returned an error")]
 	RuntimeCall,
+	#[error("Runtime does not implement version 2 of the DexRuntimeApi")]
+	UnsupportedRuntimeApi,
 }
 
 impl From<Error> for JsonRpseeError {
@@ -66,3 +540,89 @@ impl From<Error> for JsonRpseeError {
 		JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(1234, message, None::<()>)))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use sp_consensus::BlockOrigin;
+	use sp_runtime::traits::Header as HeaderT;
+
+	use super::*;
+
+	#[test]
+	fn ratio_to_decimal_large_reserves() {
+		let quote_balance = 123_456_789_012_345_678_901_234_567;
+		let base_balance = 1_000_000_000_000_000_000;
+
+		assert_eq!(
+			ratio_to_decimal(quote_balance, base_balance, 18).unwrap(),
+			"123456789.012345678901234567"
+		);
+		assert_eq!(ratio_to_decimal(quote_balance, base_balance, 2).unwrap(), "123456789.01");
+		assert_eq!(ratio_to_decimal(quote_balance, base_balance, 0).unwrap(), "123456789");
+		// f64 can't represent the exact ratio
+		assert_ne!(
+			(quote_balance as f64 / base_balance as f64).to_string(),
+			"123456789.012345678901234567"
+		);
+
+		// The remainder times 10 exceeds 128 bits
+		assert_eq!(ratio_to_decimal(u128::MAX - 1, u128::MAX, 3).unwrap(), "0.999");
+		assert_eq!(ratio_to_decimal(1, 3, 5).unwrap(), "0.33333");
+		assert_eq!(ratio_to_decimal(1, 0, 5), None);
+	}
+
+	type Block = sp_runtime::testing::Block<sp_runtime::testing::ExtrinsicWrapper<u64>>;
+
+	// The notification of an imported block, whose number tells the blocks apart
+	fn imported(number: u64, is_new_best: bool) -> BlockImportNotification<Block> {
+		let header = sp_runtime::testing::Header::new_from_number(number);
+
+		BlockImportNotification {
+			hash: header.hash(),
+			origin: BlockOrigin::NetworkBroadcast,
+			header,
+			is_new_best,
+			tree_route: None,
+		}
+	}
+
+	#[test]
+	fn price_updates_from_import_notifications() {
+		let (notifications, stream) = futures::channel::mpsc::unbounded();
+		// The price of the market at each block, as the runtime API returns it
+		let prices_at = [
+			None,
+			Some((20_000, 10_000)),
+			// A block imported on a fork
+			Some((30_000, 10_000)),
+			// A trade which did not change the price
+			Some((20_000, 10_000)),
+			Some((10_000, 10_000)),
+			// The market has been removed
+			None,
+		];
+		let mut prices = price_updates(stream, move |hash| {
+			(0..prices_at.len())
+				.find(|number| imported(*number as u64, true).hash == hash)
+				.and_then(|number| prices_at[number])
+		});
+
+		// Nothing is pushed before the market exists
+		notifications.unbounded_send(imported(0, true)).unwrap();
+		notifications.unbounded_send(imported(1, true)).unwrap();
+		// The same price dex_currentPrice returns
+		assert_eq!(block_on(prices.next()), Some(price_to_f64((20_000, 10_000))));
+		assert_eq!(price_to_f64((20_000, 10_000)), 2.0);
+
+		// Neither the fork nor the unchanged price is pushed
+		notifications.unbounded_send(imported(2, false)).unwrap();
+		notifications.unbounded_send(imported(3, true)).unwrap();
+		notifications.unbounded_send(imported(4, true)).unwrap();
+		assert_eq!(block_on(prices.next()), Some(1.0));
+
+		notifications.unbounded_send(imported(5, true)).unwrap();
+		drop(notifications);
+		assert_eq!(block_on(prices.next()), None);
+	}
+}
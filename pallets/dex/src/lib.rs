@@ -17,30 +17,54 @@
 //! withdraw_liquidity: Allows the user to remove his liquidity from a pool
 //! buy: Allows the user to exchange the QUOTE asset for the BASE asset
 //! sell: Allows the user to exchange the BASE asset for the QUOTE asset
+//! buy_to / sell_to: Like buy and sell, but the output is sent to another account
+//! swap_batch: Executes several buys and sells atomically
+//! buy_until_price / sell_until_price: Like buy and sell, but stop at a limit price
+//! remove_market: Allows the sole remaining liquidity provider to tear down a pool
 //!
 //! # Hooks:
-//! The offchain worker calls a function every 10 blocks
-//! which perform the payout to the liquidity providers as a reward
+//! Every PayoutInterval blocks, on_initialize compares the recorded reserves with the
+//! balances of the pool account and performs the payout to the liquidity providers as a reward.
+//! Markets whose collected fees exceed MaxUndistributedFees are paid out
+//! in on_initialize of the next block
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
+use core::cmp::Ordering;
 use frame_support::{
+	dispatch::WithPostDispatchInfo,
+	ensure,
 	inherent::Vec,
 	traits::{
-		tokens::fungibles::{Inspect, Transfer},
+		tokens::{
+			fungibles::{Inspect, Transfer},
+			WithdrawConsequence,
+		},
 		Get,
 	},
-	transactional, PalletId,
+	transactional,
+	weights::Weight,
+	PalletId,
 };
 pub use pallet::*;
-use sp_runtime::{traits::Zero, DispatchError};
+use sp_core::U256;
+use sp_runtime::{
+	traits::{Hash, One, Saturating, UniqueSaturatedInto, Zero},
+	DispatchError, PerThing, Perbill,
+};
 
 use sp_runtime::traits::AccountIdConversion;
-use types::*;
+pub use types::*;
 
 mod types;
 
+/// The denominator of the prices returned by current_price
+pub const PRICE_DENOMINATOR: u128 = 10_000;
+
+/// The number of decimals the prices compared by arbitrage_spread are carried out in
+const CROSS_PRICE_DECIMALS: usize = 18;
+
 #[cfg(test)]
 mod tests;
 
@@ -64,12 +88,154 @@ pub mod pallet {
 		#[pallet::constant]
 		type TakerFee: Get<(u32, u32)>;
 
+		/// The maximum fee a single trade pays, regardless of its size
+		#[pallet::constant]
+		type MaxAbsoluteFee: Get<BalanceOf<Self>>;
+
+		/// Lower taker fees for traders with a high volume in a market, as
+		/// (QUOTE volume threshold, fee numerator over the TakerFee denominator).
+		/// The lowest fee whose threshold the traded volume reached applies. Empty disables tiers
+		#[pallet::constant]
+		type FeeTiers: Get<Vec<(BalanceOf<Self>, u32)>>;
+
+		/// The number of blocks after its creation in which a market charges no taker fee,
+		/// to bootstrap its volume
+		#[pallet::constant]
+		type FeeGracePeriod: Get<Self::BlockNumber>;
+
 		/// The treasury's pallet id, used for deriving its sovereign account ID.
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
 		/// The type that enables currency transfers
 		type Currencies: Transfer<Self::AccountId, Balance = u128, AssetId = u8>;
+
+		/// The maximum size of a single trade as a fraction of the pools reserve
+		/// of the asset being put into the pool.
+		/// None disables the check.
+		#[pallet::constant]
+		type MaxTradeFraction: Get<Option<Perbill>>;
+
+		/// The maximum price impact of a single trade, enforced regardless of the minimums
+		/// a user passes. The price impact is the relative shortfall of the output versus
+		/// trading the same amount at the marginal price. One means no cap
+		#[pallet::constant]
+		type GlobalMaxSlippage: Get<Perbill>;
+
+		/// The maximum slippage of a buy or sell, which take no minimum output,
+		/// so they are not left without any slippage protection. Unlike the GlobalMaxSlippage,
+		/// the shortfall includes the taker fee the trader pays. One means no cap
+		#[pallet::constant]
+		type DefaultMaxSlippage: Get<Perbill>;
+
+		/// The minimum amount a taker has to put into a pool with a single trade
+		#[pallet::constant]
+		type MinTradeAmount: Get<BalanceOf<Self>>;
+
+		/// A withdrawal which would leave a reserve of a pool below this amount
+		/// withdraws everything and removes the market instead, so no unusable dust pool remains
+		#[pallet::constant]
+		type DustThreshold: Get<BalanceOf<Self>>;
+
+		/// Whether an event is emitted for every single trade.
+		/// Pool creation and liquidity events are always emitted.
+		#[pallet::constant]
+		type EmitTradeEvents: Get<bool>;
+
+		/// Whether selling in a market which collects the fee in the input asset
+		/// collects it in the QUOTE output instead, so all fees are reported in QUOTE
+		#[pallet::constant]
+		type SellFeeInQuote: Get<bool>;
+
+		/// The maximum number of markets a single asset can be part of
+		#[pallet::constant]
+		type MaxMarketsPerAsset: Get<u32>;
+
+		/// The share of every taker fee which is taken as protocol fee
+		/// instead of being paid out to the liquidity providers
+		#[pallet::constant]
+		type ProtocolFeeShare: Get<Perbill>;
+
+		/// The account receiving the protocol fees
+		type TreasuryAccount: Get<Self::AccountId>;
+
+		/// The number of blocks between two payouts to the liquidity providers.
+		/// Must not be zero
+		#[pallet::constant]
+		type PayoutInterval: Get<Self::BlockNumber>;
+
+		/// The collected fees of a market in either asset, beyond which they are paid out
+		/// in the next block instead of waiting for the PayoutInterval
+		#[pallet::constant]
+		type MaxUndistributedFees: Get<BalanceOf<Self>>;
+
+		/// The number of blocks between requesting and executing a withdrawal of liquidity
+		#[pallet::constant]
+		type WithdrawalDelay: Get<Self::BlockNumber>;
+
+		/// The expected number of blocks produced in a year, used to annualize yields
+		#[pallet::constant]
+		type BlocksPerYear: Get<Self::BlockNumber>;
+
+		/// The origin which may configure markets
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The origin which may create markets, resolving to the account providing the liquidity
+		type CreatePoolOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+
+		/// The maximum length of a markets display name
+		#[pallet::constant]
+		type NameLimit: Get<u32>;
+
+		/// The maximum number of swaps in a single swap_batch
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		/// Whether only the pairs in AllowedPairs may be created as markets
+		#[pallet::constant]
+		type RestrictPairs: Get<bool>;
+
+		/// The maximum number of liquidity providers force_close_market refunds,
+		/// which bounds its weight
+		#[pallet::constant]
+		type MaxForceCloseProviders: Get<u32>;
+
+		/// The maximum number of liquidity providers of a single market.
+		/// Providers which already provided to the market may always top up
+		#[pallet::constant]
+		type MaxProvidersPerMarket: Get<u32>;
+
+		/// The maximum number of markets a single payout processes.
+		/// The next payout resumes after the last processed market, so none is starved
+		#[pallet::constant]
+		type MaxPayoutMarkets: Get<u32>;
+
+		/// The number of blocks liquidity has to be provided for, since the last deposit,
+		/// before it qualifies for fee payouts. Discourages just-in-time liquidity
+		#[pallet::constant]
+		type MinRewardAge: Get<Self::BlockNumber>;
+
+		/// The share of the collected fees of a market which is paid out to its creator
+		#[pallet::constant]
+		type CreatorRewardShare: Get<Perbill>;
+
+		/// The share of the collected fees of a market which is paid out to its liquidity providers.
+		/// Together with the CreatorRewardShare it must not exceed one,
+		/// whatever is left of the fees is accrued as protocol fee
+		#[pallet::constant]
+		type ProviderRewardShare: Get<Perbill>;
+
+		/// Balance of the pool fee account which is owed to nobody, e.g. left behind by failed
+		/// payouts, is swept to the TreasuryAccount once it exceeds this amount of an asset.
+		/// None disables the sweep
+		#[pallet::constant]
+		type FeeSweepThreshold: Get<Option<BalanceOf<Self>>>;
+
+		/// The maximum ratio between the larger and the smaller reserve of a pool
+		/// a trade may leave behind, as a pool with an extreme ratio is effectively broken.
+		/// None disables the check
+		#[pallet::constant]
+		type MaxReserveRatio: Get<Option<u128>>;
 	}
 
 	#[pallet::pallet]
@@ -88,6 +254,12 @@ pub mod pallet {
 	/// Used for rewarding liquidity providers from the collected taker fees.
 	///
 	/// Maps Market and Account => (BASE Balance, QUOTE Balance)
+	///
+	/// Anyone can pick the AccountId keys, so they are hashed with the cryptographic
+	/// Blake2_128Concat, as a Twox64Concat would let an attacker craft colliding prefixes
+	/// which unbalance the trie. Both hashers are reversible, as the payout and the
+	/// runtime api iterate the providers of a market and decode their accounts from the keys.
+	/// Changing a hasher requires a migration re-inserting every entry
 	#[pallet::storage]
 	#[pallet::getter(fn liq_provision_pool)]
 	pub type LiqProvisionPool<T: Config> = StorageDoubleMap<
@@ -100,6 +272,154 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// Reverse index of the markets an asset is traded in,
+	/// used for discovering routes between assets.
+	///
+	/// Maps AssetId => Markets which have the asset as either BASE or QUOTE
+	#[pallet::storage]
+	#[pallet::getter(fn markets_per_asset)]
+	pub type MarketsPerAsset<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		AssetIdOf<T>,
+		BoundedVec<Market<T>, T::MaxMarketsPerAsset>,
+		ValueQuery,
+	>;
+
+	/// Stores the display name of a market, purely cosmetic for front-ends
+	///
+	/// Maps Market => Name
+	#[pallet::storage]
+	#[pallet::getter(fn market_metadata)]
+	pub type MarketMetadata<T: Config> =
+		StorageMap<_, Blake2_128Concat, Market<T>, BoundedVec<u8, T::NameLimit>, OptionQuery>;
+
+	/// The pairs which may be created as markets, if RestrictPairs is set
+	///
+	/// Maps Market => ()
+	#[pallet::storage]
+	pub type AllowedPairs<T: Config> = StorageMap<_, Blake2_128Concat, Market<T>, (), OptionQuery>;
+
+	/// The accounts which pay no taker fee, e.g. protocol-owned market makers
+	///
+	/// Maps AccountId => ()
+	#[pallet::storage]
+	pub type FeeExempt<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// The protocol fees of a market which have not yet been collected by the treasury.
+	/// They are held by the pool fee account until then
+	///
+	/// Maps Market => (BASE fees, QUOTE fees)
+	#[pallet::storage]
+	#[pallet::getter(fn protocol_fees)]
+	pub type ProtocolFees<T: Config> =
+		StorageMap<_, Blake2_128Concat, Market<T>, (BalanceOf<T>, BalanceOf<T>), ValueQuery>;
+
+	/// The fractional taker fees of a market which did not yet add up to a whole unit,
+	/// in units of 1 / the TakerFee denominator. They are carried over to the next trade
+	///
+	/// Maps Market => (BASE remainder, QUOTE remainder)
+	#[pallet::storage]
+	#[pallet::getter(fn fee_remainders)]
+	pub type FeeRemainders<T: Config> =
+		StorageMap<_, Blake2_128Concat, Market<T>, (u128, u128), ValueQuery>;
+
+	/// The cumulative volume a trader traded in a market, denominated in its QUOTE asset,
+	/// which decides the FeeTiers of the trader
+	///
+	/// Maps (Market, AccountId) => QUOTE volume
+	#[pallet::storage]
+	#[pallet::getter(fn traded_volume)]
+	pub type TradedVolume<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Market<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// The fees ever paid out to a liquidity provider of a market
+	///
+	/// Maps (Market, AccountId) => (BASE fees, QUOTE fees)
+	#[pallet::storage]
+	#[pallet::getter(fn lifetime_fees)]
+	pub type LifetimeFees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Market<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		(BalanceOf<T>, BalanceOf<T>),
+		ValueQuery,
+	>;
+
+	/// The block of the last deposit of a liquidity provider into a market
+	///
+	/// Maps (Market, AccountId) => BlockNumber
+	#[pallet::storage]
+	#[pallet::getter(fn provided_since)]
+	pub type ProvidedSince<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Market<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		T::BlockNumber,
+		ValueQuery,
+	>;
+
+	/// The number of liquidity providers of a market
+	///
+	/// Maps Market => u32
+	#[pallet::storage]
+	#[pallet::getter(fn provider_count)]
+	pub type ProviderCount<T: Config> = StorageMap<_, Blake2_128Concat, Market<T>, u32, ValueQuery>;
+
+	/// The withdrawals of liquidity providers which wait for the WithdrawalDelay to pass
+	///
+	/// Maps (Market, AccountId) => PendingWithdrawal
+	#[pallet::storage]
+	#[pallet::getter(fn pending_withdrawal)]
+	pub type PendingWithdrawals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Market<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		PendingWithdrawal<T>,
+		OptionQuery,
+	>;
+
+	/// The number of payouts to liquidity providers which failed and have been skipped
+	#[pallet::storage]
+	#[pallet::getter(fn failed_payout_count)]
+	pub type FailedPayoutCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The market the last payout to liquidity providers stopped at.
+	/// The next payout continues with the market after it in storage order
+	#[pallet::storage]
+	#[pallet::getter(fn last_paid_out_market)]
+	pub type LastPaidOutMarket<T: Config> = StorageValue<_, Market<T>, OptionQuery>;
+
+	/// The first block whose fees have not been paid out or reinvested yet,
+	/// which starts the lookback window of estimated_apr.
+	/// Unset until the first payout of a market, which then collects since its creation
+	///
+	/// Maps Market => BlockNumber
+	#[pallet::storage]
+	pub type FeesCollectedSince<T: Config> =
+		StorageMap<_, Blake2_128Concat, Market<T>, T::BlockNumber, OptionQuery>;
+
+	/// The markets whose collected fees exceeded MaxUndistributedFees,
+	/// so they are paid out in the next block instead of waiting for the PayoutInterval
+	///
+	/// Maps Market => ()
+	#[pallet::storage]
+	pub type EarlyPayoutDue<T: Config> =
+		StorageMap<_, Blake2_128Concat, Market<T>, (), OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -112,6 +432,13 @@ pub mod pallet {
 		/// 3: Liquidity for QUOTE asset
 		PoolCreated(T::AccountId, Market<T>, BalanceOf<T>, BalanceOf<T>),
 
+		/// A market has been registered without any liquidity
+		///
+		/// # Fields:
+		/// 0: Who registered the market
+		/// 1: The market identifier
+		MarketRegistered(T::AccountId, Market<T>),
+
 		/// Emitted when liquidity has been added to a pool
 		///
 		/// # Fields:
@@ -147,6 +474,108 @@ pub mod pallet {
 		/// 2: The amount of BASE asset that was sold
 		/// 3: The amount of QUOTE asset received
 		Sold(T::AccountId, Market<T>, BalanceOf<T>, BalanceOf<T>),
+
+		/// A market has been removed and its reserves returned
+		///
+		/// # Fields:
+		/// 0: The account which removed the market
+		/// 1: The market which has been removed
+		/// 2: The amount of BASE asset returned
+		/// 3: The amount of QUOTE asset returned
+		MarketRemoved(T::AccountId, Market<T>, BalanceOf<T>, BalanceOf<T>),
+
+		/// The asset in which the fees of a market are collected has changed
+		///
+		/// # Fields:
+		/// 0: The market which has been configured
+		/// 1: The new fee asset
+		FeeAssetSet(Market<T>, FeeAsset),
+
+		/// The fee payout to a liquidity provider failed and has been skipped
+		///
+		/// # Fields:
+		/// 0: The liquidity provider which has not been paid
+		/// 1: The market of the payout
+		/// 2: The reason of the failure
+		PayoutFailed(T::AccountId, Market<T>, DispatchError),
+
+		/// The display name of a market has changed
+		///
+		/// # Fields:
+		/// 0: The market which has been named
+		/// 1: The new name, empty if it has been cleared
+		MarketMetadataSet(Market<T>, Vec<u8>),
+
+		/// The enabled operations of a market have changed
+		///
+		/// # Fields:
+		/// 0: The market which has been configured
+		/// 1: The new flags
+		MarketFlagsSet(Market<T>, MarketFlags),
+
+		/// A pair has been added to or removed from the AllowedPairs
+		///
+		/// # Fields:
+		/// 0: The pair
+		/// 1: Whether the pair may be created as market
+		PairAllowanceSet(Market<T>, bool),
+
+		/// An account has been added to or removed from the FeeExempt accounts
+		///
+		/// # Fields:
+		/// 0: The account
+		/// 1: Whether the account pays no taker fee
+		FeeExemptionSet(T::AccountId, bool),
+		/// The accumulated protocol fees of a market were transferred to the treasury
+		///
+		/// # Fields:
+		/// 0: The market
+		/// 1: The collected amount of the BASE asset
+		/// 2: The collected amount of the QUOTE asset
+		ProtocolFeesCollected(Market<T>, BalanceOf<T>, BalanceOf<T>),
+
+		/// The collected fees of a market were moved into its reserves instead of being paid out
+		///
+		/// # Fields:
+		/// 0: The market
+		/// 1: The amount of the BASE asset added to the reserves
+		/// 2: The amount of the QUOTE asset added to the reserves
+		FeesReinvested(Market<T>, BalanceOf<T>, BalanceOf<T>),
+		/// A market has been closed by the AdminOrigin and its reserves refunded to all providers
+		///
+		/// # Fields:
+		/// 0: The market which has been closed
+		/// 1: The amount of BASE asset refunded
+		/// 2: The amount of QUOTE asset refunded
+		MarketForceClosed(Market<T>, BalanceOf<T>, BalanceOf<T>),
+
+		/// The recorded reserves of an asset differ from the balance of the pool account.
+		/// The pool account holds the reserves of all markets together,
+		/// so the differences are the totals over all markets trading the asset
+		///
+		/// # Fields:
+		/// 0: A market trading the mismatched asset
+		/// 1: The actual minus the recorded BASE reserves
+		/// 2: The actual minus the recorded QUOTE reserves
+		ReserveMismatch(Market<T>, i128, i128),
+
+		/// Balance of the pool fee account owed to neither the liquidity providers
+		/// nor the treasury has been swept to the TreasuryAccount
+		///
+		/// # Fields:
+		/// 0: The swept asset
+		/// 1: The swept amount
+		FeeDustSwept(AssetIdOf<T>, BalanceOf<T>),
+
+		/// A liquidity provider requested a withdrawal, which can be executed after a delay
+		///
+		/// # Fields:
+		/// 0: The account withdrawing the liquidity
+		/// 1: The market it's being withdrawn from
+		/// 2: The amount of BASE asset reserved for the withdrawal
+		/// 3: The amount of QUOTE asset reserved for the withdrawal
+		/// 4: The block from which on the withdrawal can be executed
+		WithdrawalRequested(T::AccountId, Market<T>, BalanceOf<T>, BalanceOf<T>, T::BlockNumber),
 	}
 
 	#[pallet::error]
@@ -163,20 +592,138 @@ pub mod pallet {
 		/// Some arithmetic error occurred
 		Arithmetic,
 
-		/// originates from T::Currencies::transfer basically
+		/// Any failure of a T::Currencies::transfer
 		Transfer,
+
+		/// One of the assets of a swap, or the account holding it, is frozen
+		AssetFrozen,
+
+		/// The trade exceeds the maximum allowed fraction of the pools reserve
+		TradeTooLarge,
+
+		/// The trade is below MinTradeAmount
+		TradeTooSmall,
+
+		/// The withdrawal would leave the pool without reserves, use remove_market instead
+		WouldDrainPool,
+
+		/// Only the last remaining liquidity provider can remove a market
+		NotSoleProvider,
+
+		/// The withdrawal would leave a reserve below the DustThreshold,
+		/// but other providers still have liquidity in the pool
+		WouldLeaveDust,
+
+		/// One of the assets is already part of MaxMarketsPerAsset markets
+		TooManyMarkets,
+
+		/// The initial price of a pool deviates too much from the expected price
+		UnexpectedInitialPrice,
+
+		/// The display name of a market is longer than NameLimit
+		NameTooLong,
+
+		/// A withdrawal or a swap would return less than the minimum amounts
+		SlippageExceeded,
+
+		/// A swap_batch contains more than MaxBatchSize swaps
+		BatchTooLarge,
+
+		/// A withdrawal exceeds the reserves of the market or the balance of the pool account
+		InsufficientLiquidity,
+
+		/// A quote was requested for a pool without reserves of one of its assets
+		NoLiquidity,
+
+		/// The pair is not in the AllowedPairs
+		PairNotAllowed,
+
+		/// The initial liquidity of a pool is below the minimum balance of an asset
+		BelowMinimumBalance,
+
+		/// The market already trades at or beyond the limit price
+		PriceLimitReached,
+
+		/// The price impact of a trade exceeds the GlobalMaxSlippage,
+		/// or the DefaultMaxSlippage of a trade without a minimum output
+		PriceImpactTooHigh,
+
+		/// The execution price of a trade deviates too much from the oracle price of the trader
+		OracleDeviationExceeded,
+
+		/// The operation has been disabled for this market by the AdminOrigin
+		OperationDisabled,
+
+		/// The market has more liquidity providers than can be handled at once,
+		/// or it does not accept any new liquidity providers
+		TooManyProviders,
+
+		/// The market has been registered, but has not received liquidity yet
+		MarketInactive,
+
+		/// The trade would leave the reserves of the pool beyond the MaxReserveRatio
+		ReserveRatioExceeded,
+
+		/// The trade is too small for the reserves of the pool to receive anything
+		ZeroReceived,
+
+		/// The account already has a pending withdrawal from the market
+		WithdrawalPending,
+
+		/// The account has no pending withdrawal from the market
+		NoPendingWithdrawal,
+
+		/// The WithdrawalDelay of the pending withdrawal has not passed yet
+		WithdrawalNotReady,
+
+		/// A deposit of liquidity contains neither BASE nor QUOTE asset
+		ZeroDeposit,
+
+		/// The swap of create_and_swap is not in the market it creates
+		SwapMarketMismatch,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		fn offchain_worker(now: BlockNumberFor<T>) {
-			// Reward the liquidity providers every 10 blocks
-			if now % 10u32.into() == Zero::zero() {
-				if let Err(e) = Self::do_liquidity_provider_payout() {
-					log::error!("do_liquidity_provider_payout failed due to {:?}", e);
-				}
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			// The storage writes and events of the offchain worker are discarded,
+			// so the check and the payouts run on chain
+			if (now % T::PayoutInterval::get()).is_zero() {
+				Self::check_reserves()
+					.saturating_add(Self::do_liquidity_provider_payout())
+					.saturating_add(Self::sweep_fee_dust())
+			} else {
+				Self::do_early_payout()
 			}
 		}
+
+		/// polkadot-v0.9.27 has no try_state hook yet,
+		/// so the reserves are checked against the pool account after every runtime upgrade
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		fn integrity_test() {
+			assert!(!T::PayoutInterval::get().is_zero(), "PayoutInterval must not be zero");
+			assert!(
+				T::CreatorRewardShare::get().deconstruct() as u64
+					+ T::ProviderRewardShare::get().deconstruct() as u64
+					<= Perbill::one().deconstruct() as u64,
+				"CreatorRewardShare and ProviderRewardShare must not exceed one together"
+			);
+			assert!(
+				Self::payout_market_weight()
+					.saturating_mul(<T as Config>::MaxPayoutMarkets::get() as u64)
+					<= T::BlockWeights::get().max_block,
+				"A payout of MaxPayoutMarkets markets must fit into a block"
+			);
+			assert!(
+				<T as Config>::MaxProvidersPerMarket::get()
+					<= <T as Config>::MaxForceCloseProviders::get(),
+				"MaxForceCloseProviders must cover the MaxProvidersPerMarket"
+			);
+		}
 	}
 
 	#[pallet::call]
@@ -185,16 +732,22 @@ pub mod pallet {
 		/// The user is required to provide both BASE and QUOTE asset
 		/// to bootstrap the liquidity of the pool
 		///
+		/// If the QUOTE transfer fails after the BASE transfer, the whole call is rolled back.
+		/// There is no event for the failure, as it would be rolled back just as well,
+		/// so the dispatch error is the only signal to the user
+		///
 		/// # Arguments:
-		/// origin: The obiquitous origin of a transaction
+		/// origin: Must satisfy the CreatePoolOrigin
 		/// base_asset: The BASE asset of the market
 		/// quote_asset: The QUOTE asset of the market
 		/// base_amount: Amount of BASE currency to use for bootstrapping liquidity
 		/// quote_amount: Amount of QUOTE currency to use for bootstrapping liquidity
+		/// expected_price: If Some, the initial price implied by the amounts
+		/// 	has to be within the tolerance of this price
 		///
 		/// # Weight:
-		/// Requires base weight + 3 reads and 6 writes
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 6))]
+		/// Requires base weight + 3 reads and 7 writes
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 7))]
 		#[transactional] // This Dispatchable is atomic
 		pub fn create_market_pool(
 			origin: OriginFor<T>,
@@ -202,54 +755,59 @@ pub mod pallet {
 			quote_asset: AssetIdOf<T>,
 			base_amount: BalanceOf<T>,
 			quote_amount: BalanceOf<T>,
+			expected_price: Option<ExpectedPrice<BalanceOf<T>>>,
 		) -> DispatchResult {
-			let who = ensure_signed(origin.clone())?;
-
-			// check if market pool exists already
-			let market = (base_asset, quote_asset);
-			ensure!(LiquidityPool::<T>::get(market).is_none(), Error::<T>::MarketExists);
-
-			// Check that balance of BASE asset of caller account is sufficient
-			let base_balance = Self::balance(base_asset, &who);
-			ensure!(base_balance >= base_amount, Error::<T>::NotEnoughBalance);
-
-			// Check if balance of QUOTE asset of caller account is sufficient
-			let quote_balance = Self::balance(quote_asset, &who);
-			ensure!(quote_balance >= quote_amount, Error::<T>::NotEnoughBalance);
+			let who = T::CreatePoolOrigin::ensure_origin(origin)?;
 
-			let pool_account = Self::pool_account();
-
-			// Transfer the BASE currency into the pool
-			<T as Config>::Currencies::transfer(
-				base_asset,
+			Self::do_create_market_pool(
 				&who,
-				&pool_account,
-				base_amount,
-				true,
-			)?;
-			// Transfer the QUOTE currency into the pool
-			<T as Config>::Currencies::transfer(
+				base_asset,
 				quote_asset,
-				&who,
-				&pool_account,
+				base_amount,
 				quote_amount,
-				true,
-			)?;
+				expected_price,
+			)
+		}
+
+		/// Registers a market without any liquidity, e.g. to set up its metadata in advance.
+		/// The market is inactive and rejects trades until both of its reserves are funded
+		/// through deposit_liquidity
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the CreatePoolOrigin
+		/// base_asset: The BASE asset of the market
+		/// quote_asset: The QUOTE asset of the market
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn register_market(
+			origin: OriginFor<T>,
+			base_asset: AssetIdOf<T>,
+			quote_asset: AssetIdOf<T>,
+		) -> DispatchResult {
+			let who = T::CreatePoolOrigin::ensure_origin(origin)?;
+
+			let market = MarketId::new(base_asset, quote_asset);
+			ensure!(LiquidityPool::<T>::get(market).is_none(), Error::<T>::MarketExists);
+			ensure!(
+				!<T as Config>::RestrictPairs::get() || AllowedPairs::<T>::contains_key(market),
+				Error::<T>::PairNotAllowed
+			);
 
-			// Insert the balance information for the market
 			let market_info = MarketInfo {
-				base_balance: base_amount,
-				quote_balance: quote_amount,
+				base_balance: Zero::zero(),
+				quote_balance: Zero::zero(),
 				collected_base_fees: Zero::zero(),
 				collected_quote_fees: Zero::zero(),
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: <frame_system::Pallet<T>>::block_number(),
+				creator: who.clone(),
+				active: false,
 			};
 			LiquidityPool::<T>::insert(market, market_info);
+			Self::index_market(market)?;
 
-			// remember who depsited what in the liquidity provision pool
-			LiqProvisionPool::<T>::insert(market, who.clone(), (base_amount, quote_amount));
-
-			// Emit the event that the pool has been created
-			Self::deposit_event(Event::PoolCreated(who, market, base_amount, quote_amount));
+			Self::deposit_event_indexed(market, &who, Event::MarketRegistered(who.clone(), market));
 
 			Ok(())
 		}
@@ -262,7 +820,7 @@ pub mod pallet {
 		/// market: To which market the liquidity should be added
 		/// base_amount: The amount of BASE asset to deposit
 		/// quote_amount: The amount of QUOTE asset to deposit
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 6))]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 7))]
 		#[transactional] // This Dispatchable is atomic
 		pub fn deposit_liquidity(
 			origin: OriginFor<T>,
@@ -272,10 +830,15 @@ pub mod pallet {
 		) -> DispatchResult {
 			let who = ensure_signed(origin.clone())?;
 
-			let (base_asset, quote_asset) = market;
+			// An empty deposit would take a slot of the MaxProvidersPerMarket for free
+			ensure!(!base_amount.is_zero() || !quote_amount.is_zero(), Error::<T>::ZeroDeposit);
+
+			let MarketId { base: base_asset, quote: quote_asset } = market;
 
-			// check if market pool exists
-			ensure!(LiquidityPool::<T>::get(market).is_some(), Error::<T>::MarketDoesNotExist);
+			// check if market pool exists and accepts deposits
+			let market_info =
+				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+			ensure!(market_info.flags.allow_deposit, Error::<T>::OperationDisabled);
 
 			// Check that balance of BASE asset of caller account is sufficient
 			let base_balance = Self::balance(base_asset, &who);
@@ -285,42 +848,56 @@ pub mod pallet {
 			let quote_balance = Self::balance(quote_asset, &who);
 			ensure!(quote_balance >= quote_amount, Error::<T>::NotEnoughBalance);
 
-			// Use try_mutate in case the closure fails, e.g.: arithmetic overflow
-			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+			let pool_account = Self::pool_account();
+			// The state before the transfers, for the sanity check of the records below
+			#[cfg(debug_assertions)]
+			let (pool_balances_before, provision_before) = (
+				(
+					Self::balance(base_asset, &pool_account),
+					Self::balance(quote_asset, &pool_account),
+				),
+				LiqProvisionPool::<T>::get(market, &who),
+			);
+
+			// transfer the BASE currency to pool account
+			let base_amount =
+				Self::transfer_measured(base_asset, &who, &pool_account, base_amount, true)?;
+			// transfer the QUOTE currency to pool account
+			let quote_amount =
+				Self::transfer_measured(quote_asset, &who, &pool_account, quote_amount, true)?;
+
+			// The amounts recorded in LiquidityPool and LiqProvisionPool
+			// are exactly what arrived in the pool account.
+			// Use try_mutate in case the closure fails, e.g.: arithmetic overflow
+			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
 				let market_info = opt_market_info
-					.clone()
+					.as_mut()
 					.expect("Check that the market pool exists has been done before; qed");
 
-				market_info
+				market_info.base_balance = market_info
 					.base_balance
 					.checked_add(base_amount)
 					.ok_or(Error::<T>::Arithmetic)?;
-				market_info
+				market_info.quote_balance = market_info
 					.quote_balance
 					.checked_add(quote_amount)
 					.ok_or(Error::<T>::Arithmetic)?;
+				// A registered market opens for trading once both of its reserves are funded
+				if !market_info.base_balance.is_zero() && !market_info.quote_balance.is_zero() {
+					market_info.active = true;
+				}
 
 				Ok(())
 			})?;
 
-			let pool_account = Self::pool_account();
-
-			// transfer the BASE currency to pool account
-			<T as Config>::Currencies::transfer(
-				base_asset,
-				&who,
-				&pool_account,
-				base_amount,
-				true,
-			)?;
-			// transfer the QUOTE currency to pool account
-			<T as Config>::Currencies::transfer(
-				quote_asset,
-				&who,
-				&pool_account,
-				quote_amount,
-				true,
-			)?;
+			// Only new liquidity providers count towards MaxProvidersPerMarket
+			if !LiqProvisionPool::<T>::contains_key(market, &who) {
+				ProviderCount::<T>::try_mutate(market, |count| -> DispatchResult {
+					ensure!(*count < T::MaxProvidersPerMarket::get(), Error::<T>::TooManyProviders);
+					*count += 1;
+					Ok(())
+				})?;
+			}
 
 			// Keep track of liquidity providers
 			LiqProvisionPool::<T>::try_mutate(
@@ -335,8 +912,39 @@ pub mod pallet {
 					Ok(())
 				},
 			)?;
+			// Topping up restarts the age, otherwise old dust could make new liquidity eligible
+			ProvidedSince::<T>::insert(market, &who, <frame_system::Pallet<T>>::block_number());
+
+			// The pool account balances, the reserves and the provision of the user
+			// have to grow by exactly the same amounts
+			#[cfg(debug_assertions)]
+			{
+				let pool_balances_delta = (
+					Self::balance(base_asset, &pool_account).saturating_sub(pool_balances_before.0),
+					Self::balance(quote_asset, &pool_account)
+						.saturating_sub(pool_balances_before.1),
+				);
+				let reserves = LiquidityPool::<T>::get(market)
+					.map(|info| (info.base_balance, info.quote_balance))
+					.unwrap_or_default();
+				let reserves_delta = (
+					reserves.0.saturating_sub(market_info.base_balance),
+					reserves.1.saturating_sub(market_info.quote_balance),
+				);
+				let provision = LiqProvisionPool::<T>::get(market, &who);
+				let provision_delta = (
+					provision.0.saturating_sub(provision_before.0),
+					provision.1.saturating_sub(provision_before.1),
+				);
+				debug_assert_eq!(pool_balances_delta, reserves_delta);
+				debug_assert_eq!(pool_balances_delta, provision_delta);
+			}
 
-			Self::deposit_event(Event::LiquidityAdded(who, market, base_amount, quote_amount));
+			Self::deposit_event_indexed(
+				market,
+				&who,
+				Event::LiquidityAdded(who.clone(), market, base_amount, quote_amount),
+			);
 
 			Ok(())
 		}
@@ -348,437 +956,3315 @@ pub mod pallet {
 		/// market: The liquidity pool to withdraw from
 		/// base_amount: The amount of the BASE asset to withdraw
 		/// quote_amount: The amount of the QUOTE asset to withdraw
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		/// allow_death: Whether the pool account may be reaped by the transfers out of the pool
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 5))]
 		#[transactional] // This Dispatchable is atomic
 		pub fn withdraw_liquidity(
 			origin: OriginFor<T>,
 			market: Market<T>,
 			base_amount: BalanceOf<T>,
 			quote_amount: BalanceOf<T>,
+			allow_death: bool,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 
-			// Check that the market exists
-			ensure!(LiquidityPool::<T>::get(market).is_some(), Error::<T>::MarketDoesNotExist);
-
-			let (base_asset, quote_asset) = market;
-			let pool_account = Self::pool_account();
+			Self::do_withdraw_liquidity(
+				&who,
+				market,
+				(base_amount, quote_amount),
+				(base_amount, quote_amount),
+				allow_death,
+			)
+		}
 
-			// ensure the user has enough balance in the pool to withdraw
-			let (users_base_balance, users_quote_balance) =
-				LiqProvisionPool::<T>::get(market, &who);
-			ensure!(users_base_balance >= base_amount, Error::<T>::NotEnoughBalance);
-			ensure!(users_quote_balance >= quote_amount, Error::<T>::NotEnoughBalance);
+		/// Allows the user to withdraw a fraction of his claim on the reserves of a pool,
+		/// which shifts with trading between signing and inclusion of the transaction
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The liquidity pool to withdraw from
+		/// fraction: The fraction of the users liquidity to withdraw
+		/// min_base_out: The minimum amount of the BASE asset to receive
+		/// min_quote_out: The minimum amount of the QUOTE asset to receive
+		/// allow_death: Whether the pool account may be reaped by the transfers out of the pool
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn withdraw_liquidity_with_slippage(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			fraction: Perbill,
+			min_base_out: BalanceOf<T>,
+			min_quote_out: BalanceOf<T>,
+			allow_death: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-			// transfer out BASE asset from pool
-			<T as Config>::Currencies::transfer(
-				base_asset,
-				&pool_account,
-				&who,
-				base_amount,
-				true,
-			)?;
-			// transfer out QUOTE asset from pool
-			<T as Config>::Currencies::transfer(
-				quote_asset,
-				&pool_account,
-				&who,
-				quote_amount,
-				true,
-			)?;
+			let (amounts_out, provisions) = Self::proportional_withdrawal(market, &who, fraction)?;
+			ensure!(
+				amounts_out.0 >= min_base_out && amounts_out.1 >= min_quote_out,
+				Error::<T>::SlippageExceeded
+			);
 
-			// update LiqProvisionPool
-			LiqProvisionPool::<T>::try_mutate(
-				market,
-				who.clone(),
-				|(base_balance, quote_balance)| -> DispatchResult {
-					*base_balance =
-						base_balance.checked_sub(base_amount).ok_or(Error::<T>::Arithmetic)?;
-					*quote_balance =
-						quote_balance.checked_sub(quote_amount).ok_or(Error::<T>::Arithmetic)?;
+			Self::do_withdraw_liquidity(&who, market, amounts_out, provisions, allow_death)
+		}
 
-					Ok(())
-				},
-			)?;
+		/// Allows the user to withdraw exactly a fraction of his current claim on the reserves
+		/// of a pool. Rounding always favours the pool and the pool account is kept alive
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The liquidity pool to withdraw from
+		/// fraction: The fraction of the users liquidity to withdraw
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn withdraw_liquidity_proportional(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			fraction: Perbill,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-			Self::deposit_event(Event::LiquidityWithdrawn(who, market, base_amount, quote_amount));
+			let (amounts_out, provisions) = Self::proportional_withdrawal(market, &who, fraction)?;
 
-			Ok(())
+			Self::do_withdraw_liquidity(&who, market, amounts_out, provisions, false)
 		}
 
-		/// Allows the user to buy the BASE asset of a market
+		/// Requests to withdraw a fraction of the users claim on the reserves of a pool.
+		/// The claim is taken out of the pool right away, so it neither changes with trading
+		/// nor shares in later payouts, and can be received with execute_withdrawal
+		/// once the WithdrawalDelay has passed
 		///
-		/// # Arguments
+		/// # Arguments:
 		/// origin: The obiquitous origin of a transaction
-		/// market: The market in which the user wants to trade
-		/// quote_amount: The amount of the QUOTE asset the user is willing to spend
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 4))]
+		/// market: The liquidity pool to withdraw from
+		/// fraction: The fraction of the users liquidity to withdraw
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 7))]
 		#[transactional] // This Dispatchable is atomic
-		pub fn buy(
+		pub fn request_withdrawal(
 			origin: OriginFor<T>,
 			market: Market<T>,
-			quote_amount: BalanceOf<T>,
+			fraction: Perbill,
 		) -> DispatchResult {
-			let who = ensure_signed(origin.clone())?;
+			let who = ensure_signed(origin)?;
 
-			// get balance of pool, if it exists
 			let market_info =
 				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+			ensure!(market_info.flags.allow_withdraw, Error::<T>::OperationDisabled);
+			ensure!(
+				!PendingWithdrawals::<T>::contains_key(market, &who),
+				Error::<T>::WithdrawalPending
+			);
 
-			let (base_asset, quote_asset) = market;
+			let ((base_amount, quote_amount), (base_provision, quote_provision)) =
+				Self::proportional_withdrawal(market, &who, fraction)?;
 
-			// Check that balance of QUOTE asset of caller account is sufficient
-			let quote_balance = Self::balance(quote_asset, &who);
-			ensure!(quote_balance >= quote_amount, Error::<T>::NotEnoughBalance);
+			// Unlike withdraw_liquidity, the pool is never removed here,
+			// so it has to keep reserves which can still be traded against
+			let base_left = market_info
+				.base_balance
+				.checked_sub(base_amount)
+				.ok_or(Error::<T>::InsufficientLiquidity)?;
+			let quote_left = market_info
+				.quote_balance
+				.checked_sub(quote_amount)
+				.ok_or(Error::<T>::InsufficientLiquidity)?;
+			let dust_threshold = <T as Config>::DustThreshold::get();
+			ensure!(
+				!base_left.is_zero()
+					&& !quote_left.is_zero()
+					&& base_left >= dust_threshold
+					&& quote_left >= dust_threshold,
+				Error::<T>::WouldDrainPool
+			);
+			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+				let market_info = opt_market_info.as_mut().ok_or(Error::<T>::MarketDoesNotExist)?;
+				market_info.base_balance = base_left;
+				market_info.quote_balance = quote_left;
 
-			// get the amount to receive
-			let receive_amount = Self::get_received_amount(
-				market_info.base_balance,
-				market_info.quote_balance,
-				OrderType::Buy,
-				quote_amount,
-			)?;
-			let fee_quote = Self::fee_from_amount(quote_amount)?;
-			// This is the amount of QUOTE currency being deposited into the pool
-			let deposit_amount =
-				quote_amount.checked_sub(fee_quote).ok_or(Error::<T>::Arithmetic)?;
+				Ok(())
+			})?;
 
+			// Lock the claim away from the pool account, which only holds the reserves
 			let pool_account = Self::pool_account();
-
-			// Transfer the QUOTE asset into the pool
-			<T as Config>::Currencies::transfer(
-				quote_asset,
-				&who,
+			let withdrawal_account = Self::withdrawal_account();
+			Self::safe_transfer(
+				market.base,
 				&pool_account,
-				deposit_amount,
+				&withdrawal_account,
+				base_amount,
 				true,
 			)?;
-			// And get the BASE asset out of the pool
-			<T as Config>::Currencies::transfer(
-				base_asset,
+			Self::safe_transfer(
+				market.quote,
 				&pool_account,
-				&who,
-				receive_amount,
+				&withdrawal_account,
+				quote_amount,
 				true,
 			)?;
 
-			// Transfer the taker fee to a separate account
-			let pool_fee_account = Self::pool_fee_account();
-			<T as Config>::Currencies::transfer(
-				quote_asset,
+			Self::reduce_provision(market, &who, (base_provision, quote_provision))?;
+
+			let executable_at = <frame_system::Pallet<T>>::block_number()
+				.saturating_add(<T as Config>::WithdrawalDelay::get());
+			PendingWithdrawals::<T>::insert(
+				market,
 				&who,
-				&pool_fee_account,
-				fee_quote,
-				true,
-			)?;
+				PendingWithdrawal { base_amount, quote_amount, executable_at },
+			);
 
-			// update the market_info collected
-			let fee_quote = Self::fee_from_amount(quote_amount)?;
-			LiquidityPool::<T>::try_mutate(
+			Self::deposit_event_indexed(
 				market,
-				|opt_market_info: &mut Option<MarketInfo<T>>| -> Result<(), Error<T>> {
-					match opt_market_info.as_mut() {
-						Some(market_info) => {
-							market_info.base_balance = market_info
-								.base_balance
-								.checked_sub(receive_amount)
-								.ok_or(Error::<T>::Arithmetic)?;
-							market_info.quote_balance = market_info
-								.quote_balance
-								.checked_add(deposit_amount)
-								.ok_or(Error::<T>::Arithmetic)?;
-							market_info.collected_quote_fees = market_info
-								.collected_quote_fees
-								.checked_add(fee_quote)
-								.ok_or(Error::<T>::Arithmetic)?;
-						},
-						None => panic!("It has been checked before that this is Some; qed"),
-					}
+				&who,
+				Event::WithdrawalRequested(
+					who.clone(),
+					market,
+					base_amount,
+					quote_amount,
+					executable_at,
+				),
+			);
 
-					Ok(())
-				},
+			Ok(())
+		}
+
+		/// Executes a withdrawal requested with request_withdrawal,
+		/// once its WithdrawalDelay has passed
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The liquidity pool the withdrawal has been requested from
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 3))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn execute_withdrawal(origin: OriginFor<T>, market: Market<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let pending = PendingWithdrawals::<T>::get(market, &who)
+				.ok_or(Error::<T>::NoPendingWithdrawal)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() >= pending.executable_at,
+				Error::<T>::WithdrawalNotReady
+			);
+
+			// The withdrawal account only holds pending withdrawals, so it may be emptied
+			let withdrawal_account = Self::withdrawal_account();
+			Self::safe_transfer(
+				market.base,
+				&withdrawal_account,
+				&who,
+				pending.base_amount,
+				false,
 			)?;
+			Self::safe_transfer(
+				market.quote,
+				&withdrawal_account,
+				&who,
+				pending.quote_amount,
+				false,
+			)?;
+			PendingWithdrawals::<T>::remove(market, &who);
 
-			Self::deposit_event(Event::Bought(who, market, quote_amount, receive_amount));
+			Self::deposit_event_indexed(
+				market,
+				&who,
+				Event::LiquidityWithdrawn(
+					who.clone(),
+					market,
+					pending.base_amount,
+					pending.quote_amount,
+				),
+			);
 
 			Ok(())
 		}
 
+		/// Allows the user to buy the BASE asset of a market
+		///
+		/// # Arguments
+		/// origin: The obiquitous origin of a transaction
+		/// market: The market in which the user wants to trade
+		/// quote_amount: The amount of the QUOTE asset the user is willing to spend
+		/// oracle_price: If Some, the execution price of the trade
+		/// 	has to be within the tolerance of this price from an external oracle
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
+		#[transactional] // This Dispatchable is atomic
+		pub fn buy(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			quote_amount: BalanceOf<T>,
+			oracle_price: Option<ExpectedPrice<BalanceOf<T>>>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			Self::ensure_default_slippage(market, &who, OrderType::Buy, quote_amount)
+				.map_err(|e| e.with_weight(Self::rejected_slippage_weight()))?;
+
+			Self::do_swap(
+				&who,
+				market,
+				OrderType::Buy,
+				quote_amount,
+				Zero::zero(),
+				oracle_price,
+				true,
+			)
+		}
+
 		/// Allows the user to sell the BASE asset of a market
 		///
 		/// # Arguments:
 		/// origin: The obiquitous origin of a transaction
 		/// market: The market in which the user wants to trade
 		/// base_amount: The amount of BASE asset the user wants to sell
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 4))]
+		/// allow_death: Whether the users account may be reaped,
+		/// e.g. when selling the entire BASE balance
+		/// oracle_price: If Some, the execution price of the trade
+		/// 	has to be within the tolerance of this price from an external oracle
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
 		#[transactional] // This Dispatchable is atomic
 		pub fn sell(
 			origin: OriginFor<T>,
 			market: Market<T>,
 			base_amount: BalanceOf<T>,
-		) -> DispatchResult {
-			let who = ensure_signed(origin.clone())?;
-
-			// get balance of pool, if it exists
-			let market_info =
-				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
-
-			let (base_asset, quote_asset) = market;
+			allow_death: bool,
+			oracle_price: Option<ExpectedPrice<BalanceOf<T>>>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
 
-			// Check that user has enough BASE asset to sell it
-			let base_balance = Self::balance(base_asset, &who);
-			ensure!(base_balance >= base_amount, Error::<T>::NotEnoughBalance);
+			Self::ensure_default_slippage(market, &who, OrderType::Sell, base_amount)
+				.map_err(|e| e.with_weight(Self::rejected_slippage_weight()))?;
 
-			let receive_amount = Self::get_received_amount(
-				market_info.base_balance,
-				market_info.quote_balance,
+			Self::do_swap(
+				&who,
+				market,
 				OrderType::Sell,
 				base_amount,
-			)?;
-			let fee_base = Self::fee_from_amount(base_amount)?;
-			// This is the amount of BASE currency being deposited into the pool
-			let deposit_amount = base_amount.checked_sub(fee_base).ok_or(Error::<T>::Arithmetic)?;
+				Zero::zero(),
+				oracle_price,
+				!allow_death,
+			)
+		}
 
-			let pool_account = Self::pool_account();
+		/// Swaps one asset for another in whichever market trades the two,
+		/// buying or selling its BASE asset depending on the orientation of the market
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// asset_in: The asset the user puts into the pool
+		/// asset_out: The asset the user wants to receive
+		/// amount_in: The amount of asset_in the user is willing to spend
+		/// min_out: The minimum amount of asset_out to receive, else the swap fails
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight() + T::DbWeight::get().reads(1))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn swap(
+			origin: OriginFor<T>,
+			asset_in: AssetIdOf<T>,
+			asset_out: AssetIdOf<T>,
+			amount_in: BalanceOf<T>,
+			min_out: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
 
-			// Transfer the BASE asset into the pool
-			<T as Config>::Currencies::transfer(
-				base_asset,
-				&who,
-				&pool_account,
-				deposit_amount,
-				true,
-			)?;
-			// And get the QUOTE asset out of the pool
-			<T as Config>::Currencies::transfer(
-				quote_asset,
-				&pool_account,
-				&who,
-				receive_amount,
-				true,
-			)?;
+			let (market, order_type) =
+				Self::swap_direction(asset_in, asset_out).ok_or(Error::<T>::MarketDoesNotExist)?;
 
-			// Transfer taker fee into separate pool account
-			let pool_fee_account = Self::pool_fee_account();
-			<T as Config>::Currencies::transfer(
-				base_asset,
-				&who,
-				&pool_fee_account,
-				fee_base,
-				true,
-			)?;
+			Self::do_swap(&who, market, order_type, amount_in, min_out, None, true)
+		}
 
-			// update the market_info
-			let fee_base = Self::fee_from_amount(base_amount)?;
-			LiquidityPool::<T>::try_mutate(
-				market,
-				|opt_market_info: &mut Option<MarketInfo<T>>| -> Result<(), Error<T>> {
-					match opt_market_info.as_mut() {
-						Some(market_info) => {
-							market_info.base_balance = market_info
-								.base_balance
-								.checked_add(deposit_amount)
-								.ok_or(Error::<T>::Arithmetic)?;
-							market_info.quote_balance = market_info
-								.quote_balance
-								.checked_sub(receive_amount)
-								.ok_or(Error::<T>::Arithmetic)?;
-							market_info.collected_base_fees = market_info
-								.collected_base_fees
-								.checked_add(fee_base)
-								.ok_or(Error::<T>::Arithmetic)?;
-						},
-						None => panic!("It has been checked before that this is Some; qed"),
-					}
+		/// Buys the BASE asset of a market on behalf of another account,
+		/// e.g. to pay a merchant in the asset they want
+		///
+		/// # Arguments
+		/// origin: The obiquitous origin of a transaction, paying for the trade
+		/// market: The market in which the user wants to trade
+		/// quote_amount: The amount of the QUOTE asset the user is willing to spend
+		/// recipient: The account receiving the bought BASE asset
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
+		#[transactional] // This Dispatchable is atomic
+		pub fn buy_to(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			quote_amount: BalanceOf<T>,
+			recipient: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-					Ok(())
-				},
-			)?;
+			let receive_amount =
+				Self::do_trade(&who, &recipient, market, OrderType::Buy, quote_amount, true)?;
 
-			Self::deposit_event(Event::Sold(who, market, base_amount, receive_amount));
+			if <T as Config>::EmitTradeEvents::get() {
+				Self::deposit_event_indexed(
+					market,
+					&who,
+					Event::Bought(who.clone(), market, quote_amount, receive_amount),
+				);
+			}
 
 			Ok(())
 		}
-	}
-}
-
-impl<T: Config> Pallet<T> {
-	/// The internal account of the pool derived from this pallets id
-	#[inline(always)]
-	fn pool_account() -> T::AccountId {
-		T::PalletId::get().into_account_truncating()
-	}
 
-	/// A separate account for collecting the fees into
-	#[inline(always)]
-	fn pool_fee_account() -> T::AccountId {
-		T::PalletId::get().try_into_sub_account(b"fee-account").expect("")
-	}
+		/// Sells the BASE asset of a market on behalf of another account,
+		/// e.g. to pay a merchant in the asset they want
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction, paying for the trade
+		/// market: The market in which the user wants to trade
+		/// base_amount: The amount of BASE asset the user wants to sell
+		/// recipient: The account receiving the QUOTE asset
+		/// allow_death: Whether the users account may be reaped,
+		/// e.g. when selling the entire BASE balance
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
+		#[transactional] // This Dispatchable is atomic
+		pub fn sell_to(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			base_amount: BalanceOf<T>,
+			recipient: T::AccountId,
+			allow_death: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-	/// Calculates the received amount when buying or selling a given amount
-	///
-	/// # Arguments:
-	/// pool_base_balance: The amount of the BASE asset in the pool
-	/// pool_quote_balance: The amount of the QUOTE asset in the pool
-	/// buy_or_sell: Whether the operation is buying or selling
-	/// amount: The amount to spend
-	///
-	/// # Returns:
-	/// If Ok, The balance that the user will receive from this exchange
-	/// Else some arithmetic error
-	fn get_received_amount(
-		pool_base_balance: BalanceOf<T>,
-		pool_quote_balance: BalanceOf<T>,
-		buy_or_sell: OrderType,
-		amount: BalanceOf<T>,
-	) -> Result<BalanceOf<T>, DispatchError> {
-		if amount.is_zero() {
-			Ok(Zero::zero())
-		} else {
-			let pool_k = pool_base_balance
-				.checked_mul(pool_quote_balance)
-				.ok_or(Error::<T>::Arithmetic)?;
+			let receive_amount = Self::do_trade(
+				&who,
+				&recipient,
+				market,
+				OrderType::Sell,
+				base_amount,
+				!allow_death,
+			)?;
 
-			let fee_amount = Self::fee_from_amount(amount)?;
-			let amount = amount.checked_sub(fee_amount).ok_or(Error::<T>::Arithmetic)?;
-			let receive_amount = match buy_or_sell {
-				OrderType::Buy => {
-					let new_quote_balance =
-						pool_quote_balance.checked_add(amount).ok_or(Error::<T>::Arithmetic)?;
-					let new_base_balance =
-						pool_k.checked_div(new_quote_balance).ok_or(Error::<T>::Arithmetic)?;
-					pool_base_balance.checked_sub(new_base_balance).ok_or(Error::<T>::Arithmetic)?
-				},
-				OrderType::Sell => {
-					let new_base_balance =
-						pool_base_balance.checked_add(amount).ok_or(Error::<T>::Arithmetic)?;
-					let new_quote_balance =
-						pool_k.checked_div(new_base_balance).ok_or(Error::<T>::Arithmetic)?;
-					pool_quote_balance
-						.checked_sub(new_quote_balance)
-						.ok_or(Error::<T>::Arithmetic)?
-				},
-			};
+			if <T as Config>::EmitTradeEvents::get() {
+				Self::deposit_event_indexed(
+					market,
+					&who,
+					Event::Sold(who.clone(), market, base_amount, receive_amount),
+				);
+			}
 
-			Ok(receive_amount)
+			Ok(())
 		}
-	}
+
+		/// Buys the BASE asset of a market until its price reaches a limit price.
+		/// Only the QUOTE asset required to reach the limit is spent
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The market in which the user wants to trade
+		/// quote_amount: The maximum amount of the QUOTE asset to spend
+		/// limit_price: The (numerator, denominator) of the price not to buy above,
+		/// denominated in QUOTE per BASE
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
+		#[transactional] // This Dispatchable is atomic
+		pub fn buy_until_price(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			quote_amount: BalanceOf<T>,
+			limit_price: (BalanceOf<T>, BalanceOf<T>),
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let quote_amount =
+				Self::fill_until_price(market, OrderType::Buy, quote_amount, limit_price)?;
+			let receive_amount =
+				Self::do_trade(&who, &who, market, OrderType::Buy, quote_amount, true)?;
+
+			if <T as Config>::EmitTradeEvents::get() {
+				Self::deposit_event_indexed(
+					market,
+					&who,
+					Event::Bought(who.clone(), market, quote_amount, receive_amount),
+				);
+			}
+
+			Ok(())
+		}
+
+		/// Sells the BASE asset of a market until its price reaches a limit price.
+		/// Only the BASE asset required to reach the limit is sold
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The market in which the user wants to trade
+		/// base_amount: The maximum amount of the BASE asset to sell
+		/// limit_price: The (numerator, denominator) of the price not to sell below,
+		/// denominated in QUOTE per BASE
+		/// allow_death: Whether the users account may be reaped,
+		/// e.g. when selling the entire BASE balance
+		#[pallet::weight(10_000 + Pallet::<T>::trade_weight())]
+		#[transactional] // This Dispatchable is atomic
+		pub fn sell_until_price(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			base_amount: BalanceOf<T>,
+			limit_price: (BalanceOf<T>, BalanceOf<T>),
+			allow_death: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let base_amount =
+				Self::fill_until_price(market, OrderType::Sell, base_amount, limit_price)?;
+			let receive_amount =
+				Self::do_trade(&who, &who, market, OrderType::Sell, base_amount, !allow_death)?;
+
+			if <T as Config>::EmitTradeEvents::get() {
+				Self::deposit_event_indexed(
+					market,
+					&who,
+					Event::Sold(who.clone(), market, base_amount, receive_amount),
+				);
+			}
+
+			Ok(())
+		}
+
+		/// Executes several swaps, possibly across different markets, atomically.
+		/// Either all swaps succeed or none of them is executed
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// ops: The swaps to execute in order, at most MaxBatchSize
+		#[pallet::weight(
+			10_000 + Pallet::<T>::trade_weight().saturating_mul(ops.len() as u64)
+		)]
+		#[transactional] // This Dispatchable is atomic
+		pub fn swap_batch(origin: OriginFor<T>, ops: Vec<SwapOp<T>>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(
+				ops.len() <= <T as Config>::MaxBatchSize::get() as usize,
+				Error::<T>::BatchTooLarge
+			);
+
+			for op in ops {
+				let receive_amount =
+					Self::do_trade(&who, &who, op.market, op.order_type, op.amount_in, true)?;
+				ensure!(receive_amount >= op.min_out, Error::<T>::SlippageExceeded);
+
+				if <T as Config>::EmitTradeEvents::get() {
+					let event = match op.order_type {
+						OrderType::Buy => {
+							Event::Bought(who.clone(), op.market, op.amount_in, receive_amount)
+						},
+						OrderType::Sell => {
+							Event::Sold(who.clone(), op.market, op.amount_in, receive_amount)
+						},
+					};
+					Self::deposit_event_indexed(op.market, &who, event);
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Creates a new pool for a market and executes a swap right after, atomically,
+		/// so the creator can take the first trade of the new pool.
+		/// If the swap fails, the pool is not created either
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the CreatePoolOrigin
+		/// base_asset: The BASE asset of the market
+		/// quote_asset: The QUOTE asset of the market
+		/// base_amount: Amount of BASE currency to use for bootstrapping liquidity
+		/// quote_amount: Amount of QUOTE currency to use for bootstrapping liquidity
+		/// swap: The swap to execute once the pool has been created
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(3, 7) + Pallet::<T>::trade_weight()
+		)]
+		#[transactional] // This Dispatchable is atomic
+		pub fn create_and_swap(
+			origin: OriginFor<T>,
+			base_asset: AssetIdOf<T>,
+			quote_asset: AssetIdOf<T>,
+			base_amount: BalanceOf<T>,
+			quote_amount: BalanceOf<T>,
+			swap: SwapOp<T>,
+		) -> DispatchResult {
+			let who = T::CreatePoolOrigin::ensure_origin(origin)?;
+
+			// The swap must take the first trade of the new pool, not of another market
+			ensure!(
+				swap.market == MarketId::new(base_asset, quote_asset),
+				Error::<T>::SwapMarketMismatch
+			);
+
+			Self::do_create_market_pool(
+				&who,
+				base_asset,
+				quote_asset,
+				base_amount,
+				quote_amount,
+				None,
+			)?;
+			Self::do_swap(
+				&who,
+				swap.market,
+				swap.order_type,
+				swap.amount_in,
+				swap.min_out,
+				None,
+				true,
+			)
+			.map_err(|e| e.error)?;
+
+			Ok(())
+		}
+
+		/// Removes a market entirely and returns all of its reserves to the last remaining
+		/// liquidity provider. The fees not yet paid out are distributed like in a regular payout,
+		/// so the provider receives the slice of the providers
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// market: The market to remove
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(8, 13))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn remove_market(origin: OriginFor<T>, market: Market<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let market_info =
+				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+
+			// Ensure nobody else still has liquidity in this pool
+			ensure!(Self::is_sole_provider(market, &who), Error::<T>::NotSoleProvider);
+
+			Self::do_remove_market(&who, market, &market_info)
+		}
+
+		/// Sets the asset in which the taker fee of a market is collected
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market to configure
+		/// fee_asset: The asset in which fees are collected from now on
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_fee_asset(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			fee_asset: FeeAsset,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+				let market_info = opt_market_info.as_mut().ok_or(Error::<T>::MarketDoesNotExist)?;
+				market_info.fee_asset = fee_asset;
+
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::FeeAssetSet(market, fee_asset));
+
+			Ok(())
+		}
+
+		/// Sets the display name of a market, which has no effect on trading
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market to name
+		/// name: The display name, an empty name clears it
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_market_metadata(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			name: Vec<u8>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(LiquidityPool::<T>::contains_key(market), Error::<T>::MarketDoesNotExist);
+			let bounded_name: BoundedVec<u8, T::NameLimit> =
+				name.clone().try_into().map_err(|_| Error::<T>::NameTooLong)?;
+
+			if bounded_name.is_empty() {
+				MarketMetadata::<T>::remove(market);
+			} else {
+				MarketMetadata::<T>::insert(market, bounded_name);
+			}
+
+			Self::deposit_event(Event::MarketMetadataSet(market, name));
+
+			Ok(())
+		}
+
+		/// Enables or disables individual operations of a market,
+		/// e.g. to block new deposits during a migration
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market to configure
+		/// flags: The operations which are enabled from now on
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_market_flags(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			flags: MarketFlags,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+				let market_info = opt_market_info.as_mut().ok_or(Error::<T>::MarketDoesNotExist)?;
+				market_info.flags = flags;
+
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::MarketFlagsSet(market, flags));
+
+			Ok(())
+		}
+
+		/// Adds a pair to or removes it from the AllowedPairs.
+		/// Existing markets are not affected
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The pair of (BASE, QUOTE) asset
+		/// allowed: Whether the pair may be created as market
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_pair_allowed(
+			origin: OriginFor<T>,
+			market: Market<T>,
+			allowed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if allowed {
+				AllowedPairs::<T>::insert(market, ());
+			} else {
+				AllowedPairs::<T>::remove(market);
+			}
+
+			Self::deposit_event(Event::PairAllowanceSet(market, allowed));
+
+			Ok(())
+		}
+
+		/// Adds an account to or removes it from the FeeExempt accounts
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// who: The account to configure
+		/// exempt: Whether the account pays no taker fee from now on
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_fee_exempt(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			exempt: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if exempt {
+				FeeExempt::<T>::insert(&who, ());
+			} else {
+				FeeExempt::<T>::remove(&who);
+			}
+
+			Self::deposit_event(Event::FeeExemptionSet(who, exempt));
+
+			Ok(())
+		}
+
+		/// Transfers the protocol fees accumulated by a market to the TreasuryAccount
+		/// and resets them
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market whose protocol fees to collect
+		///
+		/// # Weight:
+		/// Requires base weight + 7 reads and 5 writes,
+		/// ProtocolFees and a transfer of both assets from the pool fee account to the treasury
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(7, 5))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn collect_protocol_fees(origin: OriginFor<T>, market: Market<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let (base_fees, quote_fees) = ProtocolFees::<T>::take(market);
+			let MarketId { base: base_asset, quote: quote_asset } = market;
+			let pool_fee_account = Self::pool_fee_account();
+			let treasury = <T as Config>::TreasuryAccount::get();
+
+			if !base_fees.is_zero() {
+				Self::safe_transfer(base_asset, &pool_fee_account, &treasury, base_fees, false)?;
+			}
+			if !quote_fees.is_zero() {
+				Self::safe_transfer(quote_asset, &pool_fee_account, &treasury, quote_fees, false)?;
+			}
+
+			Self::deposit_event(Event::ProtocolFeesCollected(market, base_fees, quote_fees));
+
+			Ok(())
+		}
+
+		/// Moves the collected fees of a market, which are not yet paid out,
+		/// from the pool fee account into the reserves of its pool.
+		/// The liquidity providers profit through the grown reserves instead of a payout
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market whose fees to reinvest
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 6))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn reinvest_fees(origin: OriginFor<T>, market: Market<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let market_info =
+				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+			let MarketId { base: base_asset, quote: quote_asset } = market;
+			let pool_fee_account = Self::pool_fee_account();
+			let pool_account = Self::pool_account();
+
+			// Only what actually arrived in the pool is added to the reserves
+			let mut base_fees = Zero::zero();
+			if !market_info.collected_base_fees.is_zero() {
+				base_fees = Self::transfer_measured(
+					base_asset,
+					&pool_fee_account,
+					&pool_account,
+					market_info.collected_base_fees,
+					false,
+				)?;
+			}
+			let mut quote_fees = Zero::zero();
+			if !market_info.collected_quote_fees.is_zero() {
+				quote_fees = Self::transfer_measured(
+					quote_asset,
+					&pool_fee_account,
+					&pool_account,
+					market_info.collected_quote_fees,
+					false,
+				)?;
+			}
+
+			LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+				let market_info = opt_market_info
+					.as_mut()
+					.expect("Check that the market pool exists has been done before; qed");
+
+				market_info.base_balance = market_info
+					.base_balance
+					.checked_add(base_fees)
+					.ok_or(Error::<T>::Arithmetic)?;
+				market_info.quote_balance = market_info
+					.quote_balance
+					.checked_add(quote_fees)
+					.ok_or(Error::<T>::Arithmetic)?;
+				market_info.collected_base_fees = Zero::zero();
+				market_info.collected_quote_fees = Zero::zero();
+
+				Ok(())
+			})?;
+			Self::restart_fee_collection(market);
+
+			Self::deposit_event(Event::FeesReinvested(market, base_fees, quote_fees));
+
+			Ok(())
+		}
+
+		/// Winds down a market in an emergency, e.g. if one of its assets is compromised.
+		/// The reserves and the fees not yet paid out are refunded to all liquidity providers
+		/// proportional to their provision and pending withdrawals are paid out right away
+		///
+		/// # Arguments:
+		/// origin: Must satisfy the AdminOrigin
+		/// market: The market to close
+		///
+		/// # Weight:
+		/// Requires base weight + 3 reads and 7 writes, plus for each of at most
+		/// MaxForceCloseProviders liquidity providers 5 reads and 7 writes,
+		/// the provision, the balances, LifetimeFees, TradedVolume and ProvidedSince,
+		/// and for each of at most as many pending withdrawals 3 reads and 3 writes
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(3, 7).saturating_add(
+				T::DbWeight::get()
+					.reads_writes(8, 10)
+					.saturating_mul(<T as Config>::MaxForceCloseProviders::get() as u64)
+			)
+		)]
+		#[transactional] // This Dispatchable is atomic
+		pub fn force_close_market(origin: OriginFor<T>, market: Market<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let market_info =
+				LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+
+			let max_providers = <T as Config>::MaxForceCloseProviders::get() as usize;
+			let liquidity_providers: Vec<(T::AccountId, (BalanceOf<T>, BalanceOf<T>))> =
+				LiqProvisionPool::<T>::iter_prefix(market).take(max_providers + 1).collect();
+			ensure!(liquidity_providers.len() <= max_providers, Error::<T>::TooManyProviders);
+			// Also providers which withdrew all of their liquidity may wait for their withdrawal
+			let pending_withdrawals: Vec<(T::AccountId, PendingWithdrawal<T>)> =
+				PendingWithdrawals::<T>::iter_prefix(market).take(max_providers + 1).collect();
+			ensure!(pending_withdrawals.len() <= max_providers, Error::<T>::TooManyProviders);
+
+			let MarketId { base: base_asset, quote: quote_asset } = market;
+			let base_provisions: Vec<(T::AccountId, BalanceOf<T>)> = liquidity_providers
+				.iter()
+				.map(|(account, (base_provision, _))| (account.clone(), *base_provision))
+				.collect();
+			let quote_provisions: Vec<(T::AccountId, BalanceOf<T>)> = liquidity_providers
+				.iter()
+				.map(|(account, (_, quote_provision))| (account.clone(), *quote_provision))
+				.collect();
+
+			// The fees not yet paid out are distributed like in a regular payout
+			Self::distribute_fees(
+				market,
+				base_asset,
+				market_info.collected_base_fees,
+				&market_info.creator,
+				&base_provisions,
+			)?;
+			Self::distribute_fees(
+				market,
+				quote_asset,
+				market_info.collected_quote_fees,
+				&market_info.creator,
+				&quote_provisions,
+			)?;
+
+			// The pool account does not need to be kept alive for the refunds
+			let pool_account = Self::pool_account();
+			for (account, refund) in
+				Self::proportional_shares(market_info.base_balance, &base_provisions)?
+			{
+				Self::safe_transfer(base_asset, &pool_account, &account, refund, false)?;
+			}
+			for (account, refund) in
+				Self::proportional_shares(market_info.quote_balance, &quote_provisions)?
+			{
+				Self::safe_transfer(quote_asset, &pool_account, &account, refund, false)?;
+			}
+
+			// The WithdrawalDelay protects the market, which is closed now
+			let withdrawal_account = Self::withdrawal_account();
+			for (account, pending) in pending_withdrawals {
+				Self::safe_transfer(
+					base_asset,
+					&withdrawal_account,
+					&account,
+					pending.base_amount,
+					false,
+				)?;
+				Self::safe_transfer(
+					quote_asset,
+					&withdrawal_account,
+					&account,
+					pending.quote_amount,
+					false,
+				)?;
+				Self::deposit_event_indexed(
+					market,
+					&account,
+					Event::LiquidityWithdrawn(
+						account.clone(),
+						market,
+						pending.base_amount,
+						pending.quote_amount,
+					),
+				);
+			}
+
+			LiquidityPool::<T>::remove(market);
+			MarketMetadata::<T>::remove(market);
+			FeeRemainders::<T>::remove(market);
+			let _ = LiqProvisionPool::<T>::clear_prefix(market, u32::MAX, None);
+			let _ = ProvidedSince::<T>::clear_prefix(market, u32::MAX, None);
+			let _ = PendingWithdrawals::<T>::clear_prefix(market, u32::MAX, None);
+			let _ = TradedVolume::<T>::clear_prefix(market, u32::MAX, None);
+			let _ = LifetimeFees::<T>::clear_prefix(market, u32::MAX, None);
+			ProviderCount::<T>::remove(market);
+			FeesCollectedSince::<T>::remove(market);
+			EarlyPayoutDue::<T>::remove(market);
+			Self::unindex_market(market);
+
+			Self::deposit_event(Event::MarketForceClosed(
+				market,
+				market_info.base_balance,
+				market_info.quote_balance,
+			));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The internal account of the pool derived from this pallets id
+	#[inline(always)]
+	pub fn pool_account() -> T::AccountId {
+		T::PalletId::get().into_account_truncating()
+	}
+
+	/// A separate account for collecting the fees into
+	#[inline(always)]
+	pub fn pool_fee_account() -> T::AccountId {
+		T::PalletId::get().try_into_sub_account(b"fee-account").expect("")
+	}
+
+	/// A separate account holding the liquidity of pending withdrawals
+	#[inline(always)]
+	pub fn withdrawal_account() -> T::AccountId {
+		T::PalletId::get().try_into_sub_account(b"withdrawals").expect("")
+	}
+
+	/// Gets the accounts holding the reserves and the fees of all markets,
+	/// e.g. for other pallets to whitelist them
+	///
+	/// # Returns:
+	/// (pool account, pool fee account)
+	pub fn pool_accounts() -> (T::AccountId, T::AccountId) {
+		(Self::pool_account(), Self::pool_fee_account())
+	}
+
+	/// Transfers an asset between two accounts,
+	/// mapping every failure of T::Currencies to Error::Transfer
+	///
+	/// # Arguments:
+	/// asset: The asset to transfer
+	/// from: The account sending the asset
+	/// to: The account receiving the asset
+	/// amount: The amount to transfer
+	/// keep_alive: Whether the sending account must be kept alive
+	///
+	/// # Returns:
+	/// If Ok, the transfer succeeded
+	/// Else Error::Transfer
+	fn safe_transfer(
+		asset: AssetIdOf<T>,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: BalanceOf<T>,
+		keep_alive: bool,
+	) -> Result<(), Error<T>> {
+		<T as Config>::Currencies::transfer(asset, from, to, amount, keep_alive)
+			.map(|_| ())
+			.map_err(|_| Error::<T>::Transfer)
+	}
+
+	/// Transfers an asset between two accounts and measures how much actually arrived,
+	/// as some assets charge a fee on every transfer
+	///
+	/// # Arguments:
+	/// asset: The asset to transfer
+	/// from: The account sending the asset
+	/// to: The account receiving the asset
+	/// amount: The amount debited from the sender
+	/// keep_alive: Whether the sending account must be kept alive
+	///
+	/// # Returns:
+	/// If Ok, the amount credited to the receiver
+	/// Else Error::Transfer
+	fn transfer_measured(
+		asset: AssetIdOf<T>,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: BalanceOf<T>,
+		keep_alive: bool,
+	) -> Result<BalanceOf<T>, Error<T>> {
+		let balance_before = Self::balance(asset, to);
+		Self::safe_transfer(asset, from, to, amount, keep_alive)?;
+
+		Ok(Self::balance(asset, to).saturating_sub(balance_before))
+	}
+
+	/// Transfers the taker fee of a trade to the pool fee account.
+	/// The ProtocolFeeShare of it is accrued in ProtocolFees until the treasury collects it
+	/// and the rest is left for the liquidity providers
+	///
+	/// # Arguments:
+	/// market: The market the fee is charged in
+	/// asset: The asset the fee is paid in
+	/// who: The account paying the fee, either the taker or the pool account
+	/// fee: The total taker fee
+	/// keep_alive: Whether the paying account must be kept alive
+	///
+	/// # Returns:
+	/// If Ok, the part of the fee collected for the liquidity providers
+	/// Else the transfer or arithmetic error
+	fn charge_fee(
+		market: Market<T>,
+		asset: AssetIdOf<T>,
+		who: &T::AccountId,
+		fee: BalanceOf<T>,
+		keep_alive: bool,
+	) -> Result<BalanceOf<T>, DispatchError> {
+		// Only the fees which actually arrived can be split
+		let fee = Self::transfer_measured(asset, who, &Self::pool_fee_account(), fee, keep_alive)?;
+		let protocol_fee = <T as Config>::ProtocolFeeShare::get().mul_floor(fee);
+		let lp_fee = fee.checked_sub(protocol_fee).ok_or(Error::<T>::Arithmetic)?;
+
+		if !protocol_fee.is_zero() {
+			ProtocolFees::<T>::try_mutate(market, |(base_fees, quote_fees)| -> DispatchResult {
+				let fees = if asset == market.base { base_fees } else { quote_fees };
+				*fees = fees.checked_add(protocol_fee).ok_or(Error::<T>::Arithmetic)?;
+
+				Ok(())
+			})?;
+		}
+
+		Ok(lp_fee)
+	}
+
+	/// Calculates the received amount when buying or selling a given amount,
+	/// deducting the fee the same way do_trade would for this trader right now
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// market_info: The information of the market, whose reserves are traded against
+	/// who: The trader, whose FeeTiers and fee exemption apply
+	/// buy_or_sell: Whether the operation is buying or selling
+	/// amount: The amount to spend
+	///
+	/// # Returns:
+	/// If Ok, The balance that the user will receive from this exchange,
+	/// which is at most the output reserve minus one, so a trade can never drain the pool
+	/// Else NoLiquidity if either reserve is zero, or some arithmetic error
+	fn get_received_amount(
+		market: Market<T>,
+		market_info: &MarketInfo<T>,
+		who: &T::AccountId,
+		buy_or_sell: OrderType,
+		amount: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, DispatchError> {
+		let (side_in, side_out) = buy_or_sell.sides();
+		let (reserve_in, reserve_out) =
+			(market_info.balance(side_in), market_info.balance(side_out));
+		// Callers may pass stale or simulated reserves, which would not yield a meaningful quote
+		ensure!(!reserve_in.is_zero() && !reserve_out.is_zero(), Error::<T>::NoLiquidity);
+
+		if Self::fee_in_input(market_info.fee_asset, buy_or_sell) {
+			let (fee, _) =
+				Self::trade_fee(market, market_info, who, market.asset(side_in), amount)?;
+			let amount = amount.checked_sub(fee).ok_or(Error::<T>::Arithmetic)?;
+
+			Self::swap_output(reserve_in, reserve_out, amount)
+		} else {
+			let withdraw_amount = Self::swap_output(reserve_in, reserve_out, amount)?;
+			let (fee, _) =
+				Self::trade_fee(market, market_info, who, market.asset(side_out), withdraw_amount)?;
+
+			Ok(withdraw_amount.checked_sub(fee).ok_or(Error::<T>::Arithmetic)?)
+		}
+	}
+
+	/// Applies the constant product formula, without any fees
+	///
+	/// # Arguments:
+	/// reserve_in: The pools reserve of the asset being put in
+	/// reserve_out: The pools reserve of the asset being taken out
+	/// amount_in: The amount being put into the pool
+	///
+	/// # Returns:
+	/// If Ok, the amount taken out of the pool, rounded down so k never decreases.
+	/// The formula already stays below reserve_out, but the result is clamped to
+	/// reserve_out - 1 explicitly, so no rounding can ever empty the reserve
+	/// Else some arithmetic error
+	fn swap_output(
+		reserve_in: BalanceOf<T>,
+		reserve_out: BalanceOf<T>,
+		amount_in: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, DispatchError> {
+		if amount_in.is_zero() {
+			return Ok(Zero::zero())
+		}
+
+		// amount_out = amount_in * reserve_out / (reserve_in + amount_in),
+		// computed in 256 bits so the product of the reserves can't overflow
+		let new_reserve_in = U256::from(reserve_in)
+			.checked_add(U256::from(amount_in))
+			.ok_or(Error::<T>::Arithmetic)?;
+		let amount_out = U256::from(amount_in)
+			.checked_mul(U256::from(reserve_out))
+			.ok_or(Error::<T>::Arithmetic)?
+			.checked_div(new_reserve_in)
+			.ok_or(Error::<T>::Arithmetic)?
+			.min(U256::from(reserve_out).saturating_sub(U256::one()));
+
+		amount_out.try_into().map_err(|_| Error::<T>::Arithmetic.into())
+	}
+
+	/// Inverts swap_output, so the input is rounded up
+	///
+	/// # Arguments:
+	/// reserve_in: The pools reserve of the asset being put in
+	/// reserve_out: The pools reserve of the asset being taken out
+	/// amount_out: The amount to take out of the pool
+	///
+	/// # Returns:
+	/// If Some, the smallest amount to put into the pool for swap_output to return amount_out
+	/// None if amount_out would drain the reserve or on arithmetic overflow
+	fn swap_input(
+		reserve_in: BalanceOf<T>,
+		reserve_out: BalanceOf<T>,
+		amount_out: BalanceOf<T>,
+	) -> Option<BalanceOf<T>> {
+		if amount_out.is_zero() {
+			return Some(Zero::zero())
+		}
+		let reserve_left = reserve_out.checked_sub(amount_out).filter(|left| !left.is_zero())?;
+
+		// amount_in = ceil(amount_out * reserve_in / (reserve_out - amount_out))
+		let reserve_left = U256::from(reserve_left);
+		let amount_in = U256::from(amount_out)
+			.checked_mul(U256::from(reserve_in))?
+			.checked_add(reserve_left - U256::one())?
+			.checked_div(reserve_left)?;
+
+		amount_in.try_into().ok()
+	}
+
+	/// Whether the fee of a trade is deducted from the input before it enters the pool,
+	/// or from the output after it left the pool
+	///
+	/// # Arguments:
+	/// fee_asset: The fee asset of the market
+	/// order_type: Whether the BASE asset is bought or sold
+	fn fee_in_input(fee_asset: FeeAsset, order_type: OrderType) -> bool {
+		// Markets collecting the fee in the input asset may collect it in QUOTE when selling
+		let fee_asset = match fee_asset {
+			FeeAsset::Input
+				if order_type == OrderType::Sell && <T as Config>::SellFeeInQuote::get() =>
+			{
+				FeeAsset::Quote
+			},
+			fee_asset => fee_asset,
+		};
+
+		!matches!(
+			(fee_asset, order_type),
+			(FeeAsset::Quote, OrderType::Sell) | (FeeAsset::Base, OrderType::Buy)
+		)
+	}
+
+	/// Creates a new pool for a market, bootstrapped with the liquidity of its creator
+	///
+	/// # Arguments:
+	/// who: The creator, who has already been checked against the CreatePoolOrigin
+	/// base_asset: The BASE asset of the market
+	/// quote_asset: The QUOTE asset of the market
+	/// base_amount: Amount of BASE currency to use for bootstrapping liquidity
+	/// quote_amount: Amount of QUOTE currency to use for bootstrapping liquidity
+	/// expected_price: If Some, the initial price implied by the amounts
+	/// 	has to be within the tolerance of this price
+	///
+	/// # Returns:
+	/// If Ok, the pool has been created
+	/// Else the reason why it could not be created
+	fn do_create_market_pool(
+		who: &T::AccountId,
+		base_asset: AssetIdOf<T>,
+		quote_asset: AssetIdOf<T>,
+		base_amount: BalanceOf<T>,
+		quote_amount: BalanceOf<T>,
+		expected_price: Option<ExpectedPrice<BalanceOf<T>>>,
+	) -> DispatchResult {
+		// check if market pool exists already
+		let market = MarketId::new(base_asset, quote_asset);
+		ensure!(LiquidityPool::<T>::get(market).is_none(), Error::<T>::MarketExists);
+		ensure!(
+			!<T as Config>::RestrictPairs::get() || AllowedPairs::<T>::contains_key(market),
+			Error::<T>::PairNotAllowed
+		);
+
+		// Guard against accidentally bootstrapping the pool at the wrong price
+		if let Some(expected_price) = expected_price {
+			Self::ensure_expected_price(
+				base_amount,
+				quote_amount,
+				&expected_price,
+				Error::<T>::UnexpectedInitialPrice,
+			)?;
+		}
+
+		// The reserves must keep the pool account alive, otherwise it may be reaped
+		ensure!(
+			base_amount >= T::Currencies::minimum_balance(base_asset)
+				&& quote_amount >= T::Currencies::minimum_balance(quote_asset),
+			Error::<T>::BelowMinimumBalance
+		);
+
+		// Check that balance of BASE asset of caller account is sufficient
+		let base_balance = Self::balance(base_asset, who);
+		ensure!(base_balance >= base_amount, Error::<T>::NotEnoughBalance);
+
+		// Check if balance of QUOTE asset of caller account is sufficient
+		let quote_balance = Self::balance(quote_asset, who);
+		ensure!(quote_balance >= quote_amount, Error::<T>::NotEnoughBalance);
+
+		let pool_account = Self::pool_account();
+
+		// Transfer the BASE currency into the pool
+		let base_amount =
+			Self::transfer_measured(base_asset, who, &pool_account, base_amount, true)?;
+		// Transfer the QUOTE currency into the pool
+		let quote_amount =
+			Self::transfer_measured(quote_asset, who, &pool_account, quote_amount, true)?;
+
+		// Insert the balance information for the market,
+		// only what actually arrived in the pool is recorded
+		let market_info = MarketInfo {
+			base_balance: base_amount,
+			quote_balance: quote_amount,
+			collected_base_fees: Zero::zero(),
+			collected_quote_fees: Zero::zero(),
+			fee_asset: FeeAsset::Input,
+			flags: MarketFlags::default(),
+			created_at: <frame_system::Pallet<T>>::block_number(),
+			creator: who.clone(),
+			active: true,
+		};
+		LiquidityPool::<T>::insert(market, market_info);
+		Self::index_market(market)?;
+
+		// remember who depsited what in the liquidity provision pool
+		LiqProvisionPool::<T>::insert(market, who.clone(), (base_amount, quote_amount));
+		ProvidedSince::<T>::insert(market, who, <frame_system::Pallet<T>>::block_number());
+		ProviderCount::<T>::insert(market, 1);
+
+		// Emit the event that the pool has been created
+		Self::deposit_event_indexed(
+			market,
+			who,
+			Event::PoolCreated(who.clone(), market, base_amount, quote_amount),
+		);
+
+		Ok(())
+	}
+
+	/// Executes a trade of a taker for himself and emits the matching trade event.
+	/// Trades rejected before touching the pool only pay for reading the market
+	///
+	/// # Arguments:
+	/// who: The taker
+	/// market: The market to trade in
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+	/// min_out: The minimum amount the taker must receive, else SlippageExceeded
+	/// oracle_price: If Some, the price the execution price has to be within the tolerance of,
+	/// 	else OracleDeviationExceeded
+	/// keep_alive: Whether the takers account must be kept alive after paying for the trade
+	fn do_swap(
+		who: &T::AccountId,
+		market: Market<T>,
+		order_type: OrderType,
+		amount_in: BalanceOf<T>,
+		min_out: BalanceOf<T>,
+		oracle_price: Option<ExpectedPrice<BalanceOf<T>>>,
+		keep_alive: bool,
+	) -> DispatchResultWithPostInfo {
+		Self::ensure_tradable(market, amount_in)
+			.map_err(|e| e.with_weight(Self::rejected_trade_weight()))?;
+
+		let receive_amount = Self::do_trade(who, who, market, order_type, amount_in, keep_alive)?;
+		ensure!(receive_amount >= min_out, Error::<T>::SlippageExceeded);
+		if let Some(oracle_price) = oracle_price {
+			// The execution price is denominated in QUOTE per BASE, including the fee
+			let (base_amount, quote_amount) = match order_type {
+				OrderType::Buy => (receive_amount, amount_in),
+				OrderType::Sell => (amount_in, receive_amount),
+			};
+			Self::ensure_expected_price(
+				base_amount,
+				quote_amount,
+				&oracle_price,
+				Error::<T>::OracleDeviationExceeded,
+			)?;
+		}
+
+		if <T as Config>::EmitTradeEvents::get() {
+			let event = match order_type {
+				OrderType::Buy => Event::Bought(who.clone(), market, amount_in, receive_amount),
+				OrderType::Sell => Event::Sold(who.clone(), market, amount_in, receive_amount),
+			};
+			Self::deposit_event_indexed(market, who, event);
+		}
+
+		Ok(().into())
+	}
+
+	/// Executes a trade of a taker against the pool of a market
+	///
+	/// # Arguments:
+	/// who: The taker
+	/// recipient: The account receiving the output of the trade, usually the taker
+	/// market: The market to trade in
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+	/// keep_alive: Whether the takers account must be kept alive after paying for the trade
+	///
+	/// # Returns:
+	/// If Ok, the amount the recipient received
+	/// Else the reason why the trade failed
+	fn do_trade(
+		who: &T::AccountId,
+		recipient: &T::AccountId,
+		market: Market<T>,
+		order_type: OrderType,
+		amount_in: BalanceOf<T>,
+		keep_alive: bool,
+	) -> Result<BalanceOf<T>, DispatchError> {
+		// get balance of pool, if it exists
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+		ensure!(market_info.flags.allow_trade, Error::<T>::OperationDisabled);
+		ensure!(market_info.active, Error::<T>::MarketInactive);
+
+		let (side_in, side_out) = order_type.sides();
+		let (asset_in, asset_out) = (market.asset(side_in), market.asset(side_out));
+		let (reserve_in, reserve_out) =
+			(market_info.balance(side_in), market_info.balance(side_out));
+
+		// Check that balance of the asset the taker puts into the pool is sufficient
+		let balance_in = Self::balance(asset_in, who);
+		ensure!(balance_in >= amount_in, Error::<T>::NotEnoughBalance);
+
+		Self::ensure_trade_size(amount_in, reserve_in)?;
+		Self::ensure_price_impact(amount_in, reserve_in, <T as Config>::GlobalMaxSlippage::get())?;
+
+		let pool_account = Self::pool_account();
+
+		// A frozen asset or account would otherwise only fail deep inside the transfers
+		Self::ensure_not_frozen(asset_in, who, amount_in)?;
+		Self::ensure_not_frozen(asset_out, &pool_account, reserve_out)?;
+
+		let fee_in_input = Self::fee_in_input(market_info.fee_asset, order_type);
+		let (fee_asset_id, deposit_amount, withdraw_amount, receive_amount, lp_fee) =
+			if fee_in_input {
+				Self::ensure_not_dust(amount_in)?;
+				let fee = Self::fee_from_amount(market, &market_info, who, asset_in, amount_in)?;
+				// This is the amount being deposited into the pool
+				let deposit_amount = amount_in.checked_sub(fee).ok_or(Error::<T>::Arithmetic)?;
+				// The price is based on what actually arrived in the pool
+				let deposit_amount = Self::transfer_measured(
+					asset_in,
+					who,
+					&pool_account,
+					deposit_amount,
+					keep_alive,
+				)?;
+				let receive_amount = Self::swap_output(reserve_in, reserve_out, deposit_amount)?;
+				Self::ensure_received(amount_in, receive_amount)?;
+
+				Self::safe_transfer(asset_out, &pool_account, recipient, receive_amount, true)?;
+				// Transfer the taker fee to the separate accounts
+				let lp_fee = Self::charge_fee(market, asset_in, who, fee, keep_alive)?;
+
+				(asset_in, deposit_amount, receive_amount, receive_amount, lp_fee)
+			} else {
+				// The price is based on what actually arrived in the pool
+				let deposit_amount =
+					Self::transfer_measured(asset_in, who, &pool_account, amount_in, keep_alive)?;
+				let withdraw_amount = Self::swap_output(reserve_in, reserve_out, deposit_amount)?;
+				Self::ensure_not_dust(amount_in)?;
+				let fee =
+					Self::fee_from_amount(market, &market_info, who, asset_out, withdraw_amount)?;
+				// This is the amount the taker gets out of the pool
+				let receive_amount =
+					withdraw_amount.checked_sub(fee).ok_or(Error::<T>::Arithmetic)?;
+				Self::ensure_received(amount_in, receive_amount)?;
+
+				Self::safe_transfer(asset_out, &pool_account, recipient, receive_amount, true)?;
+				// The fee never reaches the taker, it goes straight from the pool to the fee accounts
+				let lp_fee = Self::charge_fee(market, asset_out, &pool_account, fee, true)?;
+
+				(asset_out, deposit_amount, withdraw_amount, receive_amount, lp_fee)
+			};
+
+		// update the market_info
+		LiquidityPool::<T>::try_mutate(
+			market,
+			|opt_market_info: &mut Option<MarketInfo<T>>| -> Result<(), Error<T>> {
+				let market_info = opt_market_info
+					.as_mut()
+					.expect("It has been checked before that this is Some; qed");
+
+				let (new_reserve_in, new_reserve_out) = (
+					reserve_in.checked_add(deposit_amount).ok_or(Error::<T>::Arithmetic)?,
+					reserve_out.checked_sub(withdraw_amount).ok_or(Error::<T>::Arithmetic)?,
+				);
+				if let Some(max_ratio) = <T as Config>::MaxReserveRatio::get() {
+					ensure!(
+						Self::ratio_within(new_reserve_in, new_reserve_out, max_ratio),
+						Error::<T>::ReserveRatioExceeded
+					);
+				}
+				*market_info.balance_mut(side_in) = new_reserve_in;
+				*market_info.balance_mut(side_out) = new_reserve_out;
+
+				let fee_side = if fee_asset_id == asset_in { side_in } else { side_out };
+				let collected_fees = market_info.collected_fees_mut(fee_side);
+				*collected_fees =
+					collected_fees.checked_add(lp_fee).ok_or(Error::<T>::Arithmetic)?;
+				// Recorded here, so the next block does not have to scan all markets for it
+				if *collected_fees > <T as Config>::MaxUndistributedFees::get() {
+					EarlyPayoutDue::<T>::insert(market, ());
+				}
+
+				Ok(())
+			},
+		)?;
+
+		// The volume is counted in the QUOTE asset, which is put in when buying
+		let quote_volume = match order_type {
+			OrderType::Buy => amount_in,
+			OrderType::Sell => receive_amount,
+		};
+		TradedVolume::<T>::mutate(market, who, |volume| {
+			*volume = volume.saturating_add(quote_volume)
+		});
+
+		Ok(receive_amount)
+	}
+
+	/// Gets the current price of a market
+	///
+	/// # Arguments:
+	/// market: The market to query
+	///
+	/// # Returns:
+	/// If Some, the price as (numerator, PRICE_DENOMINATOR)
+	/// None if the market does not exist
+	pub fn current_price(market: Market<T>) -> Option<(BalanceOf<T>, BalanceOf<T>)> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let price = market_info
+			.quote_balance
+			.checked_div(market_info.base_balance)
+			.unwrap_or_default();
+
+		Some((price.saturating_mul(PRICE_DENOMINATOR), PRICE_DENOMINATOR))
+	}
+
+	/// Checks whether the reserves of a market are within a maximum ratio of each other,
+	/// to monitor for pools which drifted to an extreme ratio and are effectively broken
+	///
+	/// # Arguments:
+	/// market: The market to check
+	/// max_ratio: The maximum ratio between the larger and the smaller reserve
+	///
+	/// # Returns:
+	/// If Some, whether the ratio of the reserves is at most max_ratio,
+	/// false if either reserve is empty
+	/// None if the market does not exist
+	pub fn is_ratio_sane(market: Market<T>, max_ratio: u128) -> Option<bool> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+
+		Some(Self::ratio_within(market_info.base_balance, market_info.quote_balance, max_ratio))
+	}
+
+	/// Whether the larger of two reserves is at most max_ratio times the smaller one
+	fn ratio_within(reserve_a: BalanceOf<T>, reserve_b: BalanceOf<T>, max_ratio: u128) -> bool {
+		let (smaller, larger) =
+			if reserve_a <= reserve_b { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+		if smaller.is_zero() {
+			return false
+		}
+
+		// In 256 bits, so the product can't overflow
+		U256::from(larger) <= U256::from(smaller).saturating_mul(U256::from(max_ratio))
+	}
+
+	/// Computes the price a market would have after a deposit of liquidity,
+	/// so a liquidity provider can check that the deposit does not move the price
+	///
+	/// # Arguments:
+	/// market: The market to deposit into
+	/// base_add: The amount of BASE asset to deposit
+	/// quote_add: The amount of QUOTE asset to deposit
+	///
+	/// # Returns:
+	/// If Some, the price after the deposit as (numerator, PRICE_DENOMINATOR),
+	/// computed the same way as current_price
+	/// None if the market does not exist or on arithmetic overflow
+	pub fn price_after_deposit(
+		market: Market<T>,
+		base_add: BalanceOf<T>,
+		quote_add: BalanceOf<T>,
+	) -> Option<(BalanceOf<T>, BalanceOf<T>)> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let base_balance = market_info.base_balance.checked_add(base_add)?;
+		let quote_balance = market_info.quote_balance.checked_add(quote_add)?;
+		let price = quote_balance.checked_div(base_balance).unwrap_or_default();
+
+		Some((price.saturating_mul(PRICE_DENOMINATOR), PRICE_DENOMINATOR))
+	}
+
+	/// Gets the price of one asset denominated in another one,
+	/// regardless of which of them is the BASE asset of their market
+	///
+	/// # Arguments:
+	/// asset: The asset to price
+	/// denomination: The asset the price is denominated in
+	///
+	/// # Returns:
+	/// If Some, the price as (numerator, denominator) given by the reserves of the market
+	/// None if neither (asset, denomination) nor (denomination, asset) is a market
+	pub fn price_any_direction(
+		asset: AssetIdOf<T>,
+		denomination: AssetIdOf<T>,
+	) -> Option<(BalanceOf<T>, BalanceOf<T>)> {
+		if let Some(market_info) = LiquidityPool::<T>::get(MarketId::new(asset, denomination)) {
+			return Some((market_info.quote_balance, market_info.base_balance))
+		}
+		// The inverted market, so the price is the reciprocal of its price
+		let market_info = LiquidityPool::<T>::get(MarketId::new(denomination, asset))?;
+
+		Some((market_info.base_balance, market_info.quote_balance))
+	}
+
+	/// Finds the market trading two assets and the order type which swaps one for the other
+	///
+	/// # Arguments:
+	/// asset_in: The asset put into the pool
+	/// asset_out: The asset taken out of the pool
+	///
+	/// # Returns:
+	/// The market and Sell if asset_in is its BASE asset, Buy if it is its QUOTE asset,
+	/// None if no market trades the two assets
+	fn swap_direction(
+		asset_in: AssetIdOf<T>,
+		asset_out: AssetIdOf<T>,
+	) -> Option<(Market<T>, OrderType)> {
+		let market = MarketId::new(asset_in, asset_out);
+		if LiquidityPool::<T>::contains_key(market) {
+			return Some((market, OrderType::Sell))
+		}
+		if LiquidityPool::<T>::contains_key(market.inverse()) {
+			return Some((market.inverse(), OrderType::Buy))
+		}
+
+		None
+	}
+
+	/// Computes the trade required to move the marginal price of a market to a target price
+	///
+	/// # Arguments:
+	/// market: The market to query
+	/// target_price_num: The numerator of the target price, denominated in QUOTE per BASE
+	/// target_price_den: The denominator of the target price
+	///
+	/// # Returns:
+	/// If Some, the direction of the trade and the fee inclusive amount to put into the pool
+	/// None if the market does not exist, already trades at the target or it cannot be reached
+	pub fn amount_to_reach_price(
+		market: Market<T>,
+		target_price_num: BalanceOf<T>,
+		target_price_den: BalanceOf<T>,
+	) -> Option<(OrderType, BalanceOf<T>)> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let (order_type, net_amount) =
+			Self::net_amount_to_reach_price(market, target_price_num, target_price_den)?;
+
+		// A fee taken from the output leaves the pool along with it,
+		// so the whole input moves the price
+		if !Self::fee_in_input(market_info.fee_asset, order_type) {
+			return Some((order_type, net_amount))
+		}
+
+		Some((order_type, Self::amount_before_fee(net_amount)?))
+	}
+
+	/// Computes the amount which has to enter the pool of a market
+	/// to move its marginal price to a target price
+	///
+	/// # Arguments:
+	/// market: The market to query
+	/// target_price_num: The numerator of the target price, denominated in QUOTE per BASE
+	/// target_price_den: The denominator of the target price
+	///
+	/// # Returns:
+	/// If Some, the direction of the trade and the amount to put into the pool, excluding the fee
+	/// None if the market does not exist, already trades at the target or it cannot be reached
+	fn net_amount_to_reach_price(
+		market: Market<T>,
+		target_price_num: BalanceOf<T>,
+		target_price_den: BalanceOf<T>,
+	) -> Option<(OrderType, BalanceOf<T>)> {
+		if target_price_num.is_zero() || target_price_den.is_zero() {
+			return None
+		}
+		let market_info = LiquidityPool::<T>::get(market)?;
+		if market_info.base_balance.is_zero() || market_info.quote_balance.is_zero() {
+			return None
+		}
+
+		let base_balance = U256::from(market_info.base_balance);
+		let quote_balance = U256::from(market_info.quote_balance);
+		let target_num = U256::from(target_price_num);
+		let target_den = U256::from(target_price_den);
+		let pool_k = base_balance.checked_mul(quote_balance)?;
+
+		// Compare target_num / target_den against quote_balance / base_balance
+		let target = target_num.checked_mul(base_balance)?;
+		let current = quote_balance.checked_mul(target_den)?;
+		let (order_type, reserve_in, new_reserve_in) = match target.cmp(&current) {
+			// Buying BASE pushes the price up until quote_balance^2 = k * price
+			Ordering::Greater => {
+				let new_quote_balance =
+					pool_k.checked_mul(target_num)?.checked_div(target_den)?.integer_sqrt();
+				(OrderType::Buy, quote_balance, new_quote_balance)
+			},
+			// Selling BASE pushes the price down until base_balance^2 = k / price
+			Ordering::Less => {
+				let new_base_balance =
+					pool_k.checked_mul(target_den)?.checked_div(target_num)?.integer_sqrt();
+				(OrderType::Sell, base_balance, new_base_balance)
+			},
+			Ordering::Equal => return None,
+		};
+
+		let net_amount: BalanceOf<T> = new_reserve_in.checked_sub(reserve_in)?.try_into().ok()?;
+		if net_amount.is_zero() {
+			return None
+		}
+
+		Some((order_type, net_amount))
+	}
+
+	/// Computes how much of an amount can be traded before the marginal price
+	/// of a market reaches a limit price
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The maximum amount to trade
+	/// limit_price: The (numerator, denominator) of the price not to trade past,
+	/// denominated in QUOTE per BASE
+	///
+	/// # Returns:
+	/// If Ok, the fee inclusive amount to trade, at most amount_in
+	/// Else the reason why nothing can be traded
+	fn fill_until_price(
+		market: Market<T>,
+		order_type: OrderType,
+		amount_in: BalanceOf<T>,
+		limit_price: (BalanceOf<T>, BalanceOf<T>),
+	) -> Result<BalanceOf<T>, Error<T>> {
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+
+		let fill = match Self::net_amount_to_reach_price(market, limit_price.0, limit_price.1) {
+			Some((direction, net_amount)) if direction == order_type => {
+				if Self::fee_in_input(market_info.fee_asset, order_type) {
+					Self::amount_before_fee(net_amount).ok_or(Error::<T>::Arithmetic)?
+				} else {
+					net_amount
+				}
+			},
+			// The market already trades at or beyond the limit price
+			_ => Zero::zero(),
+		};
+		ensure!(!fill.is_zero(), Error::<T>::PriceLimitReached);
+
+		Ok(fill.min(amount_in))
+	}
+
+	/// Adds a market to the MarketsPerAsset index of both of its assets
+	///
+	/// # Returns:
+	/// If Ok, the market has been indexed
+	/// Else TooManyMarkets if one of the assets can't be part of any more markets
+	fn index_market(market: Market<T>) -> Result<(), Error<T>> {
+		let MarketId { base: base_asset, quote: quote_asset } = market;
+		for asset in [base_asset, quote_asset] {
+			MarketsPerAsset::<T>::try_mutate(asset, |markets| -> Result<(), Error<T>> {
+				if !markets.contains(&market) {
+					markets.try_push(market).map_err(|_| Error::<T>::TooManyMarkets)?;
+				}
+
+				Ok(())
+			})?;
+		}
+
+		Ok(())
+	}
+
+	/// Removes a market from the MarketsPerAsset index of both of its assets
+	fn unindex_market(market: Market<T>) {
+		let MarketId { base: base_asset, quote: quote_asset } = market;
+		for asset in [base_asset, quote_asset] {
+			MarketsPerAsset::<T>::mutate_exists(asset, |opt_markets| {
+				if let Some(markets) = opt_markets {
+					markets.retain(|m| *m != market);
+					if markets.is_empty() {
+						*opt_markets = None;
+					}
+				}
+			});
+		}
+	}
+
+	/// Lists all markets in which an asset is either the BASE or the QUOTE asset
+	///
+	/// # Arguments:
+	/// asset: The asset to look up
+	///
+	/// # Returns:
+	/// The markets containing the asset, in order of creation
+	pub fn markets_for_asset(asset: AssetIdOf<T>) -> Vec<Market<T>> {
+		MarketsPerAsset::<T>::get(asset).into_inner()
+	}
+
+	/// Gets the reserve of one side of a market
+	///
+	/// # Arguments:
+	/// market: The market to query
+	/// side: Whether to get the reserve of the BASE or the QUOTE asset
+	///
+	/// # Returns:
+	/// The reserve, zero if the market does not exist
+	pub fn market_balance(market: Market<T>, side: BaseOrQuote) -> BalanceOf<T> {
+		LiquidityPool::<T>::get(market)
+			.map_or_else(Zero::zero, |market_info| market_info.balance(side))
+	}
+
+	/// Gets all information about a market at once
+	///
+	/// # Arguments:
+	/// market: The market to query
+	///
+	/// # Returns:
+	/// If Some, the reserves, collected fees and fee asset of the market
+	/// None if the market does not exist
+	pub fn market_info(market: Market<T>) -> Option<MarketDetails<BalanceOf<T>>> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+
+		Some(MarketDetails {
+			base_balance: market_info.base_balance,
+			quote_balance: market_info.quote_balance,
+			collected_base_fees: market_info.collected_base_fees,
+			collected_quote_fees: market_info.collected_quote_fees,
+			fee_asset: market_info.fee_asset,
+			flags: market_info.flags,
+		})
+	}
+
+	/// Gets the fees of a market which the next payout distributes,
+	/// including the shares of the market creator and the protocol
+	///
+	/// # Arguments:
+	/// market: The market to query
+	///
+	/// # Returns:
+	/// If Some, the collected (BASE, QUOTE) fees of the market
+	/// None if the market does not exist
+	pub fn pending_payout_total(market: Market<T>) -> Option<(BalanceOf<T>, BalanceOf<T>)> {
+		LiquidityPool::<T>::get(market)
+			.map(|market_info| (market_info.collected_base_fees, market_info.collected_quote_fees))
+	}
+
+	/// Gets who created a market and in which block
+	///
+	/// # Arguments:
+	/// market: The market to query
+	///
+	/// # Returns:
+	/// If Some, (creator, block of creation)
+	/// None if the market does not exist
+	pub fn market_origin(market: Market<T>) -> Option<(T::AccountId, T::BlockNumber)> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+
+		Some((market_info.creator, market_info.created_at))
+	}
+
+	/// Computes the minimum output a client should accept for a trade,
+	/// given the slippage it tolerates from the current quote
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// who: The trader, whose taker fee is quoted
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+	/// slippage: The tolerated relative deviation from the quoted output
+	///
+	/// # Returns:
+	/// If Some, the quoted output reduced by the slippage, rounded down
+	/// Else None if the market does not exist or the quote fails
+	pub fn min_out_for_slippage(
+		market: Market<T>,
+		who: &T::AccountId,
+		order_type: OrderType,
+		amount_in: BalanceOf<T>,
+		slippage: Perbill,
+	) -> Option<BalanceOf<T>> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let quoted =
+			Self::get_received_amount(market, &market_info, who, order_type, amount_in).ok()?;
+
+		Some(slippage.left_from_one().mul_floor(quoted))
+	}
+
+	/// Computes the input a trade requires to receive exactly a given output,
+	/// including the taker fee the trade would pay right now
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_out: The amount of BASE asset to buy, or QUOTE asset to receive when selling
+	///
+	/// # Returns:
+	/// If Some, the amount of QUOTE asset to spend when buying, or BASE asset to sell when selling
+	/// None if the market does not exist or amount_out exceeds its reserves
+	pub fn amount_in_for_exact_out(
+		market: Market<T>,
+		order_type: OrderType,
+		amount_out: BalanceOf<T>,
+	) -> Option<BalanceOf<T>> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+
+		let (side_in, side_out) = order_type.sides();
+		let (asset_in, asset_out) = (market.asset(side_in), market.asset(side_out));
+		let (reserve_in, reserve_out) =
+			(market_info.balance(side_in), market_info.balance(side_out));
+
+		// Same order of operations as do_trade, but in reverse
+		if Self::fee_in_input(market_info.fee_asset, order_type) {
+			let deposit_amount = Self::swap_input(reserve_in, reserve_out, amount_out)?;
+			Self::amount_before_trade_fee(market, &market_info, asset_in, deposit_amount)
+		} else {
+			let withdraw_amount =
+				Self::amount_before_trade_fee(market, &market_info, asset_out, amount_out)?;
+			Self::swap_input(reserve_in, reserve_out, withdraw_amount)
+		}
+	}
+
+	/// Estimates the annual yield of providing liquidity to a market,
+	/// by annualizing the fees collected since they were last paid out or reinvested.
+	///
+	/// Assumes that blocks are produced at the rate of BlocksPerYear
+	/// and that the trading activity since the last payout is representative.
+	/// The lookback window starts at FeesCollectedSince, or the creation of the market,
+	/// so it also holds for round robin and early payouts.
+	/// As both reserves of a pool have the same value,
+	/// the yield is the mean of the fees relative to each reserve.
+	///
+	/// # Arguments:
+	/// market: The market to query
+	///
+	/// # Returns:
+	/// If Some, the estimated APR, saturating at 100%
+	/// None if the market does not exist, has no reserves or the computation overflows
+	pub fn estimated_apr(market: Market<T>) -> Option<Perbill> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		if market_info.base_balance.is_zero() || market_info.quote_balance.is_zero() {
+			return None
+		}
+
+		// The fees of the current block are included
+		let since = FeesCollectedSince::<T>::get(market).unwrap_or(market_info.created_at);
+		let lookback: u128 = <frame_system::Pallet<T>>::block_number()
+			.saturating_sub(since)
+			.saturating_add(One::one())
+			.unique_saturated_into();
+		let blocks_per_year: u128 = T::BlocksPerYear::get().unique_saturated_into();
+
+		let (base, quote) =
+			(U256::from(market_info.base_balance), U256::from(market_info.quote_balance));
+		// (base_fees / base + quote_fees / quote) / 2, annualized
+		let numerator = U256::from(market_info.collected_base_fees)
+			.checked_mul(quote)?
+			.checked_add(U256::from(market_info.collected_quote_fees).checked_mul(base)?)?
+			.checked_mul(U256::from(blocks_per_year))?
+			.checked_mul(U256::from(Perbill::ACCURACY))?;
+		let denominator = U256::from(2u8)
+			.checked_mul(base)?
+			.checked_mul(quote)?
+			.checked_mul(U256::from(lookback))?;
+		let parts = (numerator / denominator).min(U256::from(Perbill::ACCURACY));
+
+		Some(Perbill::from_parts(parts.as_u32()))
+	}
+
+	/// Computes the impermanent loss of a liquidity provider,
+	/// by comparing the value of his claim on the reserves against the value
+	/// of simply holding the deposited amounts, both at the current price.
+	/// No entry price is stored in LiqProvisionPool, as the deposited amounts already carry it:
+	/// the value of holding them is the same as of holding every deposit at its own entry price,
+	/// and withdrawals reduce both amounts by the same fraction
+	///
+	/// # Arguments:
+	/// market: The market to query
+	/// who: The liquidity provider
+	///
+	/// # Returns:
+	/// If Some, the relative loss versus holding, zero if the claim is worth more
+	/// None if the market does not exist or the account provides no liquidity in it
+	pub fn impermanent_loss(market: Market<T>, who: &T::AccountId) -> Option<Perbill> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		// The provision records the deposited amounts, less the withdrawn ones
+		let (base_deposit, quote_deposit) = LiqProvisionPool::<T>::get(market, who);
+		if base_deposit.is_zero() && quote_deposit.is_zero() {
+			return None
+		}
+		let ((base_claim, quote_claim), _) =
+			Self::proportional_withdrawal(market, who, Perbill::one()).ok()?;
+
+		// Both values are denominated in QUOTE, multiplied by the BASE reserve
+		// to avoid dividing by the price
+		let base_reserve = U256::from(market_info.base_balance);
+		let quote_reserve = U256::from(market_info.quote_balance);
+		let value = |base: BalanceOf<T>, quote: BalanceOf<T>| {
+			U256::from(base)
+				.checked_mul(quote_reserve)?
+				.checked_add(U256::from(quote).checked_mul(base_reserve)?)
+		};
+		let hold_value = value(base_deposit, quote_deposit)?;
+		let claim_value = value(base_claim, quote_claim)?;
+		if hold_value.is_zero() {
+			return None
+		}
+
+		let loss = hold_value.saturating_sub(claim_value);
+		let parts = loss.checked_mul(U256::from(Perbill::ACCURACY))? / hold_value;
+
+		Some(Perbill::from_parts(parts.as_u32()))
+	}
+
+	/// Compares the cross price implied by two markets sharing an asset
+	/// against the price of the market directly trading the other two assets
+	///
+	/// # Arguments:
+	/// market_a: The first market
+	/// market_b: The second market, sharing an asset with the first one
+	///
+	/// # Returns:
+	/// If Some, the deviation of the implied from the direct price, relative to the direct price
+	/// None if the markets share no asset, one of them does not exist or there is no direct market
+	pub fn arbitrage_spread(market_a: Market<T>, market_b: Market<T>) -> Option<Perbill> {
+		let shared_asset = if market_a.base == market_b.base || market_a.base == market_b.quote {
+			market_a.base
+		} else if market_a.quote == market_b.base || market_a.quote == market_b.quote {
+			market_a.quote
+		} else {
+			return None
+		};
+
+		let (asset_x, price_x) = Self::price_denominated_in(market_a, shared_asset)?;
+		let (asset_y, price_y) = Self::price_denominated_in(market_b, shared_asset)?;
+		if asset_x == asset_y {
+			return None
+		}
+		// The price of X denominated in Y, as implied by the two markets
+		let implied_price =
+			price_x.checked_mul(U256::exp10(CROSS_PRICE_DECIMALS))?.checked_div(price_y)?;
+
+		let direct_market = MarketId::new(asset_x, asset_y);
+		let direct_market = if LiquidityPool::<T>::contains_key(direct_market) {
+			direct_market
+		} else {
+			direct_market.inverse()
+		};
+		let (_, direct_price) = Self::price_denominated_in(direct_market, asset_y)?;
+
+		let deviation = if implied_price > direct_price {
+			implied_price - direct_price
+		} else {
+			direct_price - implied_price
+		};
+		let parts = deviation
+			.checked_mul(U256::from(Perbill::ACCURACY))?
+			.checked_div(direct_price)?
+			.min(U256::from(Perbill::ACCURACY));
+
+		Some(Perbill::from_parts(parts.as_u32()))
+	}
+
+	/// Gets the price of one asset of a market, denominated in the other asset,
+	/// scaled by 10^CROSS_PRICE_DECIMALS
+	///
+	/// # Arguments:
+	/// market: The market to query
+	/// denomination: The asset of the market to denominate the price in
+	///
+	/// # Returns:
+	/// If Some, the other asset of the market and its price
+	/// None if the market does not exist or has no reserve of the denomination asset
+	fn price_denominated_in(
+		market: Market<T>,
+		denomination: AssetIdOf<T>,
+	) -> Option<(AssetIdOf<T>, U256)> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let (asset, numerator, denominator) = if market.quote == denomination {
+			(market.base, market_info.quote_balance, market_info.base_balance)
+		} else {
+			(market.quote, market_info.base_balance, market_info.quote_balance)
+		};
+		if denominator.is_zero() {
+			return None
+		}
+		let price = U256::from(numerator).checked_mul(U256::exp10(CROSS_PRICE_DECIMALS))?
+			/ U256::from(denominator);
+
+		Some((asset, price))
+	}
+
+	/// Reports whether a market is in a healthy state
+	///
+	/// # Arguments:
+	/// market: The market to check
+	///
+	/// # Returns:
+	/// If Some, the health report of the market
+	/// None if the market does not exist
+	pub fn pool_health(market: Market<T>) -> Option<PoolHealth<BalanceOf<T>>> {
+		let market_info = LiquidityPool::<T>::get(market)?;
+		let MarketId { base: base_asset, quote: quote_asset } = market;
+		let pool_account = Self::pool_account();
+
+		// The pool account is shared among all markets,
+		// so its balance has to be compared against the reserves of every market of an asset
+		let base_recorded = Self::recorded_reserve(base_asset);
+		let quote_recorded = Self::recorded_reserve(quote_asset);
+		let base_actual = Self::balance(base_asset, &pool_account);
+		let quote_actual = Self::balance(quote_asset, &pool_account);
+
+		Some(PoolHealth {
+			reserves_non_zero: !market_info.base_balance.is_zero()
+				&& !market_info.quote_balance.is_zero(),
+			reserves_match_balances: base_recorded == base_actual && quote_recorded == quote_actual,
+			base_surplus: base_actual.saturating_sub(base_recorded),
+			quote_surplus: quote_actual.saturating_sub(quote_recorded),
+		})
+	}
+
+	/// Sums up the reserves of an asset over all markets it is part of
+	fn recorded_reserve(asset: AssetIdOf<T>) -> BalanceOf<T> {
+		MarketsPerAsset::<T>::get(asset)
+			.iter()
+			.filter_map(|market| {
+				let market_info = LiquidityPool::<T>::get(market)?;
+				let MarketId { base: base_asset, quote: quote_asset } = *market;
+
+				let mut reserve: BalanceOf<T> = Zero::zero();
+				if base_asset == asset {
+					reserve = reserve.saturating_add(market_info.base_balance);
+				}
+				if quote_asset == asset {
+					reserve = reserve.saturating_add(market_info.quote_balance);
+				}
+
+				Some(reserve)
+			})
+			.fold(Zero::zero(), |acc: BalanceOf<T>, reserve| acc.saturating_add(reserve))
+	}
+
+	/// Checks whether an account is the only one with liquidity in a pool
+	fn is_sole_provider(market: Market<T>, who: &T::AccountId) -> bool {
+		LiqProvisionPool::<T>::contains_key(market, who)
+			&& LiqProvisionPool::<T>::iter_prefix(market).all(|(account, (base, quote))| {
+				account == *who || (base.is_zero() && quote.is_zero())
+			})
+	}
+
+	/// Removes a market entirely and returns all of its reserves to the sole remaining
+	/// liquidity provider. The fees not yet paid out are split by distribute_fees,
+	/// so the creator and the treasury still receive their slices
+	///
+	/// # Arguments:
+	/// who: The sole remaining liquidity provider
+	/// market: The market to remove
+	/// market_info: The information of the market
+	///
+	/// # Returns:
+	/// If Ok, the market has been removed
+	/// Else the transfer error
+	fn do_remove_market(
+		who: &T::AccountId,
+		market: Market<T>,
+		market_info: &MarketInfo<T>,
+	) -> DispatchResult {
+		let MarketId { base: base_asset, quote: quote_asset } = market;
+		let pool_account = Self::pool_account();
+
+		// Return the reserves, the pool account does not need to be kept alive for them
+		Self::safe_transfer(base_asset, &pool_account, who, market_info.base_balance, false)?;
+		Self::safe_transfer(quote_asset, &pool_account, who, market_info.quote_balance, false)?;
+
+		// As the only provider, the whole slice of the providers belongs to the caller,
+		// whatever he provided of either asset
+		let provisions = [(who.clone(), One::one())];
+		Self::distribute_fees(
+			market,
+			base_asset,
+			market_info.collected_base_fees,
+			&market_info.creator,
+			&provisions,
+		)?;
+		Self::distribute_fees(
+			market,
+			quote_asset,
+			market_info.collected_quote_fees,
+			&market_info.creator,
+			&provisions,
+		)?;
+
+		LiquidityPool::<T>::remove(market);
+		MarketMetadata::<T>::remove(market);
+		FeeRemainders::<T>::remove(market);
+		let _ = LiqProvisionPool::<T>::clear_prefix(market, u32::MAX, None);
+		let _ = ProvidedSince::<T>::clear_prefix(market, u32::MAX, None);
+		ProviderCount::<T>::remove(market);
+		FeesCollectedSince::<T>::remove(market);
+		EarlyPayoutDue::<T>::remove(market);
+		Self::unindex_market(market);
+
+		Self::deposit_event(Event::MarketRemoved(
+			who.clone(),
+			market,
+			market_info.base_balance,
+			market_info.quote_balance,
+		));
+
+		Ok(())
+	}
+
+	/// Withdraws liquidity from a pool and reduces the liquidity provision of the user
+	///
+	/// # Arguments:
+	/// who: The liquidity provider
+	/// market: The liquidity pool to withdraw from
+	/// amounts_out: The (BASE, QUOTE) amounts transferred to the user
+	/// provisions: The (BASE, QUOTE) amounts deducted from the liquidity provision of the user
+	/// allow_death: Whether the pool account may be reaped by the transfers out of the pool
+	///
+	/// # Returns:
+	/// If Ok, the liquidity has been withdrawn
+	/// Else the reason why the withdrawal failed
+	fn do_withdraw_liquidity(
+		who: &T::AccountId,
+		market: Market<T>,
+		amounts_out: (BalanceOf<T>, BalanceOf<T>),
+		provisions: (BalanceOf<T>, BalanceOf<T>),
+		allow_death: bool,
+	) -> DispatchResult {
+		let (base_amount, quote_amount) = amounts_out;
+		let (base_provision, quote_provision) = provisions;
+
+		// Check that the market exists and allows withdrawals
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+		ensure!(market_info.flags.allow_withdraw, Error::<T>::OperationDisabled);
+
+		let MarketId { base: base_asset, quote: quote_asset } = market;
+		let pool_account = Self::pool_account();
+
+		// ensure the user has enough balance in the pool to withdraw
+		let (users_base_balance, users_quote_balance) = LiqProvisionPool::<T>::get(market, who);
+		ensure!(users_base_balance >= base_provision, Error::<T>::NotEnoughBalance);
+		ensure!(users_quote_balance >= quote_provision, Error::<T>::NotEnoughBalance);
+
+		// All or dust: a pool with dust reserves can't be traded in anymore,
+		// so the withdrawal takes everything if that is all which would remain
+		let dust_threshold = <T as Config>::DustThreshold::get();
+		// The provision of a user may exceed the reserves if the accounting is off,
+		// so fail clearly instead of deep inside the transfer
+		let base_left = market_info
+			.base_balance
+			.checked_sub(base_amount)
+			.ok_or(Error::<T>::InsufficientLiquidity)?;
+		let quote_left = market_info
+			.quote_balance
+			.checked_sub(quote_amount)
+			.ok_or(Error::<T>::InsufficientLiquidity)?;
+		// The pool account has to actually hold the amounts, as the reserves are only recorded
+		ensure!(
+			Self::balance(base_asset, &pool_account) >= base_amount,
+			Error::<T>::InsufficientLiquidity
+		);
+		ensure!(
+			Self::balance(quote_asset, &pool_account) >= quote_amount,
+			Error::<T>::InsufficientLiquidity
+		);
+		if base_left < dust_threshold || quote_left < dust_threshold {
+			ensure!(Self::is_sole_provider(market, who), Error::<T>::WouldLeaveDust);
+			return Self::do_remove_market(who, market, &market_info)
+		}
+
+		// update the reserves of the market, which may never be drained completely
+		// as that would leave a pool behind which can't be traded in anymore
+		ensure!(!base_left.is_zero() && !quote_left.is_zero(), Error::<T>::WouldDrainPool);
+		LiquidityPool::<T>::try_mutate(market, |opt_market_info| -> DispatchResult {
+			let market_info = opt_market_info.as_mut().ok_or(Error::<T>::MarketDoesNotExist)?;
+			market_info.base_balance = base_left;
+			market_info.quote_balance = quote_left;
+
+			Ok(())
+		})?;
+
+		// transfer out BASE asset from pool
+		Self::safe_transfer(base_asset, &pool_account, who, base_amount, !allow_death)?;
+		// transfer out QUOTE asset from pool
+		Self::safe_transfer(quote_asset, &pool_account, who, quote_amount, !allow_death)?;
+
+		// update LiqProvisionPool
+		Self::reduce_provision(market, who, provisions)?;
+
+		Self::deposit_event_indexed(
+			market,
+			who,
+			Event::LiquidityWithdrawn(who.clone(), market, base_amount, quote_amount),
+		);
+
+		Ok(())
+	}
+
+	/// Reduces the liquidity provision of a user.
+	/// A provision reaching zero in both assets is removed,
+	/// which frees its slot of the MaxProvidersPerMarket
+	///
+	/// # Arguments:
+	/// market: The liquidity pool the provision is in
+	/// who: The liquidity provider
+	/// provisions: The (BASE, QUOTE) amounts deducted from the liquidity provision of the user
+	fn reduce_provision(
+		market: Market<T>,
+		who: &T::AccountId,
+		provisions: (BalanceOf<T>, BalanceOf<T>),
+	) -> DispatchResult {
+		let (base_provision, quote_provision) = provisions;
+		let (base_balance, quote_balance) = LiqProvisionPool::<T>::get(market, who);
+		let base_balance =
+			base_balance.checked_sub(base_provision).ok_or(Error::<T>::Arithmetic)?;
+		let quote_balance =
+			quote_balance.checked_sub(quote_provision).ok_or(Error::<T>::Arithmetic)?;
+
+		if base_balance.is_zero() && quote_balance.is_zero() {
+			LiqProvisionPool::<T>::remove(market, who);
+			ProvidedSince::<T>::remove(market, who);
+			ProviderCount::<T>::mutate(market, |count| *count = count.saturating_sub(1));
+		} else {
+			LiqProvisionPool::<T>::insert(market, who, (base_balance, quote_balance));
+		}
+
+		Ok(())
+	}
+
+	/// Computes a fraction of the claim of a liquidity provider on the reserves of a pool.
+	/// The claim on each reserve is proportional to the share of the provision of the user
+	/// in all provisions of that asset
+	///
+	/// # Arguments:
+	/// market: The liquidity pool to withdraw from
+	/// who: The liquidity provider
+	/// fraction: The fraction of the claim to withdraw
+	///
+	/// # Returns:
+	/// If Ok, the (BASE, QUOTE) amounts to transfer out, rounded down,
+	/// and the (BASE, QUOTE) amounts to deduct from the provision of the user, rounded up
+	/// Else some error
+	fn proportional_withdrawal(
+		market: Market<T>,
+		who: &T::AccountId,
+		fraction: Perbill,
+	) -> Result<((BalanceOf<T>, BalanceOf<T>), (BalanceOf<T>, BalanceOf<T>)), Error<T>> {
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+		let (base_provision, quote_provision) = LiqProvisionPool::<T>::get(market, who);
+
+		let (total_base_provision, total_quote_provision) =
+			LiqProvisionPool::<T>::iter_prefix_values(market)
+				.try_fold(
+					(BalanceOf::<T>::zero(), BalanceOf::<T>::zero()),
+					|(total_base, total_quote), (base, quote)| {
+						Some((total_base.checked_add(base)?, total_quote.checked_add(quote)?))
+					},
+				)
+				.ok_or(Error::<T>::Arithmetic)?;
+
+		let base_claim =
+			Self::proportional(base_provision, total_base_provision, market_info.base_balance)?;
+		let quote_claim =
+			Self::proportional(quote_provision, total_quote_provision, market_info.quote_balance)?;
+
+		Ok((
+			(fraction.mul_floor(base_claim), fraction.mul_floor(quote_claim)),
+			(fraction.mul_ceil(base_provision), fraction.mul_ceil(quote_provision)),
+		))
+	}
+
+	/// Lists every market in which an account provides liquidity
+	///
+	/// # Arguments:
+	/// who: The liquidity provider
+	///
+	/// # Returns:
+	/// The markets together with the (BASE, QUOTE) liquidity provided by the account
+	///
+	/// # Complexity:
+	/// O(n) in the number of LiqProvisionPool entries, so this is meant for runtime api usage only
+	pub fn positions_of(who: &T::AccountId) -> Vec<(Market<T>, (BalanceOf<T>, BalanceOf<T>))> {
+		LiqProvisionPool::<T>::iter()
+			.filter(|(_, account, _)| account == who)
+			.map(|(market, _, provision)| (market, provision))
+			.collect()
+	}
+
+	/// Lists every liquidity provider of a market
+	///
+	/// # Arguments:
+	/// market: The market to look up
+	///
+	/// # Returns:
+	/// The accounts together with the (BASE, QUOTE) liquidity they provide
+	///
+	/// # Complexity:
+	/// O(n) in the number of liquidity providers of the market,
+	/// so this is meant for runtime api usage only
+	pub fn providers(market: Market<T>) -> Vec<(T::AccountId, (BalanceOf<T>, BalanceOf<T>))> {
+		LiqProvisionPool::<T>::iter_prefix(market).collect()
+	}
+
+	/// Lists a page of the liquidity providers of a market.
+	/// Pages are stable as long as no liquidity provider joins or leaves the market
+	///
+	/// # Arguments:
+	/// market: The market to look up
+	/// offset: The number of liquidity providers to skip
+	/// limit: The maximum number of liquidity providers to return
+	///
+	/// # Returns:
+	/// The accounts together with the (BASE, QUOTE) liquidity they provide
+	pub fn providers_paged(
+		market: Market<T>,
+		offset: u32,
+		limit: u32,
+	) -> Vec<(T::AccountId, (BalanceOf<T>, BalanceOf<T>))> {
+		LiqProvisionPool::<T>::iter_prefix(market)
+			.skip(offset as usize)
+			.take(limit as usize)
+			.collect()
+	}
+
+	/// Computes the smallest amount which still leaves the given amount after the fee is deducted
+	///
+	/// # Arguments:
+	/// net_amount: The amount which should remain after the taker fee
+	///
+	/// # Returns:
+	/// If Some, the fee inclusive amount
+	/// Else None on arithmetic errors
+	fn amount_before_fee(net_amount: BalanceOf<T>) -> Option<BalanceOf<T>> {
+		if net_amount.is_zero() {
+			return Some(Zero::zero())
+		}
+		let (fee_numerator, fee_denominator) = <T as Config>::TakerFee::get();
+		let net_fraction = fee_denominator.checked_sub(fee_numerator)?;
+
+		// The fee is rounded down, so amount - floor(amount * num / den) >= net_amount
+		// holds for every amount greater than (net_amount - 1) * den / (den - num)
+		let amount: BalanceOf<T> = U256::from(net_amount - 1)
+			.checked_mul(U256::from(fee_denominator))?
+			.checked_div(U256::from(net_fraction))?
+			.checked_add(U256::one())?
+			.try_into()
+			.ok()?;
+
+		// Paying at most MaxAbsoluteFee may require less
+		Some(amount.min(net_amount.saturating_add(<T as Config>::MaxAbsoluteFee::get())))
+	}
 
 	/// Helper function to get the account balance easily
 	///
 	/// # Arguments:
-	/// asset_id: The asset were trying to query
-	/// who: The account for which the balance should be retrived
+	/// asset_id: The asset were trying to query
+	/// who: The account for which the balance should be retrived
+	///
+	/// # Returns:
+	/// The balance of a user for a given asset
+	///
+	/// # Weight:
+	/// This function has a DB read weight of 1, as it retreives the balance
+	fn balance(asset_id: AssetIdOf<T>, who: &T::AccountId) -> BalanceOf<T> {
+		<<T as Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::balance(
+			asset_id, who,
+		)
+	}
+
+	/// Computes the fee amount a trade in a market pays.
+	/// The fractional part of the fee is not rounded away, but stored in FeeRemainders
+	/// and added to the fee of the next trade in the same asset,
+	/// so many small trades still pay their share
+	///
+	/// # Arguments:
+	/// market: The market the trade happens in
+	/// market_info: The information of the market
+	/// who: The trader, whose traded volume decides the FeeTiers
+	/// asset: The asset the fee is paid in
+	/// amount: The amount to exchange from which the fees are deducted
+	///
+	/// # Returns:
+	/// If ok, the fee amount, zero during the FeeGracePeriod of the market or for FeeExempt traders
+	/// Else the arithmetic error
+	fn fee_from_amount(
+		market: Market<T>,
+		market_info: &MarketInfo<T>,
+		who: &T::AccountId,
+		asset: AssetIdOf<T>,
+		amount: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, Error<T>> {
+		let (fee, new_remainder) = Self::trade_fee(market, market_info, who, asset, amount)?;
+		if let Some(new_remainder) = new_remainder {
+			FeeRemainders::<T>::mutate(market, |(base_remainder, quote_remainder)| {
+				*(if asset == market.base { base_remainder } else { quote_remainder }) =
+					new_remainder
+			});
+		}
+
+		Ok(fee)
+	}
+
+	/// Computes the fee amount a trade in a market pays, without storing anything,
+	/// so quotes deduct exactly the fee the trade would pay
+	///
+	/// # Arguments:
+	/// market: The market the trade happens in
+	/// market_info: The information of the market
+	/// who: The trader, whose traded volume decides the FeeTiers
+	/// asset: The asset the fee is paid in
+	/// amount: The amount to exchange from which the fees are deducted
 	///
 	/// # Returns:
-	/// The balance of a user for a given asset
+	/// If ok, the fee amount, including the remainder carried over in FeeRemainders,
+	/// and the new remainder to store, None if the remainder is left unchanged
+	/// Else the arithmetic error
+	fn trade_fee(
+		market: Market<T>,
+		market_info: &MarketInfo<T>,
+		who: &T::AccountId,
+		asset: AssetIdOf<T>,
+		amount: BalanceOf<T>,
+	) -> Result<(BalanceOf<T>, Option<u128>), Error<T>> {
+		if Self::in_fee_grace_period(market_info) || Self::is_fee_exempt(who) {
+			return Ok((Zero::zero(), None))
+		}
+		let (_, fee_denominator) = <T as Config>::TakerFee::get();
+		let fee_numerator = Self::fee_numerator(market, who);
+		let max_fee = <T as Config>::MaxAbsoluteFee::get();
+		let (base_remainder, quote_remainder) = FeeRemainders::<T>::get(market);
+		let remainder = if asset == market.base { base_remainder } else { quote_remainder };
+
+		// amount * fee_numerator + remainder, in units of 1 / fee_denominator
+		let fee_units = U256::from(amount)
+			.checked_mul(U256::from(fee_numerator))
+			.ok_or(Error::<T>::Arithmetic)?
+			.checked_add(U256::from(remainder))
+			.ok_or(Error::<T>::Arithmetic)?;
+		let fee: BalanceOf<T> = fee_units
+			.checked_div(U256::from(fee_denominator))
+			.ok_or(Error::<T>::Arithmetic)?
+			.try_into()
+			.map_err(|_| Error::<T>::Arithmetic)?;
+
+		// A capped fee already exceeds the fractional part, which is kept for later
+		if fee >= max_fee {
+			return Ok((max_fee, None))
+		}
+		let new_remainder = fee_units
+			.checked_rem(U256::from(fee_denominator))
+			.ok_or(Error::<T>::Arithmetic)?
+			.as_u128();
+
+		Ok((fee, Some(new_remainder)))
+	}
+
+	/// Looks up the taker fee numerator of a trader in a market.
+	/// The FeeTiers share the TakerFee denominator, so the FeeRemainders stay comparable
 	///
-	/// # Weight:
-	/// This function has a DB read weight of 1, as it retreives the balance
-	fn balance(asset_id: AssetIdOf<T>, who: &T::AccountId) -> BalanceOf<T> {
-		<<T as Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::balance(
-			asset_id, who,
-		)
+	/// # Arguments:
+	/// market: The market the trade happens in
+	/// who: The trader
+	///
+	/// # Returns:
+	/// The lowest numerator of the TakerFee and the FeeTiers the traded volume reached
+	fn fee_numerator(market: Market<T>, who: &T::AccountId) -> u32 {
+		let (fee_numerator, _) = <T as Config>::TakerFee::get();
+		let volume = TradedVolume::<T>::get(market, who);
+
+		<T as Config>::FeeTiers::get()
+			.into_iter()
+			.filter(|(threshold, _)| volume >= *threshold)
+			.fold(fee_numerator, |lowest, (_, numerator)| lowest.min(numerator))
 	}
 
-	/// Computes the fee amount
+	/// Inverts fee_from_amount without touching the FeeRemainders.
+	/// Unlike amount_before_fee, the carried over remainder and the FeeGracePeriod are included
 	///
 	/// # Arguments:
-	/// amount: The amount to exchange from which the fees are deducted
+	/// market: The market the trade happens in
+	/// market_info: The information of the market
+	/// asset: The asset the fee is paid in
+	/// amount: The amount which has to be left after deducting the fee
 	///
 	/// # Returns:
-	/// If ok, the fee amount
-	/// Else the arithmetic error
-	fn fee_from_amount(amount: BalanceOf<T>) -> Result<BalanceOf<T>, Error<T>> {
+	/// If Some, the smallest amount which is still at least amount after the fee
+	/// None on arithmetic overflow
+	fn amount_before_trade_fee(
+		market: Market<T>,
+		market_info: &MarketInfo<T>,
+		asset: AssetIdOf<T>,
+		amount: BalanceOf<T>,
+	) -> Option<BalanceOf<T>> {
+		if Self::in_fee_grace_period(market_info) {
+			return Some(amount)
+		}
 		let (fee_numerator, fee_denominator) = <T as Config>::TakerFee::get();
+		let (fee_numerator, fee_denominator) =
+			(U256::from(fee_numerator), U256::from(fee_denominator));
+		let (base_remainder, quote_remainder) = FeeRemainders::<T>::get(market);
+		let remainder =
+			U256::from(if asset == market.base { base_remainder } else { quote_remainder });
+		let amount = U256::from(amount);
+
+		// What is left of a gross amount after the uncapped fee of fee_from_amount
+		let after_fee = |gross: U256| -> Option<U256> {
+			let fee = gross
+				.checked_mul(fee_numerator)?
+				.checked_add(remainder)?
+				.checked_div(fee_denominator)?;
+			gross.checked_sub(fee)
+		};
+		// gross * (fee_denominator - fee_numerator) >= amount * fee_denominator + remainder
+		// always suffices, but the fee rounds down, so a slightly smaller amount may suffice too
+		let uncapped = fee_denominator
+			.checked_sub(fee_numerator)
+			.filter(|share| !share.is_zero())
+			.and_then(|share| {
+				let mut gross = amount
+					.checked_mul(fee_denominator)?
+					.checked_add(remainder)?
+					.checked_add(share - U256::one())?
+					.checked_div(share)?;
+				while !gross.is_zero()
+					&& after_fee(gross - U256::one()).map_or(false, |net| net >= amount)
+				{
+					gross -= U256::one();
+				}
+				Some(gross)
+			});
+		// Paying MaxAbsoluteFee on top always suffices as well
+		let capped = amount.checked_add(U256::from(<T as Config>::MaxAbsoluteFee::get()));
+
+		let gross = match (uncapped, capped) {
+			(Some(uncapped), Some(capped)) => uncapped.min(capped),
+			(uncapped, capped) => uncapped.or(capped)?,
+		};
+
+		gross.try_into().ok()
+	}
+
+	/// Checks whether an account pays no taker fee, as it is one of the FeeExempt accounts
+	pub fn is_fee_exempt(who: &T::AccountId) -> bool {
+		FeeExempt::<T>::contains_key(who)
+	}
+
+	/// Checks whether a market is still within the FeeGracePeriod after its creation
+	fn in_fee_grace_period(market_info: &MarketInfo<T>) -> bool {
+		let grace_end = market_info.created_at.saturating_add(<T as Config>::FeeGracePeriod::get());
+
+		<frame_system::Pallet<T>>::block_number() < grace_end
+	}
+
+	/// Checks that the price implied by an amount of BASE and QUOTE asset,
+	/// e.g. the liquidity of a pool or the amounts of a trade,
+	/// is within the tolerance of an expected price
+	///
+	/// # Arguments:
+	/// base_amount: The amount of BASE asset
+	/// quote_amount: The amount of QUOTE asset
+	/// expected_price: The expected price and the tolerated relative deviation from it
+	/// error: The error if the implied price is not within the tolerance
+	///
+	/// # Returns:
+	/// If Ok, the implied price is within the tolerance
+	/// Else the given error
+	fn ensure_expected_price(
+		base_amount: BalanceOf<T>,
+		quote_amount: BalanceOf<T>,
+		expected_price: &ExpectedPrice<BalanceOf<T>>,
+		error: Error<T>,
+	) -> Result<(), Error<T>> {
+		ensure!(!base_amount.is_zero() && !expected_price.denominator.is_zero(), error);
+
+		// Compare quote_amount / base_amount against numerator / denominator
+		// by bringing both to the common denominator base_amount * denominator
+		let implied = U256::from(quote_amount) * U256::from(expected_price.denominator);
+		let expected = U256::from(expected_price.numerator) * U256::from(base_amount);
+		let deviation = if implied > expected { implied - expected } else { expected - implied };
 
-		let a = amount
-			.checked_mul(BalanceOf::<T>::from(fee_numerator))
+		// deviation / expected <= tolerance
+		let scaled_deviation = deviation
+			.checked_mul(U256::from(Perbill::ACCURACY))
 			.ok_or(Error::<T>::Arithmetic)?;
+		let max_deviation = expected
+			.checked_mul(U256::from(expected_price.tolerance.deconstruct()))
+			.ok_or(Error::<T>::Arithmetic)?;
+		ensure!(scaled_deviation <= max_deviation, error);
 
-		a.checked_div(BalanceOf::<T>::from(fee_denominator))
-			.ok_or(Error::<T>::Arithmetic)
+		Ok(())
 	}
 
-	/// Performs the payout of collected fee to liquidity providers
-	/// Triggered every 10 blocks by offchain worker
+	/// Checks that a trade does not exceed the configured MaxTradeFraction
 	///
-	/// # Complexity:
-	/// O(n^2) currently which should be improved upon
-	fn do_liquidity_provider_payout() -> Result<(), Error<T>> {
+	/// # Arguments:
+	/// amount_in: The amount the user puts into the pool
+	/// reserve_in: The pools reserve of the asset being put in
+	///
+	/// # Returns:
+	/// If Ok, the trade size is acceptable
+	/// Else TradeTooLarge
+	fn ensure_trade_size(
+		amount_in: BalanceOf<T>,
+		reserve_in: BalanceOf<T>,
+	) -> Result<(), Error<T>> {
+		if let Some(max_fraction) = <T as Config>::MaxTradeFraction::get() {
+			ensure!(amount_in <= max_fraction.mul_floor(reserve_in), Error::<T>::TradeTooLarge);
+		}
+
+		Ok(())
+	}
+
+	/// Checks that the price impact of a trade does not exceed a maximum slippage
+	///
+	/// # Arguments:
+	/// amount_in: The amount the user puts into the pool
+	/// reserve_in: The pools reserve of the asset being put in
+	/// max_slippage: The maximum price impact, where one means no cap
+	///
+	/// # Returns:
+	/// If Ok, the price impact is acceptable
+	/// Else PriceImpactTooHigh
+	fn ensure_price_impact(
+		amount_in: BalanceOf<T>,
+		reserve_in: BalanceOf<T>,
+		max_slippage: Perbill,
+	) -> Result<(), Error<T>> {
+		if max_slippage.is_one() {
+			return Ok(())
+		}
+
+		// With a constant product, the output falls short of the marginal price
+		// by amount_in / (reserve_in + amount_in), fees aside
+		let amount_in = U256::from(amount_in);
+		let impact = amount_in
+			.checked_mul(U256::from(Perbill::ACCURACY))
+			.ok_or(Error::<T>::Arithmetic)?;
+		let max_impact = amount_in
+			.checked_add(U256::from(reserve_in))
+			.and_then(|total| total.checked_mul(U256::from(max_slippage.deconstruct())))
+			.ok_or(Error::<T>::Arithmetic)?;
+		ensure!(impact <= max_impact, Error::<T>::PriceImpactTooHigh);
+
+		Ok(())
+	}
+
+	/// Checks the slippage of a buy or sell against the DefaultMaxSlippage,
+	/// as they take no minimum output the user would accept.
+	/// The output is quoted with the taker fee of the trader, so the check admits
+	/// exactly the trades which would receive enough at their FeeTiers or fee exemption
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// who: The trader
+	/// order_type: Whether the BASE asset is bought or sold
+	/// amount_in: The amount the user puts into the pool
+	///
+	/// # Returns:
+	/// If Ok, the quoted output is at most DefaultMaxSlippage below the output at the marginal price
+	/// Else PriceImpactTooHigh, MarketDoesNotExist or the reason the quote failed
+	fn ensure_default_slippage(
+		market: Market<T>,
+		who: &T::AccountId,
+		order_type: OrderType,
+		amount_in: BalanceOf<T>,
+	) -> DispatchResult {
+		let max_slippage = <T as Config>::DefaultMaxSlippage::get();
+		if max_slippage.is_one() || amount_in.is_zero() {
+			return Ok(())
+		}
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+		let (side_in, side_out) = order_type.sides();
+		let received = Self::get_received_amount(market, &market_info, who, order_type, amount_in)?;
+
+		// received / (amount_in * reserve_out / reserve_in) >= 1 - max_slippage
+		// The quote rounds down, which may lose up to one unit even on tiny trades
+		let received = U256::from(received.saturating_add(One::one()))
+			.checked_mul(U256::from(market_info.balance(side_in)))
+			.and_then(|received| received.checked_mul(U256::from(Perbill::ACCURACY)))
+			.ok_or(Error::<T>::Arithmetic)?;
+		let min_received = U256::from(amount_in)
+			.checked_mul(U256::from(market_info.balance(side_out)))
+			.and_then(|marginal| {
+				marginal.checked_mul(U256::from(max_slippage.left_from_one().deconstruct()))
+			})
+			.ok_or(Error::<T>::Arithmetic)?;
+		ensure!(received >= min_received, Error::<T>::PriceImpactTooHigh);
+
+		Ok(())
+	}
+
+	/// Checks the guards of a trade which only need to read the market,
+	/// so a rejected trade can be charged less than the full weight
+	///
+	/// # Arguments:
+	/// market: The market to trade in
+	/// amount_in: The amount the user puts into the pool
+	///
+	/// # Returns:
+	/// If Ok, the market exists, allows trading and the trade is large enough
+	/// Else the reason for rejecting the trade
+	fn ensure_tradable(market: Market<T>, amount_in: BalanceOf<T>) -> Result<(), Error<T>> {
+		let market_info = LiquidityPool::<T>::get(market).ok_or(Error::<T>::MarketDoesNotExist)?;
+		ensure!(market_info.flags.allow_trade, Error::<T>::OperationDisabled);
+		ensure!(market_info.active, Error::<T>::MarketInactive);
+
+		Self::ensure_not_dust(amount_in)
+	}
+
+	/// Checks that an account may move an asset at all,
+	/// which is not the case if either of them is frozen
+	///
+	/// # Arguments:
+	/// asset: The asset to move
+	/// who: The account moving the asset
+	/// amount: Any non-zero amount the account holds
+	///
+	/// # Returns:
+	/// If Ok, neither the asset nor the account is frozen
+	/// Else AssetFrozen
+	fn ensure_not_frozen(
+		asset: AssetIdOf<T>,
+		who: &T::AccountId,
+		amount: BalanceOf<T>,
+	) -> Result<(), Error<T>> {
+		ensure!(
+			T::Currencies::can_withdraw(asset, who, amount) != WithdrawConsequence::Frozen,
+			Error::<T>::AssetFrozen
+		);
+
+		Ok(())
+	}
+
+	/// The actual weight of a trade rejected by ensure_tradable, which only read the market
+	fn rejected_trade_weight() -> Weight {
+		10_000 + T::DbWeight::get().reads(1)
+	}
+
+	/// The actual weight of a trade rejected by ensure_default_slippage,
+	/// which read the market, FeeExempt, TradedVolume and FeeRemainders to quote the trade
+	fn rejected_slippage_weight() -> Weight {
+		10_000 + T::DbWeight::get().reads(4)
+	}
+
+	/// The weight of a single trade executed by do_trade, without the base weight.
+	/// It reads the market, FeeExempt, TradedVolume, FeeRemainders and ProtocolFees,
+	/// both asset details and the balances of the taker, of the pool account in both assets,
+	/// of the recipient and of the pool fee account, for can_withdraw and the transfers.
+	/// It writes the market, TradedVolume, FeeRemainders, ProtocolFees, EarlyPayoutDue
+	/// and the same balances, apart from the unchanged asset details.
+	/// Repeated reads of the same key are served from the overlay and only counted once
+	fn trade_weight() -> Weight {
+		T::DbWeight::get().reads_writes(12, 10)
+	}
+
+	/// Checks that a trade receives something for its input,
+	/// as rounding may leave nothing for a tiny input, which would only pay the fee
+	///
+	/// # Arguments:
+	/// amount_in: The amount the user puts into the pool
+	/// receive_amount: The amount the user would receive
+	///
+	/// # Returns:
+	/// If Ok, the trade receives something or has no input at all
+	/// Else ZeroReceived
+	fn ensure_received(
+		amount_in: BalanceOf<T>,
+		receive_amount: BalanceOf<T>,
+	) -> Result<(), Error<T>> {
+		ensure!(amount_in.is_zero() || !receive_amount.is_zero(), Error::<T>::ZeroReceived);
+
+		Ok(())
+	}
+
+	/// Checks that a trade is not below MinTradeAmount.
+	/// Taker fees which round down to zero are carried over by fee_from_amount instead
+	///
+	/// # Arguments:
+	/// amount_in: The amount the user puts into the pool
+	///
+	/// # Returns:
+	/// If Ok, the trade is large enough
+	/// Else TradeTooSmall
+	fn ensure_not_dust(amount_in: BalanceOf<T>) -> Result<(), Error<T>> {
+		ensure!(amount_in >= <T as Config>::MinTradeAmount::get(), Error::<T>::TradeTooSmall);
+
+		Ok(())
+	}
+
+	/// Checks that the reserves recorded in LiquidityPool add up to
+	/// the balances actually held by the pool account, for every asset.
+	/// Runs in post_upgrade with the try-runtime feature and is called by the tests
+	#[cfg(any(test, feature = "try-runtime"))]
+	pub(crate) fn do_try_state() -> Result<(), &'static str> {
+		use frame_support::sp_std::collections::btree_map::BTreeMap;
+
+		let mut recorded: BTreeMap<AssetIdOf<T>, BalanceOf<T>> = BTreeMap::new();
+		for (MarketId { base: base_asset, quote: quote_asset }, market_info) in
+			LiquidityPool::<T>::iter()
+		{
+			let base = recorded.entry(base_asset).or_default();
+			*base = base.checked_add(market_info.base_balance).ok_or("reserve overflow")?;
+			let quote = recorded.entry(quote_asset).or_default();
+			*quote = quote.checked_add(market_info.quote_balance).ok_or("reserve overflow")?;
+		}
+
+		let pool_account = Self::pool_account();
+		for (asset, reserve) in recorded {
+			if Self::balance(asset, &pool_account) != reserve {
+				return Err("recorded reserves do not match the pool account balance")
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Compares the reserves recorded in LiquidityPool with the balances of the pool account
+	/// and emits ReserveMismatch for every market trading an asset whose totals differ.
+	/// Only reports the mismatch, as it can't be attributed to a single market
+	///
+	/// # Returns:
+	/// The consumed weight, a read of every market and of the pool account balance
+	/// of every asset they trade
+	fn check_reserves() -> Weight {
+		let mut markets: Vec<Market<T>> = Vec::new();
+		// (asset, recorded reserves over all markets)
+		let mut recorded: Vec<(AssetIdOf<T>, u128)> = Vec::new();
+		for (market, market_info) in LiquidityPool::<T>::iter() {
+			markets.push(market);
+			for (asset, reserve) in
+				[(market.base, market_info.base_balance), (market.quote, market_info.quote_balance)]
+			{
+				let reserve: u128 = reserve.unique_saturated_into();
+				match recorded.iter_mut().find(|(recorded_asset, _)| *recorded_asset == asset) {
+					Some((_, total)) => *total = total.saturating_add(reserve),
+					None => recorded.push((asset, reserve)),
+				}
+			}
+		}
+
+		let pool_account = Self::pool_account();
+		let mismatch = |asset: AssetIdOf<T>| -> i128 {
+			let total = recorded
+				.iter()
+				.find(|(recorded_asset, _)| *recorded_asset == asset)
+				.map_or(0, |(_, total)| *total);
+			let actual: u128 = Self::balance(asset, &pool_account).unique_saturated_into();
+			if actual >= total {
+				i128::try_from(actual - total).unwrap_or(i128::MAX)
+			} else {
+				i128::try_from(total - actual).map_or(i128::MIN, |difference| -difference)
+			}
+		};
+
+		// The storage order depends on the hashes of the keys, so sort for reproducible events
+		markets.sort();
+		for market in &markets {
+			let (base_mismatch, quote_mismatch) = (mismatch(market.base), mismatch(market.quote));
+			if base_mismatch != 0 || quote_mismatch != 0 {
+				log::error!("reserves of market {:?} do not match the pool account", market);
+				Self::deposit_event(Event::ReserveMismatch(*market, base_mismatch, quote_mismatch));
+			}
+		}
+
+		T::DbWeight::get().reads((markets.len() + recorded.len()) as u64)
+	}
+
+	/// Transfers the balances of the pool fee account which are owed to nobody
+	/// to the TreasuryAccount, once they exceed the FeeSweepThreshold.
+	/// The fees collected for the liquidity providers and the protocol fees are owed,
+	/// anything beyond them, e.g. left behind by failed payouts, is dust
+	///
+	/// # Returns:
+	/// The consumed weight, a read of every market, of the protocol fees and
+	/// of the pool fee account balance of every asset, plus the transfers
+	fn sweep_fee_dust() -> Weight {
+		let threshold = match <T as Config>::FeeSweepThreshold::get() {
+			Some(threshold) => threshold,
+			None => return 0,
+		};
+
+		// (asset, fees owed over all markets)
+		let mut owed: Vec<(AssetIdOf<T>, BalanceOf<T>)> = Vec::new();
+		let mut reads: u64 = 0;
+		let mut add_owed = |asset: AssetIdOf<T>, fees: BalanceOf<T>| match owed
+			.iter_mut()
+			.find(|(owed_asset, _)| *owed_asset == asset)
+		{
+			Some((_, total)) => *total = total.saturating_add(fees),
+			None => owed.push((asset, fees)),
+		};
+		for (market, market_info) in LiquidityPool::<T>::iter() {
+			reads += 1;
+			add_owed(market.base, market_info.collected_base_fees);
+			add_owed(market.quote, market_info.collected_quote_fees);
+		}
+		// Protocol fees of removed markets are still owed to the treasury
+		for (market, (base_fees, quote_fees)) in ProtocolFees::<T>::iter() {
+			reads += 1;
+			add_owed(market.base, base_fees);
+			add_owed(market.quote, quote_fees);
+		}
+		// The storage order depends on the hashes of the keys, so sort for reproducible events
+		owed.sort_by(|(a, _), (b, _)| a.cmp(b));
+
 		let pool_fee_account = Self::pool_fee_account();
+		let treasury = <T as Config>::TreasuryAccount::get();
+		let mut weight = T::DbWeight::get().reads(reads + owed.len() as u64);
+		for (asset, owed) in owed {
+			let dust = Self::balance(asset, &pool_fee_account).saturating_sub(owed);
+			if dust <= threshold {
+				continue
+			}
+			// The asset and both accounts are read, both accounts written
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 2));
+			match Self::safe_transfer(asset, &pool_fee_account, &treasury, dust, false) {
+				Ok(()) => Self::deposit_event(Event::FeeDustSwept(asset, dust)),
+				Err(e) => {
+					log::error!("sweeping the fee dust of asset {:?} failed due to {:?}", asset, e)
+				},
+			}
+		}
+
+		weight
+	}
 
-		let lps: Vec<(Market<T>, MarketInfo<T>)> = LiquidityPool::<T>::iter().collect();
+	/// Performs the payout of collected fee to liquidity providers
+	/// Triggered every PayoutInterval blocks by on_initialize.
+	/// At most MaxPayoutMarkets markets are processed, in a round robin over all markets
+	/// in storage order. A market whose payout fails is logged and skipped until its next turn
+	///
+	/// # Returns:
+	/// The consumed weight, at most MaxPayoutMarkets times the weight of payout_market
+	///
+	/// # Complexity:
+	/// O(m * p) for MaxPayoutMarkets m and MaxProvidersPerMarket p
+	fn do_liquidity_provider_payout() -> Weight {
+		let max_markets = <T as Config>::MaxPayoutMarkets::get() as usize;
+		// Resume after the market the last payout stopped at, which may have been removed since
+		let mut lps: Vec<(Market<T>, MarketInfo<T>)> = match LastPaidOutMarket::<T>::get() {
+			Some(last) => LiquidityPool::<T>::iter_from(LiquidityPool::<T>::hashed_key_for(last))
+				.take(max_markets)
+				.collect(),
+			None => Vec::new(),
+		};
+		// Wrap around to the start of the map, up to the first market of this payout
+		if lps.len() < max_markets {
+			let first = lps.first().map(|(market, _)| *market);
+			let wrapped: Vec<(Market<T>, MarketInfo<T>)> = LiquidityPool::<T>::iter()
+				.take_while(|(market, _)| Some(*market) != first)
+				.take(max_markets - lps.len())
+				.collect();
+			lps.extend(wrapped);
+		}
 
+		// The cursor, the markets and the end of both iterations
+		let mut weight = T::DbWeight::get().reads_writes(lps.len() as u64 + 3, 1);
 		for (market, market_info) in &lps {
-			let (base_asset, quote_asset) = market;
+			if let Err(e) = Self::payout_market(*market, market_info) {
+				log::error!("the payout of market {:?} failed due to {:?}", market, e);
+			}
+			weight = weight.saturating_add(Self::payout_market_weight());
+		}
+		if let Some((market, _)) = lps.last() {
+			LastPaidOutMarket::<T>::put(market);
+		}
 
-			if market_info.collected_base_fees == Zero::zero()
-				&& market_info.collected_quote_fees == Zero::zero()
-			{
-				continue;
+		weight
+	}
+
+	/// Pays out the markets whose collected fees in either asset exceeded MaxUndistributedFees,
+	/// without waiting for the next payout.
+	/// At most MaxPayoutMarkets markets are processed, the others stay due for the next block.
+	/// A market whose payout fails is logged and left to the regular payout
+	///
+	/// # Returns:
+	/// The consumed weight, at most MaxPayoutMarkets times the weight of payout_market
+	///
+	/// # Complexity:
+	/// O(m * p) for MaxPayoutMarkets m and MaxProvidersPerMarket p
+	fn do_early_payout() -> Weight {
+		let max_markets = <T as Config>::MaxPayoutMarkets::get() as usize;
+		let due: Vec<Market<T>> = EarlyPayoutDue::<T>::iter_keys().take(max_markets).collect();
+
+		// The due markets and the end of the iteration
+		let mut weight = T::DbWeight::get().reads(due.len() as u64 + 1);
+		for market in due {
+			// Removing the market from EarlyPayoutDue and reading its information
+			weight = weight
+				.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+				.saturating_add(Self::payout_market_weight());
+			EarlyPayoutDue::<T>::remove(market);
+			// The market may have been removed since its fees exceeded the cap
+			let market_info = match LiquidityPool::<T>::get(market) {
+				Some(market_info) => market_info,
+				None => continue,
+			};
+			if let Err(e) = Self::payout_market(market, &market_info) {
+				log::error!("the early payout of market {:?} failed due to {:?}", market, e);
 			}
+		}
 
-			let liquidity_providers: Vec<(T::AccountId, (BalanceOf<T>, BalanceOf<T>))> =
-				LiqProvisionPool::<T>::iter_prefix(market).collect();
-			for (account, (base_provision, quote_provision)) in &liquidity_providers {
-				if *base_provision > Zero::zero() {
-					// The ratio of the users provided liquidity relative to pool liquidity for the
-					// BASE asset
-					let payout_fraction = base_provision
-						.checked_div(market_info.base_balance)
-						.ok_or(Error::<T>::Arithmetic)?;
-					// The payout which is a fraction of the total collected fees
-					let payout = market_info
-						.collected_base_fees
-						.checked_mul(payout_fraction)
-						.ok_or(Error::<T>::Arithmetic)?;
-
-					// transfer payout amount from pool_fee_account to liquidity provider
-					<T as Config>::Currencies::transfer(
-						*base_asset,
-						&pool_fee_account,
-						account,
-						payout,
-						true,
-					)
-					.map_err(|_| Error::<T>::Transfer)?;
-				}
-				if *quote_provision > Zero::zero() {
-					// similar procedure as for the BASE asset
-
-					let payout_fraction = quote_provision
-						.checked_div(market_info.quote_balance)
-						.ok_or(Error::<T>::Arithmetic)?;
-					let payout = market_info
-						.collected_quote_fees
-						.checked_mul(payout_fraction)
-						.ok_or(Error::<T>::Arithmetic)?;
-
-					// transfer payout amount from pool_fee_account to liquidity provider
-					<T as Config>::Currencies::transfer(
-						*quote_asset,
-						&pool_fee_account,
-						account,
-						payout,
-						true,
-					)
-					.map_err(|_| Error::<T>::Transfer)?;
-				}
+		weight
+	}
+
+	/// Pays out the collected fees of a market to its liquidity providers
+	///
+	/// # Arguments:
+	/// market: The market of the payout
+	/// market_info: The information of the market
+	///
+	/// # Returns:
+	/// If Ok, the fees have been paid out, apart from failed payouts,
+	/// or kept because no liquidity provider is eligible yet
+	/// Else some arithmetic error
+	fn payout_market(market: Market<T>, market_info: &MarketInfo<T>) -> Result<(), Error<T>> {
+		if market_info.collected_base_fees == Zero::zero()
+			&& market_info.collected_quote_fees == Zero::zero()
+		{
+			Self::restart_fee_collection(market);
+			return Ok(())
+		}
+
+		// Only liquidity provided for at least MinRewardAge blocks shares in the fees
+		let now = <frame_system::Pallet<T>>::block_number();
+		let min_age = <T as Config>::MinRewardAge::get();
+		let liquidity_providers: Vec<(T::AccountId, (BalanceOf<T>, BalanceOf<T>))> =
+			LiqProvisionPool::<T>::iter_prefix(market)
+				.filter(|(account, _)| {
+					now.saturating_sub(ProvidedSince::<T>::get(market, account)) >= min_age
+				})
+				.collect();
+		// The fees are kept until the first providers become eligible
+		if liquidity_providers.is_empty() {
+			return Ok(())
+		}
+
+		for side in [BaseOrQuote::Base, BaseOrQuote::Quote] {
+			// Without collected fees in an asset there is nothing to transfer
+			let collected_fees = market_info.collected_fees(side);
+			if collected_fees.is_zero() {
+				continue
+			}
+			let provisions: Vec<(T::AccountId, BalanceOf<T>)> = liquidity_providers
+				.iter()
+				.map(|(account, provision)| (account.clone(), side.pick(*provision)))
+				.collect();
+			Self::distribute_fees(
+				market,
+				market.asset(side),
+				collected_fees,
+				&market_info.creator,
+				&provisions,
+			)?;
+		}
+
+		// clear collected_base_fee as they've been distributed
+		LiquidityPool::<T>::mutate(market, |opt_market_info| match opt_market_info.as_mut() {
+			Some(market_info) => {
+				market_info.collected_base_fees = Zero::zero();
+				market_info.collected_quote_fees = Zero::zero();
+			},
+			None => log::error!(
+				"this should not happen ever, as we previously got the key from the map; qed"
+			),
+		});
+		Self::restart_fee_collection(market);
+
+		Ok(())
+	}
+
+	/// The worst case weight of payout_market, for a market with MaxProvidersPerMarket
+	/// liquidity providers and collected fees in both assets.
+	/// Every provider is read twice to check the MinRewardAge and paid in both assets.
+	/// A transfer reads the asset and both accounts and writes both accounts
+	///
+	/// # Returns:
+	/// Per asset the creator transfer, ProtocolFees and for every provider a transfer
+	/// and its LifetimeFees or FailedPayoutCount, then LiquidityPool and restart_fee_collection
+	fn payout_market_weight() -> Weight {
+		let providers = <T as Config>::MaxProvidersPerMarket::get() as u64;
+		T::DbWeight::get().reads_writes(10 * providers + 9, 6 * providers + 9)
+	}
+
+	/// Starts a new lookback window of estimated_apr with the next block,
+	/// after the collected fees of a market have been paid out or reinvested.
+	/// No early payout is due for the market anymore
+	fn restart_fee_collection(market: Market<T>) {
+		let next_block = <frame_system::Pallet<T>>::block_number().saturating_add(One::one());
+		FeesCollectedSince::<T>::insert(market, next_block);
+		EarlyPayoutDue::<T>::remove(market);
+	}
+
+	/// Distributes the collected fees of one asset of a market.
+	/// The creator receives the CreatorRewardShare of them, the fees not covered by
+	/// either share are accrued as protocol fee and the rest is split among the
+	/// liquidity providers, proportional to their provision of that asset
+	///
+	/// # Arguments:
+	/// market: The market of the payout
+	/// asset: The asset in which the fees have been collected
+	/// collected_fees: The fees to distribute
+	/// creator: The creator of the market
+	/// provisions: The liquidity providers together with their provision of the asset
+	///
+	/// # Returns:
+	/// If Ok, the fees have been distributed, apart from failed payouts
+	/// Else some arithmetic error
+	fn distribute_fees(
+		market: Market<T>,
+		asset: AssetIdOf<T>,
+		collected_fees: BalanceOf<T>,
+		creator: &T::AccountId,
+		provisions: &[(T::AccountId, BalanceOf<T>)],
+	) -> Result<(), Error<T>> {
+		let pool_fee_account = Self::pool_fee_account();
+
+		let creator_share = <T as Config>::CreatorRewardShare::get();
+		let unallocated_share = Perbill::one()
+			.saturating_sub(creator_share)
+			.saturating_sub(<T as Config>::ProviderRewardShare::get());
+		let creator_reward = creator_share.mul_floor(collected_fees);
+		let protocol_fee = unallocated_share.mul_floor(collected_fees);
+		// The providers receive whatever the rounding down of the other slices left
+		let provider_rewards = collected_fees
+			.checked_sub(creator_reward)
+			.and_then(|fees| fees.checked_sub(protocol_fee))
+			.ok_or(Error::<T>::Arithmetic)?;
+
+		if !creator_reward.is_zero() {
+			if let Err(e) =
+				Self::safe_transfer(asset, &pool_fee_account, creator, creator_reward, false)
+			{
+				Self::payout_failed(creator, market, e.into());
 			}
+		}
+		if !protocol_fee.is_zero() {
+			ProtocolFees::<T>::try_mutate(
+				market,
+				|(base_fees, quote_fees)| -> Result<(), Error<T>> {
+					let fees = if asset == market.base { base_fees } else { quote_fees };
+					*fees = fees.checked_add(protocol_fee).ok_or(Error::<T>::Arithmetic)?;
 
-			// clear collected_base_fee as they've been distributed
-			LiquidityPool::<T>::mutate(market, |opt_market_info| match opt_market_info.as_mut() {
-				Some(market_info) => {
-					market_info.collected_base_fees = Zero::zero();
-					market_info.collected_quote_fees = Zero::zero();
+					Ok(())
 				},
-				None => log::error!(
-					"this should not happen ever, as we previously got the key from the map; qed"
-				),
-			});
+			)?;
+		}
+
+		for (account, payout) in Self::proportional_shares(provider_rewards, provisions)? {
+			// The fee account only holds fees awaiting their payout, so it may be emptied
+			match Self::safe_transfer(asset, &pool_fee_account, &account, payout, false) {
+				Ok(()) => LifetimeFees::<T>::mutate(market, &account, |(base_fees, quote_fees)| {
+					let fees = if asset == market.base { base_fees } else { quote_fees };
+					*fees = fees.saturating_add(payout);
+				}),
+				Err(e) => Self::payout_failed(&account, market, e.into()),
+			}
 		}
 
 		Ok(())
 	}
+
+	/// Splits an amount among liquidity providers, proportional to their provision.
+	/// The providers are ordered by their account ids and the last one receives
+	/// the remainder, so exactly the amount is split
+	///
+	/// # Arguments:
+	/// amount: The amount to split
+	/// provisions: The liquidity providers together with their provision of the asset
+	///
+	/// # Returns:
+	/// If Ok, the providers with a non-zero provision together with their share
+	/// Else some arithmetic error
+	fn proportional_shares(
+		amount: BalanceOf<T>,
+		provisions: &[(T::AccountId, BalanceOf<T>)],
+	) -> Result<Vec<(T::AccountId, BalanceOf<T>)>, Error<T>> {
+		if amount.is_zero() {
+			return Ok(Vec::new())
+		}
+
+		let mut provisions: Vec<&(T::AccountId, BalanceOf<T>)> =
+			provisions.iter().filter(|(_, provision)| !provision.is_zero()).collect();
+		// Independent of the storage order, which decides who receives the remainder
+		provisions.sort_by(|(a, _), (b, _)| a.cmp(b));
+		let total_provision = provisions
+			.iter()
+			.try_fold(BalanceOf::<T>::zero(), |total, (_, provision)| total.checked_add(*provision))
+			.ok_or(Error::<T>::Arithmetic)?;
+
+		let mut shares = Vec::with_capacity(provisions.len());
+		let mut split: BalanceOf<T> = Zero::zero();
+		for (i, (account, provision)) in provisions.iter().enumerate() {
+			let share = if i + 1 == provisions.len() {
+				amount.checked_sub(split).ok_or(Error::<T>::Arithmetic)?
+			} else {
+				Self::proportional(*provision, total_provision, amount)?
+			};
+			split = split.checked_add(share).ok_or(Error::<T>::Arithmetic)?;
+			shares.push((account.clone(), share));
+		}
+
+		Ok(shares)
+	}
+
+	/// Computes the share of an amount which corresponds to a part of a whole.
+	/// Multiplies before dividing, in 256 bits, so small shares are not truncated to zero
+	/// and the product can't overflow
+	///
+	/// # Arguments:
+	/// part: The part of the whole, e.g. the provision of a single liquidity provider
+	/// whole: The whole, e.g. the provisions of all liquidity providers
+	/// of: The amount to take the share of
+	///
+	/// # Returns:
+	/// If Ok, of * part / whole, rounded down, and zero if part is zero
+	/// Else Arithmetic if whole is zero or the share exceeds the balance type
+	fn proportional(
+		part: BalanceOf<T>,
+		whole: BalanceOf<T>,
+		of: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, Error<T>> {
+		if part.is_zero() {
+			return Ok(Zero::zero())
+		}
+
+		U256::from(of)
+			.checked_mul(U256::from(part))
+			.ok_or(Error::<T>::Arithmetic)?
+			.checked_div(U256::from(whole))
+			.ok_or(Error::<T>::Arithmetic)?
+			.try_into()
+			.map_err(|_| Error::<T>::Arithmetic)
+	}
+
+	/// Deposits an event with the market and the account as topics,
+	/// so clients can subscribe to the activity of a market or an account
+	/// without decoding every event
+	///
+	/// # Arguments:
+	/// market: The market the event is about
+	/// who: The account the event is about
+	/// event: The event to deposit
+	fn deposit_event_indexed(market: Market<T>, who: &T::AccountId, event: Event<T>) {
+		let topics = [T::Hashing::hash_of(&market), T::Hashing::hash_of(who)];
+		frame_system::Pallet::<T>::deposit_event_indexed(
+			&topics,
+			<T as Config>::Event::from(event).into(),
+		);
+	}
+
+	/// Records a failed payout, so a single failing liquidity provider
+	/// does not prevent the others from being paid
+	///
+	/// # Arguments:
+	/// account: The liquidity provider which has not been paid
+	/// market: The market of the payout
+	/// error: The reason of the failure
+	fn payout_failed(account: &T::AccountId, market: Market<T>, error: DispatchError) {
+		FailedPayoutCount::<T>::mutate(|count| *count = count.saturating_add(1));
+		Self::deposit_event(Event::PayoutFailed(account.clone(), market, error));
+	}
 }
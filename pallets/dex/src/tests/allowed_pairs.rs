@@ -0,0 +1,85 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{DispatchError, DispatchResult};
+
+use crate::{tests::*, Error, MarketId};
+
+fn create_market(base_asset: AssetId) -> DispatchResult {
+	crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		base_asset,
+		USD,
+		100_000,
+		100_000,
+		None,
+	)
+}
+
+#[test]
+fn unrestricted_pairs() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(create_market(BTC));
+		assert_ok!(create_market(XMR));
+	})
+}
+
+#[test]
+fn allowed_pair() {
+	new_test_ext().execute_with(|| {
+		RestrictPairs::set(true);
+
+		assert_ok!(crate::Pallet::<Test>::set_pair_allowed(
+			Origin::root(),
+			MarketId::new(BTC, USD),
+			true
+		));
+		System::assert_last_event(Event::Dex(crate::Event::PairAllowanceSet(
+			MarketId::new(BTC, USD),
+			true,
+		)));
+		assert_ok!(create_market(BTC));
+	})
+}
+
+#[test]
+fn disallowed_pair() {
+	new_test_ext().execute_with(|| {
+		RestrictPairs::set(true);
+
+		assert_noop!(create_market(XMR), Error::<Test>::PairNotAllowed);
+
+		// The reverse pair is a different market
+		assert_ok!(crate::Pallet::<Test>::set_pair_allowed(
+			Origin::root(),
+			MarketId::new(USD, XMR),
+			true
+		));
+		assert_noop!(create_market(XMR), Error::<Test>::PairNotAllowed);
+
+		// Once removed, a pair can't be created anymore
+		assert_ok!(crate::Pallet::<Test>::set_pair_allowed(
+			Origin::root(),
+			MarketId::new(XMR, USD),
+			true
+		));
+		assert_ok!(crate::Pallet::<Test>::set_pair_allowed(
+			Origin::root(),
+			MarketId::new(XMR, USD),
+			false
+		));
+		assert_noop!(create_market(XMR), Error::<Test>::PairNotAllowed);
+	})
+}
+
+#[test]
+fn set_pair_allowed_not_admin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::set_pair_allowed(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				true
+			),
+			DispatchError::BadOrigin
+		);
+	})
+}
@@ -0,0 +1,97 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, types::FeeAsset, MarketId, OrderType};
+
+fn setup_market(fee_asset: FeeAsset) {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	assert_ok!(crate::Pallet::<Test>::set_fee_asset(
+		Origin::root(),
+		MarketId::new(BTC, USD),
+		fee_asset
+	));
+}
+
+#[test]
+fn amount_in_for_exact_out_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			crate::Pallet::<Test>::amount_in_for_exact_out(
+				MarketId::new(BTC, USD),
+				OrderType::Buy,
+				1_000
+			),
+			None
+		);
+	})
+}
+
+#[test]
+fn amount_in_for_exact_out_exceeds_reserves() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Input);
+
+		assert_eq!(
+			crate::Pallet::<Test>::amount_in_for_exact_out(
+				MarketId::new(BTC, USD),
+				OrderType::Buy,
+				100_000
+			),
+			None
+		);
+	})
+}
+
+#[test]
+fn amount_in_for_exact_out_buy() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Input);
+		let market = MarketId::new(BTC, USD);
+
+		let amount_in =
+			crate::Pallet::<Test>::amount_in_for_exact_out(market, OrderType::Buy, 9_000).unwrap();
+		assert_eq!(amount_in, 9_900);
+
+		// One less would not be enough
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(
+			crate::Pallet::<Test>::get_received_amount(
+				market,
+				&market_info,
+				&ALICE,
+				OrderType::Buy,
+				9_899
+			),
+			Ok(8_999)
+		);
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, amount_in, None));
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 9_900, 9_000)));
+	})
+}
+
+#[test]
+fn amount_in_for_exact_out_sell_fee_in_output() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Quote);
+		let market = MarketId::new(BTC, USD);
+
+		let amount_in =
+			crate::Pallet::<Test>::amount_in_for_exact_out(market, OrderType::Sell, 9_000).unwrap();
+		assert_eq!(amount_in, 9_901);
+
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			market,
+			amount_in,
+			false,
+			None
+		));
+		System::assert_last_event(Event::Dex(crate::Event::Sold(ALICE, market, 9_901, 9_000)));
+	})
+}
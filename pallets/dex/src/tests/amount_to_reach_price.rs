@@ -0,0 +1,150 @@
+use frame_support::assert_ok;
+
+use crate::{
+	tests::*,
+	types::{FeeAsset, OrderType},
+	MarketId,
+};
+
+#[test]
+fn amount_to_reach_price_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			crate::Pallet::<Test>::amount_to_reach_price(MarketId::new(BTC, USD), 2, 1),
+			None
+		);
+	})
+}
+
+#[test]
+fn amount_to_reach_price_above() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Moving the price from 1.0 to 1.21 requires 10_000 QUOTE to end up in the pool
+		let (order_type, amount) =
+			crate::Pallet::<Test>::amount_to_reach_price(market, 121, 100).unwrap();
+		assert_eq!(order_type, OrderType::Buy);
+		assert_eq!(amount, 10_010);
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, amount, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.quote_balance, 110_000);
+	})
+}
+
+#[test]
+fn amount_to_reach_price_below() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Moving the price from 1.0 to 1 / 1.21 requires 10_000 BASE to end up in the pool
+		let (order_type, amount) =
+			crate::Pallet::<Test>::amount_to_reach_price(market, 100, 121).unwrap();
+		assert_eq!(order_type, OrderType::Sell);
+		assert_eq!(amount, 10_010);
+
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, amount, false, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 110_000);
+	})
+}
+
+#[test]
+fn amount_to_reach_price_fee_in_quote() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::set_fee_asset(Origin::root(), market, FeeAsset::Quote));
+
+		// The fee of a sell is taken from the QUOTE output, so all 10_000 BASE enter the pool
+		let (order_type, amount) =
+			crate::Pallet::<Test>::amount_to_reach_price(market, 100, 121).unwrap();
+		assert_eq!(order_type, OrderType::Sell);
+		assert_eq!(amount, 10_000);
+
+		assert_ok!(crate::Pallet::<Test>::sell(origin.clone(), market, amount, false, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 110_000);
+
+		// Buying back pays the fee from the QUOTE input, so the 9_090 QUOTE
+		// the pool is missing are only deposited with the fee on top
+		let (order_type, amount) =
+			crate::Pallet::<Test>::amount_to_reach_price(market, 1, 1).unwrap();
+		assert_eq!(order_type, OrderType::Buy);
+		assert_eq!(amount, 9_099);
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, amount, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.quote_balance, 100_000);
+	})
+}
+
+#[test]
+fn amount_to_reach_price_sell_fee_in_quote() {
+	new_test_ext().execute_with(|| {
+		SellFeeInQuote::set(true);
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// A market collecting the fee in the input collects it from the output of a sell
+		let (order_type, amount) =
+			crate::Pallet::<Test>::amount_to_reach_price(market, 100, 121).unwrap();
+		assert_eq!(order_type, OrderType::Sell);
+		assert_eq!(amount, 10_000);
+
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, amount, false, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 110_000);
+	})
+}
+
+#[test]
+fn amount_to_reach_price_unreachable() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, BTC, USD, 100_000, 100_000, None
+		));
+
+		// Already trading at the target
+		assert_eq!(crate::Pallet::<Test>::amount_to_reach_price(market, 1, 1), None);
+		// A zero price can never be reached
+		assert_eq!(crate::Pallet::<Test>::amount_to_reach_price(market, 0, 1), None);
+	})
+}
@@ -0,0 +1,89 @@
+use frame_support::assert_ok;
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+fn create_market(base_asset: AssetId, quote_asset: AssetId, base: Balance, quote: Balance) {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		base_asset,
+		quote_asset,
+		base,
+		quote,
+		None
+	));
+}
+
+#[test]
+fn arbitrage_spread() {
+	new_test_ext().execute_with(|| {
+		// 1 BTC = 1 USD and 1 XMR = 2 USD imply 1 BTC = 0.5 XMR
+		create_market(BTC, USD, 100_000, 100_000);
+		create_market(XMR, USD, 100_000, 200_000);
+		// The direct market trades 1 BTC = 0.6 XMR
+		create_market(BTC, XMR, 100_000, 60_000);
+
+		// |0.5 - 0.6| / 0.6
+		assert_eq!(
+			crate::Pallet::<Test>::arbitrage_spread(
+				MarketId::new(BTC, USD),
+				MarketId::new(XMR, USD)
+			),
+			Some(Perbill::from_parts(166_666_666))
+		);
+	})
+}
+
+#[test]
+fn arbitrage_spread_reversed_direct_market() {
+	new_test_ext().execute_with(|| {
+		create_market(BTC, USD, 100_000, 100_000);
+		create_market(XMR, USD, 100_000, 200_000);
+		// 1 XMR = 2 BTC, which matches the implied cross price
+		create_market(XMR, BTC, 50_000, 100_000);
+
+		assert_eq!(
+			crate::Pallet::<Test>::arbitrage_spread(
+				MarketId::new(BTC, USD),
+				MarketId::new(XMR, USD)
+			),
+			Some(Perbill::zero())
+		);
+	})
+}
+
+#[test]
+fn arbitrage_spread_no_direct_market() {
+	new_test_ext().execute_with(|| {
+		create_market(BTC, USD, 100_000, 100_000);
+		create_market(XMR, USD, 100_000, 200_000);
+
+		assert_eq!(
+			crate::Pallet::<Test>::arbitrage_spread(
+				MarketId::new(BTC, USD),
+				MarketId::new(XMR, USD)
+			),
+			None
+		);
+	})
+}
+
+#[test]
+fn arbitrage_spread_no_shared_asset() {
+	new_test_ext().execute_with(|| {
+		create_market(BTC, USD, 100_000, 100_000);
+
+		assert_eq!(
+			crate::Pallet::<Test>::arbitrage_spread(MarketId::new(BTC, USD), (XMR, 3)),
+			None
+		);
+		// Two markets of the same pair have no third asset to compare against
+		assert_eq!(
+			crate::Pallet::<Test>::arbitrage_spread(
+				MarketId::new(BTC, USD),
+				MarketId::new(BTC, USD)
+			),
+			None
+		);
+	})
+}
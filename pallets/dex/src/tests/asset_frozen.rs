@@ -0,0 +1,44 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn swap_into_frozen_asset() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(Assets::freeze_asset(Origin::signed(DEX_PALLET_ACCOUNT), USD));
+
+		assert_noop!(
+			crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, USD, 10_000, 0)
+				.map_err(|e| e.error),
+			Error::<Test>::AssetFrozen
+		);
+	})
+}
+
+#[test]
+fn swap_from_frozen_account() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(Assets::freeze(Origin::signed(DEX_PALLET_ACCOUNT), BTC, ALICE));
+
+		assert_noop!(
+			crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, USD, 10_000, 0)
+				.map_err(|e| e.error),
+			Error::<Test>::AssetFrozen
+		);
+		// Buying BTC only moves the frozen asset out of the pool
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(ALICE), USD, BTC, 10_000, 0));
+	})
+}
@@ -1,6 +1,6 @@
 use frame_support::{assert_noop, assert_ok};
 
-use crate::types::MarketInfo;
+use crate::types::{FeeAsset, MarketFlags, MarketId, MarketInfo};
 
 use super::*;
 
@@ -8,9 +8,9 @@ use super::*;
 fn buy_no_pool() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
-		let market = (BTC, XMR);
+		let market = MarketId::new(BTC, XMR);
 		assert_noop!(
-			crate::Pallet::<Test>::buy(origin, market, 100),
+			crate::Pallet::<Test>::buy(origin, market, 100, None).map_err(|e| e.error),
 			crate::Error::<Test>::MarketDoesNotExist
 		);
 	})
@@ -20,12 +20,19 @@ fn buy_no_pool() {
 fn buy_not_enough_balance() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
-		assert_ok!(crate::Pallet::<Test>::create_market_pool(origin.clone(), BTC, XMR, 100, 100));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			XMR,
+			100,
+			100,
+			None
+		));
 
-		let market = (BTC, XMR);
+		let market = MarketId::new(BTC, XMR);
 		// This should obviously fail as ALICE does not have enough balance
 		assert_noop!(
-			crate::Pallet::<Test>::buy(origin, market, u128::MAX),
+			crate::Pallet::<Test>::buy(origin, market, u128::MAX, None),
 			crate::Error::<Test>::NotEnoughBalance
 		);
 	})
@@ -40,30 +47,36 @@ fn buy() {
 			BTC,
 			USD,
 			100_000,
-			100_000
+			100_000,
+			None
 		));
 
-		let market = (BTC, USD);
-		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000));
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
 
 		// Check the market_info
 		assert_eq!(
 			crate::LiquidityPool::<Test>::get(market).unwrap(),
 			MarketInfo {
-				base_balance: 90_917,
+				base_balance: 90_918,
 				quote_balance: 109_990,
 				collected_base_fees: 0,
 				collected_quote_fees: 10,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: 1,
+				creator: ALICE,
+				active: true,
 			}
 		);
 
 		// Check balance of ALICE
 		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 890_000);
-		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 909_083);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 909_082);
 
 		// Check balance of pool_account
 		let pool_account = crate::Pallet::<Test>::pool_account();
-		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_account), 90_917);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_account), 90_918);
 		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_account), 109_990);
 
 		// Check balance of pool_fee_account
@@ -72,3 +85,80 @@ fn buy() {
 		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 10);
 	})
 }
+
+#[test]
+fn buy_enormous_amount() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
+
+		// Spending almost all of ALICE's QUOTE on a tiny pool
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 900_000, None));
+
+		// 899_100 * 100 / 899_200 rounds down to 99, one unit of BASE stays in the pool
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 999_999);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 1);
+		assert_eq!(market_info.quote_balance, 899_200);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &DEX_PALLET_ACCOUNT), 1);
+	})
+}
+
+#[test]
+fn buy_fee_accounting() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		let market = MarketId::new(BTC, USD);
+		let pool_account = crate::Pallet::<Test>::pool_account();
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		// 12.345 QUOTE of fee each, the fractional parts add up to a whole unit on the third buy
+		for expected_fee in [12, 12, 13] {
+			let user_before = crate::Pallet::<Test>::balance(USD, &ALICE);
+			let pool_before = crate::Pallet::<Test>::balance(USD, &pool_account);
+			let fee_account_before = crate::Pallet::<Test>::balance(USD, &pool_fee_account);
+			let market_info_before = crate::LiquidityPool::<Test>::get(market).unwrap();
+
+			assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 12_345, None));
+
+			let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+			let deposit_amount = 12_345 - expected_fee;
+			// The user pays exactly the quote_amount, of which the pool gets all but the fee
+			assert_eq!(user_before - crate::Pallet::<Test>::balance(USD, &ALICE), 12_345);
+			assert_eq!(
+				crate::Pallet::<Test>::balance(USD, &pool_account) - pool_before,
+				deposit_amount
+			);
+			assert_eq!(
+				market_info.quote_balance - market_info_before.quote_balance,
+				deposit_amount
+			);
+			// The fee is transferred and collected exactly once
+			assert_eq!(
+				crate::Pallet::<Test>::balance(USD, &pool_fee_account) - fee_account_before,
+				expected_fee
+			);
+			assert_eq!(
+				market_info.collected_quote_fees - market_info_before.collected_quote_fees,
+				expected_fee
+			);
+			assert_eq!(market_info.collected_base_fees, 0);
+		}
+	})
+}
@@ -0,0 +1,115 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{
+	tests::*,
+	types::{OrderType, SwapOp},
+	Error, MarketId,
+};
+
+fn buy_op(amount_in: Balance, min_out: Balance) -> SwapOp<Test> {
+	SwapOp { market: MarketId::new(BTC, USD), order_type: OrderType::Buy, amount_in, min_out }
+}
+
+#[test]
+fn create_and_swap() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_and_swap(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			buy_op(10_000, 9_000)
+		));
+
+		System::assert_has_event(Event::Dex(crate::Event::PoolCreated(
+			ALICE, market, 100_000, 100_000,
+		)));
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_082)));
+		// The same as creating the pool and buying in separate transactions
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 90_918);
+		assert_eq!(market_info.quote_balance, 109_990);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 909_082);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 890_000);
+	})
+}
+
+#[test]
+fn create_and_swap_failing_swap() {
+	new_test_ext().execute_with(|| {
+		// The swap fails, so the pool is not created either
+		assert_noop!(
+			crate::Pallet::<Test>::create_and_swap(
+				Origin::signed(ALICE),
+				BTC,
+				USD,
+				100_000,
+				100_000,
+				buy_op(10_000, 10_000)
+			),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_eq!(crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)), None);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 1_000_000);
+	})
+}
+
+#[test]
+fn create_and_swap_existing_market() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_and_swap(
+				Origin::signed(ALICE),
+				BTC,
+				USD,
+				100_000,
+				100_000,
+				buy_op(10_000, 0)
+			),
+			Error::<Test>::MarketExists
+		);
+	})
+}
+
+#[test]
+fn create_and_swap_other_market() {
+	new_test_ext().execute_with(|| {
+		let other_market = MarketId::new(XMR, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			XMR,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// The swap would take a trade of an existing market instead of the new one
+		for market in [other_market, MarketId::new(USD, BTC)] {
+			assert_noop!(
+				crate::Pallet::<Test>::create_and_swap(
+					Origin::signed(ALICE),
+					BTC,
+					USD,
+					100_000,
+					100_000,
+					SwapOp { market, order_type: OrderType::Buy, amount_in: 10_000, min_out: 0 }
+				),
+				Error::<Test>::SwapMarketMismatch
+			);
+		}
+		assert_eq!(crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)), None);
+		assert_eq!(crate::LiquidityPool::<Test>::get(other_market).unwrap().quote_balance, 100_000);
+	})
+}
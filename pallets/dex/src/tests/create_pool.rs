@@ -1,6 +1,10 @@
-use frame_support::assert_ok;
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{DispatchError, Perbill};
 
-use crate::types::MarketInfo;
+use crate::{
+	types::{ExpectedPrice, FeeAsset, MarketFlags, MarketInfo},
+	Error, MarketId,
+};
 
 use super::*;
 
@@ -8,7 +12,7 @@ use super::*;
 fn create_market_pool_failing() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(EMPTY_ACCOUNT);
-		let ret = crate::Pallet::<Test>::create_market_pool(origin, BTC, XMR, 100, 100);
+		let ret = crate::Pallet::<Test>::create_market_pool(origin, BTC, XMR, 100, 100, None);
 		assert!(ret.is_err());
 	})
 }
@@ -19,7 +23,7 @@ fn create_market_pool() {
 		let origin = Origin::signed(ALICE);
 		let base_asset = BTC;
 		let quote_asset = USD;
-		let market = (base_asset, quote_asset);
+		let market = MarketId::new(base_asset, quote_asset);
 
 		// Create two assets
 		assert_ok!(crate::Pallet::<Test>::create_market_pool(
@@ -27,7 +31,8 @@ fn create_market_pool() {
 			base_asset,
 			quote_asset,
 			100,
-			100
+			100,
+			None
 		));
 
 		// Check LiquidityPool storage changes
@@ -38,6 +43,11 @@ fn create_market_pool() {
 				quote_balance: 100,
 				collected_base_fees: 0,
 				collected_quote_fees: 0,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: 1,
+				creator: ALICE,
+				active: true,
 			}
 		);
 
@@ -45,3 +55,148 @@ fn create_market_pool() {
 		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (100, 100));
 	})
 }
+
+#[test]
+fn create_market_pool_expected_price() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+
+		// The implied price of 2.0 is within 5% of 2.1
+		let expected_price =
+			ExpectedPrice { numerator: 21, denominator: 10, tolerance: Perbill::from_percent(5) };
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin,
+			BTC,
+			USD,
+			100_000,
+			200_000,
+			Some(expected_price)
+		));
+	})
+}
+
+#[test]
+fn create_market_pool_unexpected_price() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+
+		// The implied price of 2.0 is more than 5% below 2.2
+		let expected_price =
+			ExpectedPrice { numerator: 22, denominator: 10, tolerance: Perbill::from_percent(5) };
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				origin,
+				BTC,
+				USD,
+				100_000,
+				200_000,
+				Some(expected_price)
+			),
+			Error::<Test>::UnexpectedInitialPrice
+		);
+	})
+}
+
+#[test]
+fn create_market_pool_permissioned() {
+	new_test_ext().execute_with(|| {
+		PoolCreators::set(Some(vec![ALICE]));
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(BOB),
+				BTC,
+				USD,
+				100_000,
+				100_000,
+				None
+			),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+	})
+}
+
+#[test]
+fn create_market_pool_minimum_balance() {
+	new_test_ext().execute_with(|| {
+		// An asset which requires at least 1_000 to keep an account alive
+		let asset = 3;
+		assert_ok!(Assets::force_create(Origin::root(), asset, ALICE, true, 1_000));
+		assert_ok!(Assets::mint(Origin::signed(ALICE), asset, ALICE, 1_000_000));
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(ALICE),
+				asset,
+				USD,
+				999,
+				100_000,
+				None
+			),
+			Error::<Test>::BelowMinimumBalance
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(ALICE),
+				BTC,
+				asset,
+				100_000,
+				999,
+				None
+			),
+			Error::<Test>::BelowMinimumBalance
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(Origin::signed(ALICE), BTC, USD, 0, 1, None),
+			Error::<Test>::BelowMinimumBalance
+		);
+
+		// Exactly the minimum balance keeps the pool account alive
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			asset,
+			USD,
+			1_000,
+			100_000,
+			None
+		));
+	})
+}
+
+#[test]
+fn create_market_pool_quote_transfer_failure() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		// The BASE transfer succeeds, but the QUOTE asset can't be sent
+		assert_ok!(Assets::freeze(Origin::signed(DEX_PALLET_ACCOUNT), USD, ALICE));
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(ALICE),
+				BTC,
+				USD,
+				100_000,
+				100_000,
+				None
+			),
+			Error::<Test>::Transfer
+		);
+
+		// Neither storage map is populated
+		assert!(!crate::LiquidityPool::<Test>::contains_key(market));
+		assert!(!crate::LiqProvisionPool::<Test>::contains_key(market, ALICE));
+		assert!(crate::Pallet::<Test>::markets_for_asset(BTC).is_empty());
+		// The BASE transfer has been rolled back
+		let pool_account = crate::Pallet::<Test>::pool_account();
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 1_000_000);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_account), 0);
+	})
+}
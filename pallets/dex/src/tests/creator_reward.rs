@@ -0,0 +1,124 @@
+use frame_support::{assert_ok, traits::Hooks};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn creator_reward() {
+	new_test_ext().execute_with(|| {
+		CreatorRewardShare::set(Perbill::from_percent(10));
+		ProviderRewardShare::set(Perbill::from_percent(80));
+
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			10_000,
+			10_000,
+			None
+		));
+		// BOB provides 3/4 of the BASE liquidity
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			30_000,
+			0
+		));
+
+		// Collects 100 BTC as fee
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			market,
+			100_000,
+			false,
+			None
+		));
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 100);
+
+		let alice_before = crate::Pallet::<Test>::balance(BTC, &ALICE);
+		let bob_before = crate::Pallet::<Test>::balance(BTC, &BOB);
+
+		run_to_block(PayoutInterval::get());
+
+		// ALICE receives the creator slice of 10 and 1/4 of the providers slice of 80
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE) - alice_before, 10 + 20);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB) - bob_before, 60);
+		// The unallocated 10% are left for the treasury
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (10, 0));
+		assert_eq!(
+			crate::Pallet::<Test>::balance(BTC, &crate::Pallet::<Test>::pool_fee_account()),
+			10
+		);
+	})
+}
+
+#[test]
+fn creator_reward_rounding_goes_to_providers() {
+	new_test_ext().execute_with(|| {
+		CreatorRewardShare::set(Perbill::from_percent(15));
+		ProviderRewardShare::set(Perbill::from_percent(85));
+
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			10_000,
+			10_000,
+			None
+		));
+
+		// Collects 10 BTC as fee, of which the creator slice rounds down to 1
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 10_000, false, None));
+		let alice_before = crate::Pallet::<Test>::balance(BTC, &ALICE);
+
+		run_to_block(PayoutInterval::get());
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE) - alice_before, 10);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 0));
+	})
+}
+
+#[test]
+#[should_panic(
+	expected = "CreatorRewardShare and ProviderRewardShare must not exceed one together"
+)]
+fn creator_reward_exceeding_one() {
+	CreatorRewardShare::set(Perbill::from_percent(10));
+	ProviderRewardShare::set(Perbill::from_percent(95));
+	<crate::Pallet<Test> as Hooks<BlockNumber>>::integrity_test();
+}
+
+#[test]
+fn creator_reward_on_remove_market() {
+	new_test_ext().execute_with(|| {
+		CreatorRewardShare::set(Perbill::from_percent(10));
+		ProviderRewardShare::set(Perbill::from_percent(80));
+
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// Collects 10 BTC as fee
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 10_000, false, None));
+		let base_reserve = crate::LiquidityPool::<Test>::get(market).unwrap().base_balance;
+		let alice_before = crate::Pallet::<Test>::balance(BTC, &ALICE);
+
+		assert_ok!(crate::Pallet::<Test>::remove_market(Origin::signed(ALICE), market));
+
+		// ALICE receives the creator slice of 1 and the providers slice of 8 besides the reserve
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE) - alice_before, base_reserve + 9);
+		// The unallocated 10% are still left for the treasury
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (1, 0));
+		assert_eq!(
+			crate::Pallet::<Test>::balance(BTC, &crate::Pallet::<Test>::pool_fee_account()),
+			1
+		);
+	})
+}
@@ -0,0 +1,74 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId, PRICE_DENOMINATOR};
+
+#[test]
+fn current_price() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, BTC, USD, 100_000, 200_000, None
+		));
+
+		assert_eq!(
+			crate::Pallet::<Test>::current_price(MarketId::new(BTC, USD)),
+			Some((2 * PRICE_DENOMINATOR, PRICE_DENOMINATOR))
+		);
+		assert_eq!(crate::Pallet::<Test>::current_price(MarketId::new(XMR, USD)), None);
+	})
+}
+
+#[test]
+fn current_prices() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			200_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, XMR, USD, 100_000, 300_000, None
+		));
+
+		// The order of the request is preserved, with None for markets that don't exist
+		let prices: Vec<_> = [
+			MarketId::new(BTC, USD),
+			MarketId::new(BTC, XMR),
+			MarketId::new(XMR, USD),
+			MarketId::new(USD, BTC),
+		]
+		.into_iter()
+		.map(crate::Pallet::<Test>::current_price)
+		.collect();
+		assert_eq!(
+			prices,
+			vec![
+				Some((2 * PRICE_DENOMINATOR, PRICE_DENOMINATOR)),
+				None,
+				Some((3 * PRICE_DENOMINATOR, PRICE_DENOMINATOR)),
+				None,
+			]
+		);
+	})
+}
+
+#[test]
+fn price_any_direction() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, BTC, USD, 100_000, 200_000, None
+		));
+
+		// 1 BTC = 2 USD in the canonical orientation
+		assert_eq!(crate::Pallet::<Test>::price_any_direction(BTC, USD), Some((200_000, 100_000)));
+		// and 1 USD = 0.5 BTC in the inverted one
+		assert_eq!(crate::Pallet::<Test>::price_any_direction(USD, BTC), Some((100_000, 200_000)));
+		assert_eq!(crate::Pallet::<Test>::price_any_direction(XMR, USD), None);
+		assert_eq!(crate::Pallet::<Test>::price_any_direction(USD, XMR), None);
+	})
+}
@@ -0,0 +1,112 @@
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	DefaultMaxSlippage::set(Perbill::from_percent(10));
+}
+
+#[test]
+fn buy_default_max_slippage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		// With the fee of 11, 11_016 receives 9_913, which is just over 10% below 11_016
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_016, None)
+				.map_err(|e| e.error),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		// 11_015 receives 9_913 as well, which is just below 10%
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_015, None));
+	})
+}
+
+#[test]
+fn sell_default_max_slippage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_016, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_015, false, None));
+	})
+}
+
+#[test]
+fn default_max_slippage_only_without_minimum() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// A swap passes its own minimum output, so the default does not apply
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(ALICE), USD, BTC, 50_000, 0));
+	})
+}
+
+#[test]
+fn default_max_slippage_fee_exempt() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), ALICE, true));
+
+		// Without the fee 11_116 receives 10_003, which is just over 10% below 11_116
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_116, None)
+				.map_err(|e| e.error),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		// The amount rejected from a trader paying the fee is still fine
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_016, None));
+	})
+}
+
+#[test]
+fn default_max_slippage_fee_tiers() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		// Every trader reached a tier without any fee
+		FeeTiers::set(vec![(0, 0)]);
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_116, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_016, false, None));
+	})
+}
+
+#[test]
+fn default_max_slippage_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		let err = crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			50_000,
+			None,
+		)
+		.unwrap_err();
+
+		// Quoting the trade read the market, FeeExempt, TradedVolume and FeeRemainders
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		assert_eq!(err.post_info.actual_weight, Some(10_000 + db_weight.reads(4)));
+	})
+}
@@ -1,12 +1,12 @@
 use frame_support::{assert_noop, assert_ok};
 
-use crate::{tests::*, Error};
+use crate::{tests::*, Error, MarketId};
 
 #[test]
 fn deposit_liquidity_no_market() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
-		let market = (BTC, USD);
+		let market = MarketId::new(BTC, USD);
 
 		assert_noop!(
 			crate::Pallet::<Test>::deposit_liquidity(origin, market, 100, 100),
@@ -21,9 +21,16 @@ fn deposit_liquidity_no_enough_balance() {
 		let origin = Origin::signed(ALICE);
 		let base_asset = BTC;
 		let quote_asset = USD;
-		let market = (base_asset, quote_asset);
+		let market = MarketId::new(base_asset, quote_asset);
 
-		assert_ok!(crate::Pallet::<Test>::create_market_pool(origin.clone(), BTC, USD, 100, 100));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
 
 		assert_noop!(
 			crate::Pallet::<Test>::deposit_liquidity(origin, market, u128::MAX, u128::MAX),
@@ -38,14 +45,15 @@ fn deposit_liquidity() {
 		let origin = Origin::signed(ALICE);
 		let base_asset = BTC;
 		let quote_asset = USD;
-		let market = (base_asset, quote_asset);
+		let market = MarketId::new(base_asset, quote_asset);
 
 		assert_ok!(crate::Pallet::<Test>::create_market_pool(
 			origin.clone(),
 			BTC,
 			USD,
 			100_000,
-			100_000
+			100_000,
+			None
 		));
 		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(origin, market, 100_000, 100_000));
 
@@ -57,3 +65,29 @@ fn deposit_liquidity() {
 		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (200_000, 200_000));
 	})
 }
+
+#[test]
+fn deposit_liquidity_updates_reserves() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(origin, market, 50_000, 25_000));
+
+		// The deposit has to be reflected in the reserves of the market
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 150_000);
+		assert_eq!(market_info.quote_balance, 125_000);
+
+		// And they have to match what the pool account actually holds
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+	})
+}
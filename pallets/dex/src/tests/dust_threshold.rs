@@ -0,0 +1,86 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn withdraw_leaving_dust_drains_pool() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		DustThreshold::set(1_000);
+		let market = MarketId::new(BTC, USD);
+
+		// Only 500 of each asset would remain
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			Origin::signed(ALICE),
+			market,
+			99_500,
+			99_500,
+			false
+		));
+
+		// ALICE receives the full reserves instead and the market is gone
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 1_000_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 1_000_000);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market), None);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (0, 0));
+		System::assert_last_event(Event::Dex(crate::Event::MarketRemoved(
+			ALICE, market, 100_000, 100_000,
+		)));
+	})
+}
+
+#[test]
+fn withdraw_leaving_healthy_reserves() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		DustThreshold::set(1_000);
+		let market = MarketId::new(BTC, USD);
+
+		// Exactly the threshold remains
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			Origin::signed(ALICE),
+			market,
+			99_000,
+			99_000,
+			false
+		));
+
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 1_000);
+		assert_eq!(market_info.quote_balance, 1_000);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (1_000, 1_000));
+	})
+}
+
+#[test]
+fn withdraw_leaving_dust_with_other_providers() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		DustThreshold::set(1_000);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(Origin::signed(BOB), market, 1_000, 0));
+
+		// The remaining dust would still belong to BOB
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				Origin::signed(ALICE),
+				market,
+				0,
+				99_500,
+				false
+			),
+			Error::<Test>::WouldLeaveDust
+		);
+	})
+}
@@ -0,0 +1,60 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn trade_events() -> usize {
+	System::events()
+		.iter()
+		.filter(|record| {
+			matches!(record.event, Event::Dex(crate::Event::Bought(..) | crate::Event::Sold(..)))
+		})
+		.count()
+}
+
+#[test]
+fn emit_trade_events_enabled() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 1_000, None));
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 1_000, false, None));
+
+		assert_eq!(trade_events(), 2);
+	})
+}
+
+#[test]
+fn emit_trade_events_disabled() {
+	new_test_ext().execute_with(|| {
+		EmitTradeEvents::set(false);
+
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 1_000, None));
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 1_000, false, None));
+
+		assert_eq!(trade_events(), 0);
+		// The pool creation is still visible
+		System::assert_has_event(Event::Dex(crate::Event::PoolCreated(
+			ALICE, market, 100_000, 100_000,
+		)));
+	})
+}
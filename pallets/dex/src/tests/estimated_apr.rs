@@ -0,0 +1,102 @@
+use frame_support::assert_ok;
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn estimated_apr_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(crate::Pallet::<Test>::estimated_apr(MarketId::new(BTC, USD)), None);
+	})
+}
+
+#[test]
+fn estimated_apr() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// Nothing has been traded yet
+		assert_eq!(crate::Pallet::<Test>::estimated_apr(market), Some(Perbill::zero()));
+
+		// 10 QUOTE fees collected over the 5 blocks since the last payout.
+		// Relative to the QUOTE reserve of 109_990 and averaged over both reserves
+		// that is ~0.0045% per 5 blocks, or ~9.09% for 10_000 blocks per year
+		System::set_block_number(5);
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+		assert_eq!(
+			crate::Pallet::<Test>::estimated_apr(market),
+			Some(Perbill::from_parts(90_917_356))
+		);
+
+		// The same fees collected over a longer time yield less
+		System::set_block_number(9);
+		assert!(crate::Pallet::<Test>::estimated_apr(market).unwrap() < Perbill::from_percent(6));
+	})
+}
+
+#[test]
+fn estimated_apr_after_early_payout() {
+	new_test_ext().execute_with(|| {
+		MaxUndistributedFees::set(5);
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// The early payout in block 4 is not at a PayoutInterval,
+		// still the lookback window restarts with the next block
+		System::set_block_number(3);
+		assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		run_to_block(4);
+		assert_eq!(crate::FeesCollectedSince::<Test>::get(market), Some(5));
+		assert_eq!(crate::Pallet::<Test>::estimated_apr(market), Some(Perbill::zero()));
+
+		// 10 QUOTE fees collected over the blocks 5 to 8
+		System::set_block_number(8);
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		let parts = 10 * 10_000 * 1_000_000_000 / (2 * market_info.quote_balance * 4);
+		assert_eq!(
+			crate::Pallet::<Test>::estimated_apr(market),
+			Some(Perbill::from_parts(parts as u32))
+		);
+	})
+}
+
+#[test]
+fn estimated_apr_overflow() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		crate::LiquidityPool::<Test>::mutate(market, |market_info| {
+			let market_info = market_info.as_mut().unwrap();
+			market_info.base_balance = u128::MAX;
+			market_info.quote_balance = u128::MAX;
+			market_info.collected_quote_fees = u128::MAX;
+		});
+
+		// Fails cleanly instead of panicking
+		assert_eq!(crate::Pallet::<Test>::estimated_apr(market), None);
+	})
+}
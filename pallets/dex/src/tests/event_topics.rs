@@ -0,0 +1,31 @@
+use frame_support::assert_ok;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn bought_event_topics() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+
+		let record = System::events().pop().unwrap();
+		assert_eq!(record.event, Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_082)));
+		assert_eq!(
+			record.topics,
+			vec![BlakeTwo256::hash_of(&market), BlakeTwo256::hash_of(&ALICE)]
+		);
+
+		// Clients can look up the activity of a market by its topic
+		let market_events = System::event_topics(BlakeTwo256::hash_of(&market));
+		assert_eq!(market_events.len(), 2);
+	})
+}
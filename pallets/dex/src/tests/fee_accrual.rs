@@ -0,0 +1,86 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+fn collected_fees() -> (Balance, Balance) {
+	let market_info = crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap();
+	(market_info.collected_base_fees, market_info.collected_quote_fees)
+}
+
+#[test]
+fn small_trades_accrue_fee() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// With a 10 bps taker fee, each trade owes a tenth of a QUOTE unit
+		for _ in 0..9 {
+			assert_ok!(crate::Pallet::<Test>::buy(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				100,
+				None
+			));
+		}
+		assert_eq!(collected_fees(), (0, 0));
+		assert_eq!(crate::Pallet::<Test>::fee_remainders(MarketId::new(BTC, USD)), (0, 900));
+
+		// The tenth trade completes a whole unit of fee
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			100,
+			None
+		));
+		assert_eq!(collected_fees(), (0, 1));
+		assert_eq!(crate::Pallet::<Test>::fee_remainders(MarketId::new(BTC, USD)), (0, 0));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 1);
+	})
+}
+
+#[test]
+fn fee_remainders_per_asset() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			500,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			500,
+			false,
+			None
+		));
+
+		// Fractions of different assets never add up
+		assert_eq!(collected_fees(), (0, 0));
+		assert_eq!(crate::Pallet::<Test>::fee_remainders(MarketId::new(BTC, USD)), (500, 500));
+
+		// The carried fraction is added to the fee of the next trade in the same asset
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			1_500,
+			false,
+			None
+		));
+		assert_eq!(collected_fees(), (2, 0));
+		assert_eq!(crate::Pallet::<Test>::fee_remainders(MarketId::new(BTC, USD)), (0, 500));
+	})
+}
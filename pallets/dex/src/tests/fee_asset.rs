@@ -0,0 +1,171 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+use crate::{tests::*, types::FeeAsset, MarketId};
+
+/// Creates a MarketId::new(BTC, USD) market with 100_000 of each asset and sets its fee asset
+fn setup_market(fee_asset: FeeAsset) {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	assert_ok!(crate::Pallet::<Test>::set_fee_asset(
+		Origin::root(),
+		MarketId::new(BTC, USD),
+		fee_asset
+	));
+}
+
+#[test]
+fn set_fee_asset_not_admin() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_noop!(
+			crate::Pallet::<Test>::set_fee_asset(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				FeeAsset::Base
+			),
+			DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn fee_asset_input() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Input);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+
+		// Buying pays the fee in QUOTE
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 10);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 0);
+
+		// Selling pays the fee in BASE
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			false,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 10);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 10);
+	})
+}
+
+#[test]
+fn fee_asset_base() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Base);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+
+		// The whole 10_000 QUOTE enter the pool, 9_090 BASE leave it of which 9 are the fee
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 9);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 909_081);
+
+		let market_info = crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap();
+		assert_eq!(market_info.base_balance, 90_910);
+		assert_eq!(market_info.quote_balance, 110_000);
+		assert_eq!(market_info.collected_base_fees, 9);
+		assert_eq!(market_info.collected_quote_fees, 0);
+
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+	})
+}
+
+#[test]
+fn fee_asset_quote() {
+	new_test_ext().execute_with(|| {
+		setup_market(FeeAsset::Quote);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+
+		// The whole 10_000 BASE enter the pool, 9_090 QUOTE leave it of which 9 are the fee
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			false,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 9);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 909_081);
+
+		let market_info = crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap();
+		assert_eq!(market_info.base_balance, 110_000);
+		assert_eq!(market_info.quote_balance, 90_910);
+		assert_eq!(market_info.collected_base_fees, 0);
+		assert_eq!(market_info.collected_quote_fees, 9);
+
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+	})
+}
+
+#[test]
+fn sell_fee_in_quote() {
+	new_test_ext().execute_with(|| {
+		SellFeeInQuote::set(true);
+		setup_market(FeeAsset::Input);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+
+		// The whole 10_000 BASE enter the pool, 9_090 QUOTE leave it of which 9 are the fee
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			false,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 9);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 909_081);
+
+		let market_info = crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap();
+		assert_eq!(market_info.base_balance, 110_000);
+		assert_eq!(market_info.quote_balance, 90_910);
+		assert_eq!(market_info.collected_base_fees, 0);
+		assert_eq!(market_info.collected_quote_fees, 9);
+		// k did not decrease
+		assert!(110_000 * 90_910 >= 100_000 * 100_000);
+
+		// Buying still pays the fee in QUOTE
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 0);
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD))
+				.unwrap()
+				.collected_quote_fees,
+			19
+		);
+	})
+}
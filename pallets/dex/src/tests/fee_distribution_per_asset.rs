@@ -0,0 +1,52 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn fee_distribution_per_asset() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// BOB provides half of the BASE reserve, but none of the QUOTE reserve
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			100_000,
+			0
+		));
+
+		// A sell collects its fee in BASE and a buy collects its fee in QUOTE
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_base_fees, 10);
+		assert_eq!(market_info.collected_quote_fees, 10);
+
+		let alice_btc = crate::Pallet::<Test>::balance(BTC, &ALICE);
+		let alice_usd = crate::Pallet::<Test>::balance(USD, &ALICE);
+		let bob_btc = crate::Pallet::<Test>::balance(BTC, &BOB);
+		let bob_usd = crate::Pallet::<Test>::balance(USD, &BOB);
+
+		run_to_block(PayoutInterval::get());
+
+		// The BASE fees are split by the shares of the BASE reserve
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), alice_btc + 5);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), bob_btc + 5);
+		// The QUOTE fees are split by the shares of the QUOTE reserve, of which BOB has none
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), alice_usd + 10);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), bob_usd);
+	})
+}
@@ -0,0 +1,80 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+use crate::{tests::*, MarketId};
+
+fn create_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn set_fee_exempt_requires_admin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::set_fee_exempt(Origin::signed(ALICE), ALICE, true),
+			DispatchError::BadOrigin
+		);
+		assert!(!crate::Pallet::<Test>::is_fee_exempt(&ALICE));
+	})
+}
+
+#[test]
+fn exempt_account_swaps_without_fee() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), ALICE, true));
+		System::assert_last_event(Event::Dex(crate::Event::FeeExemptionSet(ALICE, true)));
+		assert!(crate::Pallet::<Test>::is_fee_exempt(&ALICE));
+
+		// The whole amount entered the pool
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_090)));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.quote_balance, 110_000);
+		assert_eq!(market_info.collected_quote_fees, 0);
+	})
+}
+
+#[test]
+fn non_exempt_account_swaps_with_fee() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), BOB, true));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+
+		// ALICE is not exempt, so the regular taker fee of 10 QUOTE is charged
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_082)));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_quote_fees, 10);
+	})
+}
+
+#[test]
+fn removed_exemption_charges_fee() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), ALICE, true));
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), ALICE, false));
+		System::assert_last_event(Event::Dex(crate::Event::FeeExemptionSet(ALICE, false)));
+		assert!(!crate::Pallet::<Test>::is_fee_exempt(&ALICE));
+
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_quote_fees, 10);
+	})
+}
@@ -1,8 +1,71 @@
-use crate::tests::*;
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
 
 #[test]
 fn fee_from_amount() {
 	new_test_ext().execute_with(|| {
-		assert_eq!(crate::Pallet::<Test>::fee_from_amount(1_000_000).unwrap(), 1_000);
+		let (market, market_info) = market_with_reserves(100_000, 100_000);
+		assert_eq!(
+			crate::Pallet::<Test>::trade_fee(market, &market_info, &ALICE, USD, 1_000_000),
+			Ok((1_000, Some(0)))
+		);
+	})
+}
+
+#[test]
+fn fee_from_amount_large_amount() {
+	new_test_ext().execute_with(|| {
+		// amount * fee_numerator would overflow a u128 for any numerator above 1
+		TakerFee::set((3, 1_000));
+		let (market, market_info) = market_with_reserves(100_000, 100_000);
+		assert_eq!(
+			crate::Pallet::<Test>::trade_fee(market, &market_info, &ALICE, USD, u128::MAX - 1)
+				.unwrap()
+				.0,
+			(u128::MAX - 1) / 1_000 * 3 + (u128::MAX - 1) % 1_000 * 3 / 1_000
+		);
+	})
+}
+
+#[test]
+fn fee_from_amount_capped() {
+	new_test_ext().execute_with(|| {
+		MaxAbsoluteFee::set(500);
+		let (market, market_info) = market_with_reserves(100_000, 100_000);
+		// A capped fee keeps the remainder unchanged
+		assert_eq!(
+			crate::Pallet::<Test>::trade_fee(market, &market_info, &ALICE, USD, 1_000_000),
+			Ok((500, None))
+		);
+		// Below the cap the percentage fee applies
+		assert_eq!(
+			crate::Pallet::<Test>::trade_fee(market, &market_info, &ALICE, USD, 100_000),
+			Ok((100, Some(0)))
+		);
+	})
+}
+
+#[test]
+fn trade_fee_capped() {
+	new_test_ext().execute_with(|| {
+		MaxAbsoluteFee::set(5);
+
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// The percentage fee would be 10 QUOTE
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 5);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().quote_balance, 109_995);
 	})
 }
@@ -0,0 +1,54 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn create_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn trade_within_fee_grace_period() {
+	new_test_ext().execute_with(|| {
+		FeeGracePeriod::set(10);
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// The market was created in block 1, so the last fee-free block is 10
+		System::set_block_number(10);
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+
+		// The whole amount entered the pool
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_090)));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.quote_balance, 110_000);
+		assert_eq!(market_info.collected_quote_fees, 0);
+	})
+}
+
+#[test]
+fn trade_after_fee_grace_period() {
+	new_test_ext().execute_with(|| {
+		FeeGracePeriod::set(10);
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		System::set_block_number(11);
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+
+		// The regular taker fee of 10 QUOTE is charged again
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 10);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.quote_balance, 109_990);
+		assert_eq!(market_info.collected_quote_fees, 10);
+	})
+}
@@ -0,0 +1,103 @@
+use frame_support::assert_ok;
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+/// Every transfer of XMR delivers 1% less to the receiver
+fn setup_fee_on_transfer() {
+	TransferFeeAsset::set(Some(XMR));
+	TransferFee::set(Perbill::from_percent(1));
+}
+
+#[test]
+fn create_market_pool_fee_on_transfer() {
+	new_test_ext().execute_with(|| {
+		setup_fee_on_transfer();
+		let market = MarketId::new(XMR, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			XMR,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Only 99_000 XMR arrived in the pool
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 99_000);
+		assert_eq!(market_info.quote_balance, 100_000);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (99_000, 100_000));
+		System::assert_has_event(Event::Dex(crate::Event::PoolCreated(
+			ALICE, market, 99_000, 100_000,
+		)));
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+	})
+}
+
+#[test]
+fn deposit_liquidity_fee_on_transfer() {
+	new_test_ext().execute_with(|| {
+		setup_fee_on_transfer();
+		let market = MarketId::new(XMR, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			XMR,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			10_000,
+			10_000
+		));
+
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 108_900);
+		assert_eq!(market_info.quote_balance, 110_000);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (108_900, 110_000));
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+	})
+}
+
+#[test]
+fn trade_fee_on_transfer() {
+	new_test_ext().execute_with(|| {
+		setup_fee_on_transfer();
+		let market = MarketId::new(XMR, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			XMR,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// 10 XMR taker fee, of the remaining 9_990 XMR only 9_891 arrive in the pool
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 10_000, false, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 108_891);
+		assert_eq!(market_info.quote_balance, 90_917);
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+
+		// The XMR leaving the pool is debited in full
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_ok!(crate::Pallet::<Test>::do_try_state());
+
+		// The recorded fees can be paid out in full
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(
+			crate::Pallet::<Test>::balance(XMR, &pool_fee_account),
+			market_info.collected_base_fees
+		);
+		assert_eq!(
+			crate::Pallet::<Test>::balance(USD, &pool_fee_account),
+			market_info.collected_quote_fees
+		);
+	})
+}
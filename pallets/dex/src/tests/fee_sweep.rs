@@ -0,0 +1,127 @@
+use frame_support::{assert_ok, traits::Get};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+fn fee_account_balance(asset: AssetId) -> Balance {
+	crate::Pallet::<Test>::balance(asset, &crate::Pallet::<Test>::pool_fee_account())
+}
+
+#[test]
+fn fee_sweep() {
+	new_test_ext().execute_with(|| {
+		FeeSweepThreshold::set(Some(20));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		let fee_account = crate::Pallet::<Test>::pool_fee_account();
+
+		// Remainders owed to nobody pile up in the fee account until they exceed the threshold
+		for cycle in 1..=2 {
+			assert_ok!(Assets::transfer(Origin::signed(ALICE), BTC, fee_account.clone(), 8));
+			run_to_block(cycle * PayoutInterval::get());
+			assert_eq!(fee_account_balance(BTC), cycle as Balance * 8);
+			assert_eq!(crate::Pallet::<Test>::balance(BTC, &TREASURY), 0);
+		}
+
+		assert_ok!(Assets::transfer(Origin::signed(ALICE), BTC, fee_account, 8));
+		run_to_block(3 * PayoutInterval::get());
+		System::assert_last_event(Event::Dex(crate::Event::FeeDustSwept(BTC, 24)));
+		assert_eq!(fee_account_balance(BTC), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &TREASURY), 24);
+	})
+}
+
+#[test]
+fn fee_sweep_keeps_owed_fees() {
+	new_test_ext().execute_with(|| {
+		FeeSweepThreshold::set(Some(0));
+		ProtocolFeeShare::set(Perbill::from_percent(50));
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// Collects 10 USD as fee, 5 of which are protocol fees
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_ok!(Assets::transfer(
+			Origin::signed(ALICE),
+			USD,
+			crate::Pallet::<Test>::pool_fee_account(),
+			3
+		));
+
+		run_to_block(PayoutInterval::get());
+
+		// Only the dust is swept, the protocol fees are left for collect_protocol_fees
+		System::assert_last_event(Event::Dex(crate::Event::FeeDustSwept(USD, 3)));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &TREASURY), 3);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 5));
+		assert_eq!(fee_account_balance(USD), 5);
+	})
+}
+
+#[test]
+fn fee_sweep_disabled() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(Assets::transfer(
+			Origin::signed(ALICE),
+			BTC,
+			crate::Pallet::<Test>::pool_fee_account(),
+			100
+		));
+
+		run_to_block(PayoutInterval::get());
+
+		assert_eq!(fee_account_balance(BTC), 100);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &TREASURY), 0);
+	})
+}
+
+#[test]
+fn fee_sweep_weight() {
+	new_test_ext().execute_with(|| {
+		FeeSweepThreshold::set(Some(20));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+
+		// A read of the market and of the fee account balances of BTC and USD
+		assert_eq!(crate::Pallet::<Test>::sweep_fee_dust(), db_weight.reads(3));
+
+		// Plus the transfer of the dust
+		assert_ok!(Assets::transfer(
+			Origin::signed(ALICE),
+			BTC,
+			crate::Pallet::<Test>::pool_fee_account(),
+			100
+		));
+		assert_eq!(
+			crate::Pallet::<Test>::sweep_fee_dust(),
+			db_weight.reads(3) + db_weight.reads_writes(3, 2)
+		);
+	})
+}
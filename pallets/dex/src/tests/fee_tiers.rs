@@ -0,0 +1,64 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn create_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn trader_crosses_fee_tier() {
+	new_test_ext().execute_with(|| {
+		TakerFee::set((3, 1_000));
+		FeeTiers::set(vec![(10_000, 1)]);
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// Below the threshold the regular taker fee of 30 QUOTE is charged
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_eq!(crate::Pallet::<Test>::traded_volume(market, ALICE), 10_000);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_quote_fees, 30);
+
+		// The traded volume reached the tier, so only 10 QUOTE are charged
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_eq!(crate::Pallet::<Test>::traded_volume(market, ALICE), 20_000);
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_quote_fees, 40);
+	})
+}
+
+#[test]
+fn traded_volume_per_account_and_market() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			XMR,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		let market = MarketId::new(BTC, USD);
+
+		// A sell counts the QUOTE received
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::traded_volume(market, CHARLIE), 9_082);
+		assert_eq!(crate::Pallet::<Test>::traded_volume(market, ALICE), 0);
+		assert_eq!(crate::Pallet::<Test>::traded_volume(MarketId::new(XMR, USD), CHARLIE), 0);
+	})
+}
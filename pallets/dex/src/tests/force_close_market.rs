@@ -0,0 +1,140 @@
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_runtime::{DispatchError, Perbill};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	let market = MarketId::new(BTC, USD);
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	// BOB provides a third of the BASE asset
+	assert_ok!(crate::Pallet::<Test>::deposit_liquidity(Origin::signed(BOB), market, 50_000, 0));
+	// Shifts the reserves to 159_990 BTC and 93_756 USD and collects 10 BTC as fee
+	assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(CHARLIE), market, 10_000, false, None));
+}
+
+#[test]
+fn force_close_market() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::force_close_market(Origin::root(), market));
+
+		// ALICE gets two thirds of the BASE reserve and fees and the whole QUOTE reserve
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 900_000 + 106_660 + 6);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 900_000 + 93_756);
+		// BOB gets the rest of the BASE reserve and fees
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 950_000 + 53_330 + 4);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), 0);
+
+		assert_eq!(crate::LiquidityPool::<Test>::get(market), None);
+		assert_eq!(crate::LiqProvisionPool::<Test>::iter_prefix(market).count(), 0);
+		assert!(crate::Pallet::<Test>::markets_per_asset(BTC).is_empty());
+		System::assert_last_event(Event::Dex(crate::Event::MarketForceClosed(
+			market, 159_990, 93_756,
+		)));
+	})
+}
+
+#[test]
+fn force_close_market_not_admin() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		assert_noop!(
+			crate::Pallet::<Test>::force_close_market(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD)
+			),
+			DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn force_close_market_does_not_exist() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::force_close_market(Origin::root(), MarketId::new(BTC, USD)),
+			Error::<Test>::MarketDoesNotExist
+		);
+	})
+}
+
+#[test]
+fn force_close_market_too_many_providers() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		MaxForceCloseProviders::set(1);
+
+		assert_noop!(
+			crate::Pallet::<Test>::force_close_market(Origin::root(), MarketId::new(BTC, USD)),
+			Error::<Test>::TooManyProviders
+		);
+	})
+}
+
+#[test]
+fn force_close_market_pays_pending_withdrawals() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		// BOB withdraws all of his liquidity, so he is no provider anymore
+		assert_ok!(crate::Pallet::<Test>::request_withdrawal(
+			Origin::signed(BOB),
+			market,
+			Perbill::one()
+		));
+		let pending = crate::Pallet::<Test>::pending_withdrawal(market, BOB).unwrap();
+		let (btc_balance, usd_balance) =
+			(crate::Pallet::<Test>::balance(BTC, &BOB), crate::Pallet::<Test>::balance(USD, &BOB));
+
+		// The withdrawal does not wait for the WithdrawalDelay of a closed market
+		assert_ok!(crate::Pallet::<Test>::force_close_market(Origin::root(), market));
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), btc_balance + pending.base_amount);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), usd_balance + pending.quote_amount);
+		let withdrawal_account = crate::Pallet::<Test>::withdrawal_account();
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &withdrawal_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &withdrawal_account), 0);
+		System::assert_has_event(Event::Dex(crate::Event::LiquidityWithdrawn(
+			BOB,
+			market,
+			pending.base_amount,
+			pending.quote_amount,
+		)));
+		assert_noop!(
+			crate::Pallet::<Test>::execute_withdrawal(Origin::signed(BOB), market),
+			Error::<Test>::NoPendingWithdrawal
+		);
+	})
+}
+
+#[test]
+fn force_close_market_clears_history() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert!(crate::TradedVolume::<Test>::get(market, CHARLIE) > 0);
+
+		assert_ok!(crate::Pallet::<Test>::force_close_market(Origin::root(), market));
+
+		// A market created again later starts without volume tiers and lifetime fees
+		assert_eq!(crate::TradedVolume::<Test>::iter_prefix(market).count(), 0);
+		assert_eq!(crate::LifetimeFees::<Test>::iter_prefix(market).count(), 0);
+	})
+}
+
+#[test]
+#[should_panic(expected = "MaxForceCloseProviders must cover the MaxProvidersPerMarket")]
+fn force_close_providers_below_providers_per_market() {
+	MaxForceCloseProviders::set(MaxProvidersPerMarket::get() - 1);
+	<crate::Pallet<Test> as Hooks<BlockNumber>>::integrity_test();
+}
@@ -1,24 +1,69 @@
-use crate::{tests::*, types::OrderType};
+use frame_support::assert_ok;
+
+use crate::{tests::*, types::FeeAsset, MarketId, OrderType};
+
+fn create_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+// Quotes a trade of ALICE, executes it and checks that she received the quoted amount
+fn assert_quote_received(order_type: OrderType, amount: Balance) {
+	let market = MarketId::new(BTC, USD);
+	let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+	let remainders = crate::FeeRemainders::<Test>::get(market);
+	let quoted = crate::Pallet::<Test>::get_received_amount(
+		market,
+		&market_info,
+		&ALICE,
+		order_type,
+		amount,
+	)
+	.unwrap();
+	// Quoting does not carry over the remainder of the fee
+	assert_eq!(crate::FeeRemainders::<Test>::get(market), remainders);
+
+	let asset_out = match order_type {
+		OrderType::Buy => BTC,
+		OrderType::Sell => USD,
+	};
+	let balance_before = crate::Pallet::<Test>::balance(asset_out, &ALICE);
+	let origin = Origin::signed(ALICE);
+	match order_type {
+		OrderType::Buy => assert_ok!(crate::Pallet::<Test>::buy(origin, market, amount, None)),
+		OrderType::Sell => {
+			assert_ok!(crate::Pallet::<Test>::sell(origin, market, amount, false, None))
+		},
+	}
+	assert_eq!(crate::Pallet::<Test>::balance(asset_out, &ALICE) - balance_before, quoted);
+}
 
 #[test]
 fn get_received_amount() {
 	new_test_ext().execute_with(|| {
-		let base_amount = 100;
-		let quote_amount = 100;
+		let (market, market_info) = market_with_reserves(100, 100);
 
 		let receive_amount = crate::Pallet::<Test>::get_received_amount(
-			base_amount,
-			quote_amount,
+			market,
+			&market_info,
+			&ALICE,
 			OrderType::Buy,
 			10,
 		)
 		.unwrap();
 		println!("receive_amount: {}", receive_amount);
-		assert_eq!(receive_amount, 10);
+		assert_eq!(receive_amount, 9);
 
 		let receive_amount = crate::Pallet::<Test>::get_received_amount(
-			base_amount,
-			quote_amount,
+			market,
+			&market_info,
+			&ALICE,
 			OrderType::Buy,
 			100,
 		)
@@ -27,18 +72,20 @@ fn get_received_amount() {
 		assert_eq!(receive_amount, 50);
 
 		let receive_amount = crate::Pallet::<Test>::get_received_amount(
-			base_amount,
-			quote_amount,
+			market,
+			&market_info,
+			&ALICE,
 			OrderType::Sell,
 			10,
 		)
 		.unwrap();
 		println!("receive_amount: {}", receive_amount);
-		assert_eq!(receive_amount, 10);
+		assert_eq!(receive_amount, 9);
 
 		let receive_amount = crate::Pallet::<Test>::get_received_amount(
-			base_amount,
-			quote_amount,
+			market,
+			&market_info,
+			&ALICE,
 			OrderType::Sell,
 			100,
 		)
@@ -47,3 +94,97 @@ fn get_received_amount() {
 		assert_eq!(receive_amount, 50);
 	})
 }
+
+#[test]
+fn get_received_amount_no_liquidity() {
+	new_test_ext().execute_with(|| {
+		for (base_amount, quote_amount) in [(0, 100), (100, 0), (0, 0)] {
+			let (market, market_info) = market_with_reserves(base_amount, quote_amount);
+			for order_type in [OrderType::Buy, OrderType::Sell] {
+				assert_eq!(
+					crate::Pallet::<Test>::get_received_amount(
+						market,
+						&market_info,
+						&ALICE,
+						order_type,
+						10,
+					),
+					Err(crate::Error::<Test>::NoLiquidity.into())
+				);
+			}
+		}
+	})
+}
+
+#[test]
+fn get_received_amount_never_drains_reserve() {
+	new_test_ext().execute_with(|| {
+		// An enormous input can at most take all but one unit of the output reserve
+		let (market, market_info) = market_with_reserves(100, 100);
+		for order_type in [OrderType::Buy, OrderType::Sell] {
+			let receive_amount = crate::Pallet::<Test>::get_received_amount(
+				market,
+				&market_info,
+				&ALICE,
+				order_type,
+				10u128.pow(30),
+			)
+			.unwrap();
+			assert_eq!(receive_amount, 99);
+		}
+	})
+}
+
+#[test]
+fn get_received_amount_fee_tiers() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		TakerFee::set((3, 1_000));
+		FeeTiers::set(vec![(10_000, 1)]);
+
+		// The first trade pays the TakerFee, the second one the tier it reached,
+		// each with the remainder carried over from before
+		for _ in 0..2 {
+			assert_quote_received(OrderType::Buy, 10_001);
+		}
+		assert_quote_received(OrderType::Sell, 10_001);
+	})
+}
+
+#[test]
+fn get_received_amount_fee_exempt() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), ALICE, true));
+
+		assert_quote_received(OrderType::Buy, 10_000);
+		assert_quote_received(OrderType::Sell, 10_000);
+	})
+}
+
+#[test]
+fn get_received_amount_fee_grace_period() {
+	new_test_ext().execute_with(|| {
+		FeeGracePeriod::set(10);
+		create_market();
+
+		assert_quote_received(OrderType::Buy, 10_000);
+		assert_quote_received(OrderType::Sell, 10_000);
+	})
+}
+
+#[test]
+fn get_received_amount_fee_in_output() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		assert_ok!(crate::Pallet::<Test>::set_fee_asset(
+			Origin::root(),
+			MarketId::new(BTC, USD),
+			FeeAsset::Quote
+		));
+
+		// Selling pays the fee out of the QUOTE asset received
+		assert_quote_received(OrderType::Sell, 10_001);
+		assert_quote_received(OrderType::Buy, 10_001);
+	})
+}
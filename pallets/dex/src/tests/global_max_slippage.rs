@@ -0,0 +1,67 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn buy_global_max_slippage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		GlobalMaxSlippage::set(Perbill::from_percent(10));
+
+		// 11_112 / 111_112 is just over 10%
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_112, None),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		// Enforced regardless of other limits passed by the user
+		assert_noop!(
+			crate::Pallet::<Test>::buy_until_price(Origin::signed(ALICE), market, 50_000, (2, 1)),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		// 11_111 / 111_111 is just below 10%
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 11_111, None));
+	})
+}
+
+#[test]
+fn sell_global_max_slippage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		GlobalMaxSlippage::set(Perbill::from_percent(10));
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_112, false, None),
+			Error::<Test>::PriceImpactTooHigh
+		);
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 11_111, false, None));
+	})
+}
+
+#[test]
+fn global_max_slippage_disabled() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// A trade as large as the reserve has a price impact of 50%
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			100_000,
+			None
+		));
+	})
+}
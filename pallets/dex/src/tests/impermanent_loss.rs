@@ -0,0 +1,33 @@
+use frame_support::assert_ok;
+use sp_runtime::Perbill;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn impermanent_loss() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::impermanent_loss(market, &ALICE), None);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// No loss without a price move
+		assert_eq!(crate::Pallet::<Test>::impermanent_loss(market, &ALICE), Some(Perbill::zero()));
+		assert_eq!(crate::Pallet::<Test>::impermanent_loss(market, &BOB), None);
+
+		// Moves the reserves to 109_990 BTC and 90_918 USD
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 10_000, false, None));
+
+		// Holding is worth 100_000 * (90_918 + 109_990) and the claim 2 * 109_990 * 90_918,
+		// both in USD times the BTC reserve
+		let impermanent_loss = crate::Pallet::<Test>::impermanent_loss(market, &ALICE).unwrap();
+		assert_eq!(impermanent_loss, Perbill::from_parts(4_512_431));
+		assert!(!impermanent_loss.is_zero());
+	})
+}
@@ -0,0 +1,96 @@
+use proptest::prelude::*;
+use sp_core::U256;
+
+use crate::{tests::*, types::OrderType};
+
+type Dex = crate::Pallet<Test>;
+
+fn order_type() -> impl Strategy<Value = OrderType> {
+	prop_oneof![Just(OrderType::Buy), Just(OrderType::Sell)]
+}
+
+proptest! {
+	#[test]
+	fn received_amount_below_reserve(
+		base_reserve in 1u128..1_000_000_000_000_000,
+		quote_reserve in 1u128..1_000_000_000_000_000,
+		order_type in order_type(),
+		amount in 1u128..1_000_000_000_000_000,
+	) {
+		let reserve_out = match order_type {
+			OrderType::Buy => base_reserve,
+			OrderType::Sell => quote_reserve,
+		};
+		new_test_ext().execute_with(|| {
+			let (market, market_info) = market_with_reserves(base_reserve, quote_reserve);
+			let received =
+				Dex::get_received_amount(market, &market_info, &ALICE, order_type, amount).unwrap();
+
+			prop_assert!(received < reserve_out);
+			Ok(())
+		})?;
+	}
+
+	#[test]
+	fn k_never_decreases(
+		base_reserve in 1u128..1_000_000_000_000_000,
+		quote_reserve in 1u128..1_000_000_000_000_000,
+		order_type in order_type(),
+		amount in 1u128..1_000_000_000_000_000,
+	) {
+		let (asset_in, reserve_in, reserve_out) = match order_type {
+			OrderType::Buy => (USD, quote_reserve, base_reserve),
+			OrderType::Sell => (BTC, base_reserve, quote_reserve),
+		};
+		new_test_ext().execute_with(|| {
+			let (market, market_info) = market_with_reserves(base_reserve, quote_reserve);
+			// Only the amount without the taker fee ends up in the pool
+			let (fee, _) = Dex::trade_fee(market, &market_info, &ALICE, asset_in, amount).unwrap();
+			let received =
+				Dex::get_received_amount(market, &market_info, &ALICE, order_type, amount).unwrap();
+
+			let k_before = U256::from(reserve_in) * U256::from(reserve_out);
+			let k_after =
+				U256::from(reserve_in + amount - fee) * U256::from(reserve_out - received);
+			prop_assert!(k_after >= k_before);
+			Ok(())
+		})?;
+	}
+
+	#[test]
+	fn round_trip_only_loses_fees(
+		base_reserve in 1_000u128..1_000_000_000_000,
+		price in 1u128..=100,
+		amount in 1_000u128..1_000_000_000_000,
+	) {
+		prop_assume!(amount <= base_reserve);
+		let quote_reserve = base_reserve * price;
+
+		new_test_ext().execute_with(|| {
+			let (market, market_info) = market_with_reserves(base_reserve, quote_reserve);
+			let (sell_fee, _) =
+				Dex::trade_fee(market, &market_info, &ALICE, BTC, amount).unwrap();
+			let quote_received =
+				Dex::get_received_amount(market, &market_info, &ALICE, OrderType::Sell, amount)
+					.unwrap();
+			// Buy back with everything received, against the reserves after the sell
+			let (market, market_info) = market_with_reserves(
+				base_reserve + amount - sell_fee,
+				quote_reserve - quote_received,
+			);
+			let base_received = Dex::get_received_amount(
+				market,
+				&market_info,
+				&ALICE,
+				OrderType::Buy,
+				quote_received,
+			)
+			.unwrap();
+
+			prop_assert!(base_received <= amount);
+			// The taker fee is paid twice, apart from that only a few units are lost to rounding
+			prop_assert!(amount - base_received <= 2 * sell_fee + 4);
+			Ok(())
+		})?;
+	}
+}
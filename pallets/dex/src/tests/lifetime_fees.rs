@@ -0,0 +1,53 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn lifetime_fees() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// BOB provides half of the BASE asset
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			100_000,
+			0
+		));
+
+		// 10 BTC and 10 USD in fees
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		run_to_block(PayoutInterval::get());
+
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (5, 10));
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, BOB), (5, 0));
+
+		// The next payout adds up with the previous one
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		run_to_block(2 * PayoutInterval::get());
+
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (10, 10));
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, BOB), (10, 0));
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, CHARLIE), (0, 0));
+	})
+}
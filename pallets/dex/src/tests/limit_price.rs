@@ -0,0 +1,111 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn buy_until_price_capped() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		// Moving the price from 1.0 to 1.21 only takes 10_010 of the 50_000 QUOTE
+		assert_ok!(crate::Pallet::<Test>::buy_until_price(
+			Origin::signed(ALICE),
+			market,
+			50_000,
+			(121, 100)
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 900_000 - 10_010);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().quote_balance, 110_000);
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_010, 9_090)));
+	})
+}
+
+#[test]
+fn buy_until_price_not_capped() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::buy_until_price(
+			Origin::signed(ALICE),
+			market,
+			5_000,
+			(121, 100)
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 900_000 - 5_000);
+	})
+}
+
+#[test]
+fn sell_until_price_capped() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		// Moving the price from 1.0 to 1 / 1.21 only takes 10_010 of the 50_000 BASE
+		assert_ok!(crate::Pallet::<Test>::sell_until_price(
+			Origin::signed(ALICE),
+			market,
+			50_000,
+			(100, 121),
+			false
+		));
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 900_000 - 10_010);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().base_balance, 110_000);
+	})
+}
+
+#[test]
+fn price_limit_reached() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		// The price already is at or above the limit of a buy
+		for limit_price in [(1, 1), (1, 2)] {
+			assert_noop!(
+				crate::Pallet::<Test>::buy_until_price(
+					Origin::signed(ALICE),
+					market,
+					10_000,
+					limit_price
+				),
+				Error::<Test>::PriceLimitReached
+			);
+		}
+		// The price already is at or below the limit of a sell
+		for limit_price in [(1, 1), (2, 1)] {
+			assert_noop!(
+				crate::Pallet::<Test>::sell_until_price(
+					Origin::signed(ALICE),
+					market,
+					10_000,
+					limit_price,
+					false
+				),
+				Error::<Test>::PriceLimitReached
+			);
+		}
+		assert_noop!(
+			crate::Pallet::<Test>::buy_until_price(
+				Origin::signed(ALICE),
+				MarketId::new(XMR, USD),
+				10_000,
+				(2, 1)
+			),
+			Error::<Test>::MarketDoesNotExist
+		);
+	})
+}
@@ -0,0 +1,54 @@
+use frame_support::assert_ok;
+
+use crate::{
+	tests::*,
+	types::{BaseOrQuote, OrderType},
+	MarketId,
+};
+
+#[test]
+fn market_balance() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Base), 0);
+		assert_eq!(crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Quote), 0);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			200_000,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Base), 100_000);
+		assert_eq!(crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Quote), 200_000);
+
+		// Buying takes BASE out of the pool and puts QUOTE into it
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(
+			crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Base),
+			market_info.base_balance
+		);
+		assert_eq!(
+			crate::Pallet::<Test>::market_balance(market, BaseOrQuote::Quote),
+			market_info.quote_balance
+		);
+		assert!(market_info.base_balance < 100_000);
+		assert!(market_info.quote_balance > 200_000);
+	})
+}
+
+#[test]
+fn order_type_sides() {
+	let market = MarketId::new(BTC, USD);
+
+	let (side_in, side_out) = OrderType::Buy.sides();
+	assert_eq!((market.asset(side_in), market.asset(side_out)), (USD, BTC));
+	let (side_in, side_out) = OrderType::Sell.sides();
+	assert_eq!((market.asset(side_in), market.asset(side_out)), (BTC, USD));
+
+	assert_eq!(BaseOrQuote::Base.pick((1, 2)), 1);
+	assert_eq!(BaseOrQuote::Quote.pick((1, 2)), 2);
+}
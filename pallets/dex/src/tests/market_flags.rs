@@ -0,0 +1,156 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{DispatchError, Perbill};
+
+use crate::{tests::*, types::MarketFlags, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+fn set_flags(allow_deposit: bool, allow_withdraw: bool, allow_trade: bool) {
+	let flags = MarketFlags { allow_deposit, allow_withdraw, allow_trade };
+	assert_ok!(crate::Pallet::<Test>::set_market_flags(
+		Origin::root(),
+		MarketId::new(BTC, USD),
+		flags
+	));
+	System::assert_last_event(Event::Dex(crate::Event::MarketFlagsSet(
+		MarketId::new(BTC, USD),
+		flags,
+	)));
+}
+
+#[test]
+fn disable_deposit() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		set_flags(false, true, true);
+		assert_noop!(
+			crate::Pallet::<Test>::deposit_liquidity(Origin::signed(ALICE), market, 1_000, 1_000),
+			Error::<Test>::OperationDisabled
+		);
+		// The other operations are not affected
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 1_000, None));
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			Origin::signed(ALICE),
+			market,
+			1_000,
+			1_000,
+			false
+		));
+
+		set_flags(true, true, true);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			1_000,
+			1_000
+		));
+	})
+}
+
+#[test]
+fn disable_withdraw() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		set_flags(true, false, true);
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				Origin::signed(ALICE),
+				market,
+				1_000,
+				1_000,
+				false
+			),
+			Error::<Test>::OperationDisabled
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity_with_slippage(
+				Origin::signed(ALICE),
+				market,
+				Perbill::from_percent(10),
+				0,
+				0,
+				false
+			),
+			Error::<Test>::OperationDisabled
+		);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			1_000,
+			1_000
+		));
+
+		set_flags(true, true, true);
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			Origin::signed(ALICE),
+			market,
+			1_000,
+			1_000,
+			false
+		));
+	})
+}
+
+#[test]
+fn disable_trade() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		set_flags(true, true, false);
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 1_000, None)
+				.map_err(|e| e.error),
+			Error::<Test>::OperationDisabled
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 1_000, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::OperationDisabled
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::buy_to(Origin::signed(ALICE), market, 1_000, BOB),
+			Error::<Test>::OperationDisabled
+		);
+
+		set_flags(true, true, true);
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 1_000, None));
+	})
+}
+
+#[test]
+fn set_market_flags_errors() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::set_market_flags(
+				Origin::root(),
+				MarketId::new(BTC, USD),
+				MarketFlags::default()
+			),
+			Error::<Test>::MarketDoesNotExist
+		);
+
+		setup_market();
+		assert_noop!(
+			crate::Pallet::<Test>::set_market_flags(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				MarketFlags::default()
+			),
+			DispatchError::BadOrigin
+		);
+	})
+}
@@ -0,0 +1,54 @@
+use codec::{Decode, Encode};
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn market_id_new() {
+	let market = MarketId::new(BTC, USD);
+
+	assert_eq!(market.base, BTC);
+	assert_eq!(market.quote, USD);
+	assert_eq!(market, MarketId::from((BTC, USD)));
+	assert_eq!(<(AssetId, AssetId)>::from(market), (BTC, USD));
+	assert_eq!(market.inverse(), MarketId::new(USD, BTC));
+}
+
+#[test]
+fn market_id_canonical() {
+	// Both orders of the same assets identify the same market
+	assert_eq!(MarketId::canonical(BTC, USD), MarketId::new(BTC, USD));
+	assert_eq!(MarketId::canonical(USD, BTC), MarketId::new(BTC, USD));
+	assert!(MarketId::new(BTC, USD).is_canonical());
+	assert!(!MarketId::new(USD, BTC).is_canonical());
+}
+
+#[test]
+fn market_id_encoding() {
+	let market = MarketId::new(BTC, USD);
+	let encoded = market.encode();
+
+	assert_eq!(MarketId::<AssetId>::decode(&mut &encoded[..]).unwrap(), market);
+	// Storage keys of markets created before remain valid
+	assert_eq!(encoded, (BTC, USD).encode());
+}
+
+#[test]
+fn market_id_tuple_storage_key() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get((BTC, USD)),
+			crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD))
+		);
+		assert!(crate::LiquidityPool::<Test>::get((BTC, USD)).is_some());
+	})
+}
@@ -0,0 +1,43 @@
+use frame_support::assert_ok;
+
+use crate::{
+	tests::*,
+	types::{FeeAsset, MarketDetails, MarketFlags},
+	MarketId,
+};
+
+#[test]
+fn market_info_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(crate::Pallet::<Test>::market_info(MarketId::new(BTC, USD)), None);
+	})
+}
+
+#[test]
+fn market_info_after_trade() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 10_000, false, None));
+
+		assert_eq!(
+			crate::Pallet::<Test>::market_info(market),
+			Some(MarketDetails {
+				base_balance: 109_990,
+				quote_balance: 90_918,
+				collected_base_fees: 10,
+				collected_quote_fees: 0,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+			})
+		);
+	})
+}
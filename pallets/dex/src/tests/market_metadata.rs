@@ -0,0 +1,98 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn set_market_metadata() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::market_metadata(market), None);
+
+		assert_ok!(crate::Pallet::<Test>::set_market_metadata(
+			Origin::root(),
+			market,
+			b"Bitcoin / Dollar".to_vec()
+		));
+		assert_eq!(
+			crate::Pallet::<Test>::market_metadata(market).unwrap().into_inner(),
+			b"Bitcoin / Dollar".to_vec()
+		);
+		System::assert_last_event(Event::Dex(crate::Event::MarketMetadataSet(
+			market,
+			b"Bitcoin / Dollar".to_vec(),
+		)));
+
+		// An empty name clears the metadata
+		assert_ok!(crate::Pallet::<Test>::set_market_metadata(Origin::root(), market, vec![]));
+		assert_eq!(crate::Pallet::<Test>::market_metadata(market), None);
+	})
+}
+
+#[test]
+fn set_market_metadata_errors() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::set_market_metadata(
+				Origin::root(),
+				MarketId::new(BTC, USD),
+				b"BTC/USD".to_vec()
+			),
+			Error::<Test>::MarketDoesNotExist
+		);
+
+		setup_market();
+		assert_noop!(
+			crate::Pallet::<Test>::set_market_metadata(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				b"BTC/USD".to_vec()
+			),
+			DispatchError::BadOrigin
+		);
+		// One byte longer than the NameLimit of 16
+		assert_noop!(
+			crate::Pallet::<Test>::set_market_metadata(
+				Origin::root(),
+				MarketId::new(BTC, USD),
+				b"Bitcoin / Dollars".to_vec()
+			),
+			Error::<Test>::NameTooLong
+		);
+	})
+}
+
+#[test]
+fn metadata_has_no_effect_on_trading() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::set_market_metadata(
+			Origin::root(),
+			MarketId::new(BTC, USD),
+			b"BTC/USD".to_vec()
+		));
+
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap().base_balance,
+			90_918
+		);
+	})
+}
@@ -0,0 +1,29 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn market_origin() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::market_origin(market), None);
+
+		System::set_block_number(7);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.creator, ALICE);
+		assert_eq!(market_info.created_at, 7);
+		assert_eq!(crate::Pallet::<Test>::market_origin(market), Some((ALICE, 7)));
+
+		// The inverse market was never created
+		assert_eq!(crate::Pallet::<Test>::market_origin(market.inverse()), None);
+	})
+}
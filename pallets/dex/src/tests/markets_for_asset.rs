@@ -0,0 +1,99 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn markets_for_asset() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			XMR,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(origin, BTC, XMR, 100, 100, None));
+
+		assert_eq!(
+			crate::Pallet::<Test>::markets_for_asset(USD),
+			vec![MarketId::new(BTC, USD), MarketId::new(XMR, USD)]
+		);
+		assert_eq!(
+			crate::Pallet::<Test>::markets_for_asset(BTC),
+			vec![MarketId::new(BTC, USD), MarketId::new(BTC, XMR)]
+		);
+		assert_eq!(
+			crate::Pallet::<Test>::markets_for_asset(XMR),
+			vec![MarketId::new(XMR, USD), MarketId::new(BTC, XMR)]
+		);
+	})
+}
+
+#[test]
+fn markets_for_asset_remove_market() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			XMR,
+			USD,
+			100,
+			100,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::remove_market(origin, MarketId::new(BTC, USD)));
+
+		assert_eq!(crate::Pallet::<Test>::markets_for_asset(USD), vec![MarketId::new(XMR, USD)]);
+		assert!(crate::Pallet::<Test>::markets_for_asset(BTC).is_empty());
+	})
+}
+
+#[test]
+fn markets_for_asset_too_many_markets() {
+	new_test_ext().execute_with(|| {
+		MaxMarketsPerAsset::set(2);
+
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			XMR,
+			USD,
+			100,
+			100,
+			None
+		));
+
+		// USD is already part of two markets
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(origin, USD, BTC, 100, 100, None),
+			Error::<Test>::TooManyMarkets
+		);
+	})
+}
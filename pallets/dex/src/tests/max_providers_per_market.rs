@@ -0,0 +1,135 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	MaxProvidersPerMarket::set(2);
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn max_providers_per_market() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 1);
+
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 2);
+
+		// The cap is reached, so CHARLIE can't become a liquidity provider
+		assert_noop!(
+			crate::Pallet::<Test>::deposit_liquidity(Origin::signed(CHARLIE), market, 10_000, 0),
+			Error::<Test>::TooManyProviders
+		);
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 2);
+	})
+}
+
+#[test]
+fn max_providers_per_market_existing_providers_top_up() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			1_000,
+			1_000
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 2);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, BOB), (20_000, 0));
+	})
+}
+
+#[test]
+fn max_providers_per_market_reset_on_close() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+
+		assert_ok!(crate::Pallet::<Test>::force_close_market(Origin::root(), market));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 0);
+	})
+}
+
+#[test]
+fn max_providers_per_market_zero_deposit() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		// An empty deposit can't take the last slot
+		assert_noop!(
+			crate::Pallet::<Test>::deposit_liquidity(Origin::signed(BOB), market, 0, 0),
+			Error::<Test>::ZeroDeposit
+		);
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 1);
+	})
+}
+
+#[test]
+fn max_providers_per_market_slot_freed_on_withdrawal() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 2);
+
+		// Withdrawing everything removes the provision of BOB
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0,
+			false
+		));
+		assert!(!crate::LiqProvisionPool::<Test>::contains_key(market, BOB));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 1);
+
+		// So CHARLIE can take the freed slot
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			0
+		));
+		assert_eq!(crate::Pallet::<Test>::provider_count(market), 2);
+	})
+}
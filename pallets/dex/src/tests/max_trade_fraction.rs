@@ -0,0 +1,56 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn buy_max_trade_fraction() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		MaxTradeFraction::set(Some(Perbill::from_percent(10)));
+
+		// Just over 10% of the QUOTE reserve
+		assert_noop!(
+			crate::Pallet::<Test>::buy(origin.clone(), market, 10_001, None),
+			Error::<Test>::TradeTooLarge
+		);
+		// Exactly 10% of the QUOTE reserve
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+	})
+}
+
+#[test]
+fn sell_max_trade_fraction() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		MaxTradeFraction::set(Some(Perbill::from_percent(10)));
+
+		// Just over 10% of the BASE reserve
+		assert_noop!(
+			crate::Pallet::<Test>::sell(origin.clone(), market, 10_001, false, None),
+			Error::<Test>::TradeTooLarge
+		);
+		// Exactly 10% of the BASE reserve
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 10_000, false, None));
+	})
+}
@@ -0,0 +1,141 @@
+use frame_support::{
+	assert_ok,
+	traits::{Get, Hooks},
+};
+
+use crate::{tests::*, MarketId};
+
+fn collected_quote_fees() -> Balance {
+	crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD))
+		.unwrap()
+		.collected_quote_fees
+}
+
+#[test]
+fn early_payout_above_max_undistributed_fees() {
+	new_test_ext().execute_with(|| {
+		MaxUndistributedFees::set(15);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// 10 QUOTE of fees stay below the cap until the next payout
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		run_to_block(2);
+		assert_eq!(collected_quote_fees(), 10);
+
+		// 20 QUOTE of fees exceed the cap, so they are paid out right away
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let quote_balance = crate::Pallet::<Test>::balance(USD, &ALICE);
+		run_to_block(3);
+		assert_eq!(collected_quote_fees(), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), quote_balance + 20);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+	})
+}
+
+#[test]
+fn no_early_payout_at_max_undistributed_fees() {
+	new_test_ext().execute_with(|| {
+		MaxUndistributedFees::set(20);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Fees equal to the cap don't exceed it
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		run_to_block(2);
+		assert_eq!(collected_quote_fees(), 20);
+
+		// The regular payout still happens
+		run_to_block(PayoutInterval::get());
+		assert_eq!(collected_quote_fees(), 0);
+	})
+}
+
+#[test]
+fn early_payout_bounded_by_max_payout_markets() {
+	new_test_ext().execute_with(|| {
+		MaxUndistributedFees::set(5);
+		MaxPayoutMarkets::set(2);
+
+		let origin = Origin::signed(ALICE);
+		let markets = [MarketId::new(BTC, XMR), MarketId::new(BTC, USD), MarketId::new(XMR, USD)];
+		for market in markets {
+			assert_ok!(crate::Pallet::<Test>::create_market_pool(
+				origin.clone(),
+				market.base,
+				market.quote,
+				100_000,
+				100_000,
+				None
+			));
+			assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		}
+		let due = || crate::EarlyPayoutDue::<Test>::iter_keys().count();
+		assert_eq!(due(), 3);
+
+		// Only two of the markets are paid out, the last one stays due for the next block
+		run_to_block(2);
+		assert_eq!(due(), 1);
+		let paid_out = markets
+			.iter()
+			.filter(|market| {
+				crate::LiquidityPool::<Test>::get(market).unwrap().collected_quote_fees == 0
+			})
+			.count();
+		assert_eq!(paid_out, 2);
+
+		run_to_block(3);
+		assert_eq!(due(), 0);
+		for market in markets {
+			assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_quote_fees, 0);
+		}
+	})
+}
+
+#[test]
+fn early_payout_weight() {
+	new_test_ext().execute_with(|| {
+		MaxUndistributedFees::set(5);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+
+		// The due market is charged with a whole payout
+		assert_eq!(
+			<Dex as Hooks<BlockNumber>>::on_initialize(2),
+			db_weight.reads(2)
+				+ db_weight.reads_writes(1, 1)
+				+ crate::Pallet::<Test>::payout_market_weight()
+		);
+		// Without due markets only the iteration is charged
+		assert_eq!(<Dex as Hooks<BlockNumber>>::on_initialize(3), db_weight.reads(1));
+	})
+}
@@ -0,0 +1,71 @@
+use frame_support::assert_ok;
+use sp_runtime::Perbill;
+
+use crate::{tests::*, types::OrderType, MarketId};
+
+#[test]
+fn min_out_for_slippage() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(
+			crate::Pallet::<Test>::min_out_for_slippage(
+				market,
+				&ALICE,
+				OrderType::Buy,
+				10_000,
+				Perbill::from_percent(1)
+			),
+			None
+		);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Quoted output of 9_082, reduced by 1% is 8_991.18
+		for order_type in [OrderType::Buy, OrderType::Sell] {
+			assert_eq!(
+				crate::Pallet::<Test>::min_out_for_slippage(
+					market,
+					&ALICE,
+					order_type,
+					10_000,
+					Perbill::from_percent(1)
+				),
+				Some(8_991)
+			);
+		}
+
+		// Without slippage it is the quoted output, with a fee of 10
+		let quoted = (10_000 - 10) * 100_000 / (100_000 + 10_000 - 10);
+		assert_eq!(quoted, 9_082);
+		assert_eq!(
+			crate::Pallet::<Test>::min_out_for_slippage(
+				market,
+				&ALICE,
+				OrderType::Buy,
+				10_000,
+				Perbill::zero()
+			),
+			Some(quoted)
+		);
+
+		// An exempt trader is quoted without the fee
+		assert_ok!(crate::Pallet::<Test>::set_fee_exempt(Origin::root(), BOB, true));
+		assert_eq!(
+			crate::Pallet::<Test>::min_out_for_slippage(
+				market,
+				&BOB,
+				OrderType::Buy,
+				10_000,
+				Perbill::zero()
+			),
+			Some(10_000 * 100_000 / (100_000 + 10_000))
+		);
+	})
+}
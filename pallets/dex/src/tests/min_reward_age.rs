@@ -0,0 +1,88 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn min_reward_age_aged_and_fresh_provider() {
+	new_test_ext().execute_with(|| {
+		MinRewardAge::set(10);
+		let market = MarketId::new(BTC, USD);
+		setup_market();
+
+		// BOB provides half of the BASE asset shortly before the payout
+		System::set_block_number(15);
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			100_000,
+			0
+		));
+		assert_eq!(crate::Pallet::<Test>::provided_since(market, BOB), 15);
+
+		// 10 BTC in fees
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		run_to_block(20);
+
+		// Only ALICE has provided liquidity for long enough
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (10, 0));
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, BOB), (0, 0));
+
+		// Once aged, BOB shares in the fees as well
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		run_to_block(30);
+
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (15, 0));
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, BOB), (5, 0));
+	})
+}
+
+#[test]
+fn min_reward_age_only_fresh_providers() {
+	new_test_ext().execute_with(|| {
+		MinRewardAge::set(10);
+		let market = MarketId::new(BTC, USD);
+		System::set_block_number(5);
+		setup_market();
+
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		run_to_block(10);
+
+		// Nobody qualifies yet, so the fees are kept for a later payout
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 10);
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (0, 0));
+
+		run_to_block(20);
+
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 0);
+		assert_eq!(crate::Pallet::<Test>::lifetime_fees(market, ALICE), (10, 0));
+	})
+}
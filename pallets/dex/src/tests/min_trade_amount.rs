@@ -0,0 +1,78 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn buy_min_trade_amount() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		MinTradeAmount::set(5_000);
+
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), MarketId::new(BTC, USD), 4_999, None)
+				.map_err(|e| e.error),
+			Error::<Test>::TradeTooSmall
+		);
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			5_000,
+			None
+		));
+	})
+}
+
+#[test]
+fn sell_min_trade_amount() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		MinTradeAmount::set(5_000);
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				4_999,
+				false,
+				None
+			)
+			.map_err(|e| e.error),
+			Error::<Test>::TradeTooSmall
+		);
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			5_000,
+			false,
+			None
+		));
+	})
+}
+
+#[test]
+fn no_taker_fee() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		TakerFee::set((0, 1_000));
+
+		// Without a taker fee there is nothing to be rounded away
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10,
+			false,
+			None
+		));
+	})
+}
@@ -1,13 +1,16 @@
 use frame_support::{
 	parameter_types,
-	traits::{ConstU128, ConstU16, ConstU32, ConstU64},
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		ConstU128, ConstU16, ConstU32, ConstU64, EnsureOrigin, Hooks,
+	},
 	PalletId,
 };
-use frame_system::EnsureRoot;
+use frame_system::{EnsureRoot, EnsureSigned};
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Verify},
-	AccountId32, BuildStorage, MultiSignature,
+	AccountId32, BuildStorage, DispatchError, MultiSignature, Perbill,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -26,6 +29,8 @@ pub const ALICE: AccountId = AccountId32::new([0; 32]);
 pub const BOB: AccountId = AccountId32::new([1; 32]);
 pub const CHARLIE: AccountId = AccountId32::new([2; 32]);
 pub const EMPTY_ACCOUNT: AccountId = AccountId32::new([3; 32]);
+pub const TREASURY: AccountId = AccountId32::new([4; 32]);
+pub const TRANSFER_FEE_COLLECTOR: AccountId = AccountId32::new([5; 32]);
 pub const DEX_PALLET_ACCOUNT: AccountId = AccountId32::new([
 	109, 111, 100, 108, 100, 101, 120, 112, 97, 108, 108, 101, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 	0, 0, 0, 0, 0, 0, 0, 0,
@@ -53,7 +58,7 @@ impl frame_system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = frame_support::weights::constants::RocksDbWeight;
 	type Origin = Origin;
 	type Call = Call;
 	type Index = Index;
@@ -118,16 +123,197 @@ impl pallet_assets::Config for Test {
 
 parameter_types! {
 	// 10 Basis points taker fee, which is lower vs uniswap but may attract more taker flow
-	pub TakerFee: (u32, u32) = (1, 1_000);
+	pub static TakerFee: (u32, u32) = (1, 1_000);
+	// The taker fee is not capped unless a test opts in
+	pub static MaxAbsoluteFee: Balance = Balance::MAX;
+	// Every trader pays the TakerFee unless a test opts in
+	pub static FeeTiers: Vec<(Balance, u32)> = Vec::new();
+	// New markets charge the taker fee right away unless a test opts in
+	pub static FeeGracePeriod: BlockNumber = 0;
 	// Only 8 bytes available, so t is missing at the end
 	pub DexPalletId: PalletId = PalletId(*b"dexpalle");
+	// No cap on the trade size unless a test opts in
+	pub static MaxTradeFraction: Option<Perbill> = None;
+	// No cap on the price impact unless a test opts in
+	pub static GlobalMaxSlippage: Perbill = Perbill::one();
+	// Bare buys and sells are not capped either unless a test opts in
+	pub static DefaultMaxSlippage: Perbill = Perbill::one();
+	// Only the taker fee rounding limits the trade size unless a test opts in
+	pub static MinTradeAmount: Balance = 0;
+	// Withdrawals may leave reserves of any size unless a test opts in
+	pub static DustThreshold: Balance = 0;
+	pub static EmitTradeEvents: bool = true;
+	// Selling collects the fee in BASE unless a test opts in
+	pub static SellFeeInQuote: bool = false;
+	pub static MaxMarketsPerAsset: u32 = 4;
+	// No protocol fee unless a test opts in
+	pub static ProtocolFeeShare: Perbill = Perbill::zero();
+	pub const TreasuryAccount: AccountId = TREASURY;
+	pub static PayoutInterval: BlockNumber = 10;
+	// Fees wait for the PayoutInterval unless a test opts in
+	pub static MaxUndistributedFees: Balance = Balance::MAX;
+	pub static WithdrawalDelay: BlockNumber = 5;
+	pub static BlocksPerYear: BlockNumber = 10_000;
+	pub const NameLimit: u32 = 16;
+	pub const MaxBatchSize: u32 = 3;
+	// Every pair may be created unless a test restricts them
+	pub static RestrictPairs: bool = false;
+	pub static MaxForceCloseProviders: u32 = 16;
+	// Every account may provide liquidity unless a test opts in
+	pub static MaxProvidersPerMarket: u32 = 16;
+	// Every market is paid out at once unless a test opts in
+	pub static MaxPayoutMarkets: u32 = 16;
+	// Liquidity shares in the fees right away unless a test opts in
+	pub static MinRewardAge: BlockNumber = 0;
+	// The liquidity providers receive all collected fees unless a test opts in
+	pub static CreatorRewardShare: Perbill = Perbill::zero();
+	pub static ProviderRewardShare: Perbill = Perbill::one();
+	// Leftover fees stay in the fee account unless a test opts in
+	pub static FeeSweepThreshold: Option<Balance> = None;
+	// Trades may leave any reserve ratio unless a test opts in
+	pub static MaxReserveRatio: Option<u128> = None;
+	// No asset charges a fee on transfer unless a test opts in
+	pub static TransferFeeAsset: Option<AssetId> = None;
+	pub static TransferFee: Perbill = Perbill::zero();
+	// Any signed account can create a market unless a test restricts it
+	pub static PoolCreators: Option<Vec<AccountId>> = None;
+	// Every asset moved through the Currencies, in order
+	pub static TransferredAssets: Vec<AssetId> = Vec::new();
+}
+
+/// Behaves like EnsureSigned, but only accepts the PoolCreators if they are set
+pub struct EnsurePoolCreator;
+
+impl EnsureOrigin<Origin> for EnsurePoolCreator {
+	type Success = AccountId;
+
+	fn try_origin(o: Origin) -> Result<AccountId, Origin> {
+		EnsureSigned::<AccountId>::try_origin(o).and_then(|who| match PoolCreators::get() {
+			Some(creators) if !creators.contains(&who) => Err(Origin::signed(who)),
+			_ => Ok(who),
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> Origin {
+		Origin::signed(ALICE)
+	}
+}
+
+/// Wraps the assets pallet, but transfers of TransferFeeAsset deliver TransferFee less
+/// to the receiver, like fee-on-transfer tokens do
+pub struct FeeOnTransferAssets;
+
+impl fungibles::Inspect<AccountId> for FeeOnTransferAssets {
+	type AssetId = AssetId;
+	type Balance = Balance;
+
+	fn total_issuance(asset: AssetId) -> Balance {
+		<Assets as fungibles::Inspect<AccountId>>::total_issuance(asset)
+	}
+
+	fn minimum_balance(asset: AssetId) -> Balance {
+		<Assets as fungibles::Inspect<AccountId>>::minimum_balance(asset)
+	}
+
+	fn balance(asset: AssetId, who: &AccountId) -> Balance {
+		<Assets as fungibles::Inspect<AccountId>>::balance(asset, who)
+	}
+
+	fn reducible_balance(asset: AssetId, who: &AccountId, keep_alive: bool) -> Balance {
+		<Assets as fungibles::Inspect<AccountId>>::reducible_balance(asset, who, keep_alive)
+	}
+
+	fn can_deposit(
+		asset: AssetId,
+		who: &AccountId,
+		amount: Balance,
+		mint: bool,
+	) -> DepositConsequence {
+		<Assets as fungibles::Inspect<AccountId>>::can_deposit(asset, who, amount, mint)
+	}
+
+	fn can_withdraw(
+		asset: AssetId,
+		who: &AccountId,
+		amount: Balance,
+	) -> WithdrawConsequence<Balance> {
+		<Assets as fungibles::Inspect<AccountId>>::can_withdraw(asset, who, amount)
+	}
+}
+
+impl fungibles::Transfer<AccountId> for FeeOnTransferAssets {
+	fn transfer(
+		asset: AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+		amount: Balance,
+		keep_alive: bool,
+	) -> Result<Balance, DispatchError> {
+		let mut transferred = TransferredAssets::get();
+		transferred.push(asset);
+		TransferredAssets::set(transferred);
+
+		let fee = match TransferFeeAsset::get() {
+			Some(fee_asset) if fee_asset == asset => TransferFee::get().mul_floor(amount),
+			_ => 0,
+		};
+		if fee > 0 {
+			<Assets as fungibles::Transfer<AccountId>>::transfer(
+				asset,
+				source,
+				&TRANSFER_FEE_COLLECTOR,
+				fee,
+				keep_alive,
+			)?;
+		}
+		<Assets as fungibles::Transfer<AccountId>>::transfer(
+			asset,
+			source,
+			dest,
+			amount - fee,
+			keep_alive,
+		)?;
+
+		Ok(amount)
+	}
 }
 
 impl crate::Config for Test {
 	type Event = Event;
 	type TakerFee = TakerFee;
+	type MaxAbsoluteFee = MaxAbsoluteFee;
+	type FeeTiers = FeeTiers;
+	type FeeGracePeriod = FeeGracePeriod;
 	type PalletId = DexPalletId;
-	type Currencies = Assets;
+	type Currencies = FeeOnTransferAssets;
+	type MaxTradeFraction = MaxTradeFraction;
+	type GlobalMaxSlippage = GlobalMaxSlippage;
+	type DefaultMaxSlippage = DefaultMaxSlippage;
+	type MinTradeAmount = MinTradeAmount;
+	type DustThreshold = DustThreshold;
+	type EmitTradeEvents = EmitTradeEvents;
+	type SellFeeInQuote = SellFeeInQuote;
+	type MaxMarketsPerAsset = MaxMarketsPerAsset;
+	type ProtocolFeeShare = ProtocolFeeShare;
+	type TreasuryAccount = TreasuryAccount;
+	type PayoutInterval = PayoutInterval;
+	type MaxUndistributedFees = MaxUndistributedFees;
+	type WithdrawalDelay = WithdrawalDelay;
+	type BlocksPerYear = BlocksPerYear;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type CreatePoolOrigin = EnsurePoolCreator;
+	type NameLimit = NameLimit;
+	type MaxBatchSize = MaxBatchSize;
+	type RestrictPairs = RestrictPairs;
+	type MaxForceCloseProviders = MaxForceCloseProviders;
+	type MaxProvidersPerMarket = MaxProvidersPerMarket;
+	type MaxPayoutMarkets = MaxPayoutMarkets;
+	type MinRewardAge = MinRewardAge;
+	type CreatorRewardShare = CreatorRewardShare;
+	type ProviderRewardShare = ProviderRewardShare;
+	type FeeSweepThreshold = FeeSweepThreshold;
+	type MaxReserveRatio = MaxReserveRatio;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -163,3 +349,33 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 
 	ext
 }
+
+// Advances the block number up to and including n,
+// running on_initialize of every block
+pub fn run_to_block(n: BlockNumber) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		<Dex as Hooks<BlockNumber>>::on_initialize(System::block_number());
+	}
+}
+
+// The information of a BTC/USD market with the given reserves,
+// which is not stored, so quotes can be computed against any reserves
+pub fn market_with_reserves(
+	base_balance: Balance,
+	quote_balance: Balance,
+) -> (crate::MarketId<AssetId>, crate::MarketInfo<Test>) {
+	let market_info = crate::MarketInfo {
+		base_balance,
+		quote_balance,
+		collected_base_fees: 0,
+		collected_quote_fees: 0,
+		fee_asset: crate::types::FeeAsset::Input,
+		flags: Default::default(),
+		created_at: 0,
+		creator: ALICE,
+		active: true,
+	};
+
+	(crate::MarketId::new(BTC, USD), market_info)
+}
@@ -1,11 +1,80 @@
+mod allowed_pairs;
+mod amount_in_for_exact_out;
+mod amount_to_reach_price;
+mod arbitrage_spread;
+mod asset_frozen;
 mod buy;
+mod create_and_swap;
 mod create_pool;
+mod creator_reward;
+mod current_prices;
+mod default_max_slippage;
 mod deposit_liqudity;
+mod dust_threshold;
+mod emit_trade_events;
+mod estimated_apr;
+mod event_topics;
+mod fee_accrual;
+mod fee_asset;
+mod fee_distribution_per_asset;
+mod fee_exempt;
 mod fee_from_amount;
+mod fee_grace_period;
+mod fee_on_transfer;
+mod fee_sweep;
+mod fee_tiers;
+mod force_close_market;
 mod get_received_amount;
+mod global_max_slippage;
+mod impermanent_loss;
+mod invariants;
+mod lifetime_fees;
+mod limit_price;
+mod market_balance;
+mod market_flags;
+mod market_id;
+mod market_info;
+mod market_metadata;
+mod market_origin;
+mod markets_for_asset;
+mod max_providers_per_market;
+mod max_trade_fraction;
+mod max_undistributed_fees;
+mod min_out_for_slippage;
+mod min_reward_age;
+mod min_trade_amount;
 mod mock;
+mod oracle_price;
+mod payout_conservation;
+mod payout_failed;
+mod payout_hook;
+mod payout_interval;
+mod payout_round_robin;
+mod payout_short_circuit;
+mod pending_payout_total;
+mod pool_accounts;
+mod pool_health;
+mod positions_of;
+mod price_after_deposit;
+mod proportional;
+mod protocol_fee;
+mod providers;
+mod register_market;
+mod reinvest_fees;
+mod remove_market;
+mod reserve_mismatch;
+mod reserve_ratio;
+mod safe_transfer;
 mod sell;
+mod sole_provider_payout;
+mod storage_iteration;
+mod swap;
+mod swap_and_send;
+mod swap_batch;
+mod two_phase_withdrawal;
+mod weight_refund;
 mod withdraw_liquidity;
+mod zero_received;
 
 pub use mock::*;
 
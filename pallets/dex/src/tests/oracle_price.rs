@@ -0,0 +1,98 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, types::ExpectedPrice, Error, MarketId};
+
+fn create_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+fn expected_price(
+	numerator: Balance,
+	denominator: Balance,
+	tolerance: Perbill,
+) -> ExpectedPrice<Balance> {
+	ExpectedPrice { numerator, denominator, tolerance }
+}
+
+#[test]
+fn buy_within_oracle_tolerance() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// 10_000 QUOTE for 9_082 BASE executes at about 1.101, 0.1% off the oracle price
+		let oracle_price = expected_price(11, 10, Perbill::from_percent(1));
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			market,
+			10_000,
+			Some(oracle_price)
+		));
+		System::assert_last_event(Event::Dex(crate::Event::Bought(ALICE, market, 10_000, 9_082)));
+	})
+}
+
+#[test]
+fn buy_outside_oracle_tolerance() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// The execution price of about 1.101 is 10.1% above the oracle price
+		let oracle_price = expected_price(1, 1, Perbill::from_percent(5));
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, Some(oracle_price))
+				.map_err(|e| e.error),
+			Error::<Test>::OracleDeviationExceeded
+		);
+	})
+}
+
+#[test]
+fn sell_within_oracle_tolerance() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// 10_000 BASE for 9_082 QUOTE executes at about 0.908, 0.9% off the oracle price
+		let oracle_price = expected_price(9, 10, Perbill::from_percent(1));
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			Some(oracle_price)
+		));
+		System::assert_last_event(Event::Dex(crate::Event::Sold(CHARLIE, market, 10_000, 9_082)));
+	})
+}
+
+#[test]
+fn sell_outside_oracle_tolerance() {
+	new_test_ext().execute_with(|| {
+		create_market();
+		let market = MarketId::new(BTC, USD);
+
+		// The execution price of about 0.908 is 9.2% below the oracle price
+		let oracle_price = expected_price(1, 1, Perbill::from_percent(5));
+		assert_noop!(
+			crate::Pallet::<Test>::sell(
+				Origin::signed(CHARLIE),
+				market,
+				10_000,
+				false,
+				Some(oracle_price)
+			)
+			.map_err(|e| e.error),
+			Error::<Test>::OracleDeviationExceeded
+		);
+	})
+}
@@ -0,0 +1,103 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn payout_conserves_collected_fees() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			10_000,
+			10_000,
+			None
+		));
+		// Three uneven BASE provisions of 1/6, 2/6 and 3/6
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			20_000,
+			0
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(CHARLIE),
+			market,
+			30_000,
+			0
+		));
+
+		// Collects 10 BTC as fee, which can't be split evenly
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 10_000, false, None));
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 10);
+
+		let providers = [ALICE, BOB, CHARLIE];
+		let balances_before: Vec<Balance> =
+			providers.iter().map(|who| crate::Pallet::<Test>::balance(BTC, who)).collect();
+
+		run_to_block(PayoutInterval::get());
+
+		let payouts: Vec<Balance> = providers
+			.iter()
+			.zip(balances_before)
+			.map(|(who, before)| crate::Pallet::<Test>::balance(BTC, who) - before)
+			.collect();
+		// Every provider receives at least its rounded down share
+		assert!(payouts[0] >= 1);
+		assert!(payouts[1] >= 3);
+		assert!(payouts[2] >= 5);
+		// Exactly the collected fees are paid out, without any dust left behind
+		assert_eq!(payouts.iter().sum::<Balance>(), 10);
+		assert_eq!(
+			crate::Pallet::<Test>::balance(BTC, &crate::Pallet::<Test>::pool_fee_account()),
+			0
+		);
+		assert_eq!(crate::Pallet::<Test>::failed_payout_count(), 0);
+	})
+}
+
+#[test]
+fn payout_order_is_deterministic() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		// The same BASE provisions as above, deposited in reverse order
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(CHARLIE),
+			BTC,
+			USD,
+			30_000,
+			10_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			20_000,
+			0
+		));
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			10_000,
+			0
+		));
+
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 10_000, false, None));
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 10);
+
+		let providers = [ALICE, BOB, CHARLIE];
+		let balances_before: Vec<Balance> =
+			providers.iter().map(|who| crate::Pallet::<Test>::balance(BTC, who)).collect();
+
+		run_to_block(PayoutInterval::get());
+
+		let payouts: Vec<Balance> = providers
+			.iter()
+			.zip(balances_before)
+			.map(|(who, before)| crate::Pallet::<Test>::balance(BTC, who) - before)
+			.collect();
+		// The remainder always goes to the provider with the highest account id
+		assert_eq!(payouts, vec![1, 3, 6]);
+	})
+}
@@ -0,0 +1,41 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, types::FeeAsset, Error, MarketId};
+
+#[test]
+fn payout_failed() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::set_fee_asset(Origin::root(), market, FeeAsset::Base));
+		// Collects 9 BASE as fee
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+
+		// The fee account can not pay out anything
+		assert_ok!(Assets::freeze(
+			Origin::signed(DEX_PALLET_ACCOUNT),
+			BTC,
+			crate::Pallet::<Test>::pool_fee_account()
+		));
+		run_to_block(PayoutInterval::get());
+
+		System::assert_has_event(Event::Dex(crate::Event::PayoutFailed(
+			ALICE,
+			market,
+			Error::<Test>::Transfer.into(),
+		)));
+		assert_eq!(crate::Pallet::<Test>::failed_payout_count(), 1);
+		assert_eq!(
+			crate::Pallet::<Test>::balance(BTC, &crate::Pallet::<Test>::pool_fee_account()),
+			9
+		);
+	})
+}
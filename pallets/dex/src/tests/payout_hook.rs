@@ -0,0 +1,60 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn payout_hook() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			10_000,
+			10_000,
+			None
+		));
+		// BOB provides 3/4 of the BASE and none of the QUOTE liquidity
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			30_000,
+			0
+		));
+
+		// Accrue 20 BTC and 10 USD of fees
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 20_000, false, None));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_base_fees, 20);
+		assert_eq!(market_info.collected_quote_fees, 10);
+
+		let balances = || {
+			[ALICE, BOB].map(|who| {
+				(
+					crate::Pallet::<Test>::balance(BTC, &who),
+					crate::Pallet::<Test>::balance(USD, &who),
+				)
+			})
+		};
+		let before = balances();
+
+		// Nothing is paid out before the end of the interval
+		run_to_block(PayoutInterval::get() - 1);
+		assert_eq!(balances(), before);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 20);
+
+		run_to_block(PayoutInterval::get());
+		let after = balances();
+		// ALICE
+		assert_eq!(after[0].0 - before[0].0, 5);
+		assert_eq!(after[0].1 - before[0].1, 10);
+		// BOB
+		assert_eq!(after[1].0 - before[1].0, 15);
+		assert_eq!(after[1].1 - before[1].1, 0);
+
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.collected_base_fees, 0);
+		assert_eq!(market_info.collected_quote_fees, 0);
+	})
+}
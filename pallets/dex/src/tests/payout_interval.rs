@@ -0,0 +1,54 @@
+use frame_support::{assert_ok, traits::Hooks};
+
+use crate::{tests::*, MarketId};
+
+fn collected_quote_fees() -> Balance {
+	crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD))
+		.unwrap()
+		.collected_quote_fees
+}
+
+#[test]
+fn payout_interval() {
+	new_test_ext().execute_with(|| {
+		PayoutInterval::set(3);
+
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(
+			origin.clone(),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		assert_eq!(collected_quote_fees(), 10);
+
+		// No payout in between the intervals
+		run_to_block(2);
+		assert_eq!(collected_quote_fees(), 10);
+
+		// The collected fees are reset by the payout
+		run_to_block(3);
+		assert_eq!(collected_quote_fees(), 0);
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin, MarketId::new(BTC, USD), 10_000, None));
+		run_to_block(5);
+		assert_eq!(collected_quote_fees(), 10);
+		run_to_block(6);
+		assert_eq!(collected_quote_fees(), 0);
+	})
+}
+
+#[test]
+#[should_panic(expected = "PayoutInterval must not be zero")]
+fn payout_interval_zero() {
+	PayoutInterval::set(0);
+	<crate::Pallet<Test> as Hooks<BlockNumber>>::integrity_test();
+}
@@ -0,0 +1,123 @@
+use frame_support::{
+	assert_ok,
+	traits::{Get, Hooks},
+};
+
+use crate::{tests::*, MarketId};
+
+fn collected_quote_fees(market: MarketId<AssetId>) -> Balance {
+	crate::LiquidityPool::<Test>::get(market).unwrap().collected_quote_fees
+}
+
+fn create_markets(origin: &Origin) -> [MarketId<AssetId>; 3] {
+	let markets = [MarketId::new(BTC, XMR), MarketId::new(BTC, USD), MarketId::new(XMR, USD)];
+	for market in markets {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			market.base,
+			market.quote,
+			100_000,
+			100_000,
+			None
+		));
+	}
+	markets
+}
+
+#[test]
+fn payout_round_robin() {
+	new_test_ext().execute_with(|| {
+		MaxPayoutMarkets::set(2);
+
+		let origin = Origin::signed(ALICE);
+		let markets = create_markets(&origin);
+		for market in markets {
+			assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		}
+
+		// The first payout only gets to two of the markets
+		run_to_block(10);
+		let unpaid: Vec<MarketId<AssetId>> = markets
+			.into_iter()
+			.filter(|market| collected_quote_fees(*market) == 10)
+			.collect();
+		assert_eq!(unpaid.len(), 1);
+		let last = crate::Pallet::<Test>::last_paid_out_market().unwrap();
+		assert_eq!(collected_quote_fees(last), 0);
+
+		// The next payout starts with the market left over
+		for market in markets {
+			assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		}
+		run_to_block(20);
+		assert_eq!(collected_quote_fees(unpaid[0]), 0);
+		assert_eq!(markets.map(collected_quote_fees).iter().filter(|fees| **fees == 0).count(), 2);
+	})
+}
+
+#[test]
+fn payout_round_robin_every_market() {
+	new_test_ext().execute_with(|| {
+		MaxPayoutMarkets::set(1);
+
+		let markets = create_markets(&Origin::signed(ALICE));
+
+		// Every market is processed once within as many payouts as there are markets
+		let mut paid_out = Vec::new();
+		for block in [10, 20, 30] {
+			run_to_block(block);
+			paid_out.push(crate::Pallet::<Test>::last_paid_out_market().unwrap());
+		}
+		let mut expected = markets.to_vec();
+		expected.sort();
+		let mut sorted = paid_out.clone();
+		sorted.sort();
+		assert_eq!(sorted, expected);
+
+		// And the payout after them starts over
+		run_to_block(40);
+		assert_eq!(crate::Pallet::<Test>::last_paid_out_market(), Some(paid_out[0]));
+	})
+}
+
+#[test]
+fn payout_round_robin_continues_after_error() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let markets = create_markets(&origin);
+		for market in markets {
+			assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		}
+		// The total provision of the first market overflows, so its payout fails
+		crate::LiqProvisionPool::<Test>::insert(markets[0], BOB, (Balance::MAX, Balance::MAX));
+
+		// The other markets are still paid out
+		run_to_block(10);
+		assert_eq!(markets.map(collected_quote_fees), [10, 0, 0]);
+	})
+}
+
+#[test]
+fn payout_weight() {
+	new_test_ext().execute_with(|| {
+		MaxPayoutMarkets::set(2);
+		create_markets(&Origin::signed(ALICE));
+
+		// Three markets trading three assets are checked, then two of them are paid out
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let payout_weight =
+			db_weight.reads_writes(2 + 3, 1) + 2 * crate::Pallet::<Test>::payout_market_weight();
+		assert_eq!(
+			<Dex as Hooks<BlockNumber>>::on_initialize(10),
+			db_weight.reads(3 + 3) + payout_weight
+		);
+		assert_eq!(<Dex as Hooks<BlockNumber>>::on_initialize(11), 0);
+	})
+}
+
+#[test]
+#[should_panic(expected = "A payout of MaxPayoutMarkets markets must fit into a block")]
+fn payout_exceeding_block_weight() {
+	MaxPayoutMarkets::set(u32::MAX);
+	<crate::Pallet<Test> as Hooks<BlockNumber>>::integrity_test();
+}
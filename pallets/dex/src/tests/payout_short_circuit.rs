@@ -0,0 +1,51 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn payout_skips_asset_without_fees() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// Only collects QUOTE fees, as the fee is charged on the input
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+		let info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(info.collected_base_fees, 0);
+		assert_eq!(info.collected_quote_fees, 10);
+
+		TransferredAssets::set(Vec::new());
+		run_to_block(PayoutInterval::get());
+
+		// No BASE transfer has been attempted
+		assert!(!TransferredAssets::get().contains(&BTC));
+		let info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(info.collected_quote_fees, 0);
+	})
+}
+
+#[test]
+fn payout_without_any_fees() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		TransferredAssets::set(Vec::new());
+		run_to_block(PayoutInterval::get());
+
+		assert!(TransferredAssets::get().is_empty());
+	})
+}
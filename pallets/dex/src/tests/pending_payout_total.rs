@@ -0,0 +1,41 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn pending_payout_total_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(crate::Pallet::<Test>::pending_payout_total(MarketId::new(BTC, USD)), None);
+	})
+}
+
+#[test]
+fn pending_payout_total() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::pending_payout_total(market), Some((0, 0)));
+
+		// Collects 10 BTC and 10 USD as fees
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_eq!(crate::Pallet::<Test>::pending_payout_total(market), Some((10, 10)));
+
+		// Nothing is pending once the fees have been paid out
+		run_to_block(PayoutInterval::get());
+		assert_eq!(crate::Pallet::<Test>::pending_payout_total(market), Some((0, 0)));
+	})
+}
@@ -0,0 +1,13 @@
+use sp_runtime::traits::AccountIdConversion;
+
+use crate::tests::*;
+
+#[test]
+fn pool_accounts() {
+	new_test_ext().execute_with(|| {
+		let fee_account: AccountId =
+			DexPalletId::get().try_into_sub_account(b"fee-account").unwrap();
+
+		assert_eq!(crate::Pallet::<Test>::pool_accounts(), (DEX_PALLET_ACCOUNT, fee_account));
+	})
+}
@@ -0,0 +1,80 @@
+use frame_support::{assert_ok, traits::tokens::fungibles::Transfer};
+
+use crate::{tests::*, types::PoolHealth, MarketId};
+
+#[test]
+fn pool_health_no_market() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(crate::Pallet::<Test>::pool_health(MarketId::new(BTC, USD)), None);
+	})
+}
+
+#[test]
+fn pool_health_healthy() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			XMR,
+			USD,
+			100,
+			100,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+
+		assert_eq!(
+			crate::Pallet::<Test>::pool_health(market).unwrap(),
+			PoolHealth {
+				reserves_non_zero: true,
+				reserves_match_balances: true,
+				base_surplus: 0,
+				quote_surplus: 0,
+			}
+		);
+	})
+}
+
+#[test]
+fn pool_health_corrupted() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, BTC, USD, 100_000, 100_000, None
+		));
+
+		// Tokens sent to the pool account directly are not accounted for by the market
+		let pool_account = crate::Pallet::<Test>::pool_account();
+		assert_ok!(<Assets as Transfer<AccountId>>::transfer(
+			USD,
+			&ALICE,
+			&pool_account,
+			500,
+			true
+		));
+		// And the recorded BASE reserve is wiped
+		crate::LiquidityPool::<Test>::mutate(market, |opt_market_info| {
+			opt_market_info.as_mut().unwrap().base_balance = 0;
+		});
+
+		assert_eq!(
+			crate::Pallet::<Test>::pool_health(market).unwrap(),
+			PoolHealth {
+				reserves_non_zero: false,
+				reserves_match_balances: false,
+				base_surplus: 100_000,
+				quote_surplus: 500,
+			}
+		);
+	})
+}
@@ -0,0 +1,38 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn positions_of_no_positions() {
+	new_test_ext().execute_with(|| {
+		assert!(crate::Pallet::<Test>::positions_of(&ALICE).is_empty());
+	})
+}
+
+#[test]
+fn positions_of() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin, XMR, USD, 50_000, 20_000, None
+		));
+
+		let mut positions = crate::Pallet::<Test>::positions_of(&ALICE);
+		positions.sort();
+		assert_eq!(
+			positions,
+			vec![
+				(MarketId::new(BTC, USD), (100_000, 100_000)),
+				(MarketId::new(XMR, USD), (50_000, 20_000))
+			]
+		);
+	})
+}
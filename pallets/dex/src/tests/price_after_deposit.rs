@@ -0,0 +1,57 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId, PRICE_DENOMINATOR};
+
+#[test]
+fn price_after_deposit() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::price_after_deposit(market, 1_000, 1_000), None);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			200_000,
+			None
+		));
+		let price = crate::Pallet::<Test>::current_price(market);
+		assert_eq!(price, Some((2 * PRICE_DENOMINATOR, PRICE_DENOMINATOR)));
+
+		// A balanced deposit leaves the price unchanged
+		assert_eq!(crate::Pallet::<Test>::price_after_deposit(market, 50_000, 100_000), price);
+		assert_eq!(crate::Pallet::<Test>::price_after_deposit(market, 0, 0), price);
+	})
+}
+
+#[test]
+fn price_after_deposit_imbalanced() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			200_000,
+			None
+		));
+
+		// Only QUOTE raises the price, only BASE lowers it
+		assert_eq!(
+			crate::Pallet::<Test>::price_after_deposit(market, 0, 200_000),
+			Some((4 * PRICE_DENOMINATOR, PRICE_DENOMINATOR))
+		);
+		assert_eq!(
+			crate::Pallet::<Test>::price_after_deposit(market, 100_000, 0),
+			Some((PRICE_DENOMINATOR, PRICE_DENOMINATOR))
+		);
+
+		// Nothing is deposited by the query
+		assert_eq!(
+			crate::Pallet::<Test>::current_price(market),
+			Some((2 * PRICE_DENOMINATOR, PRICE_DENOMINATOR))
+		);
+	})
+}
@@ -0,0 +1,40 @@
+use sp_runtime::DispatchError;
+
+use crate::{tests::*, Error};
+
+fn proportional(part: Balance, whole: Balance, of: Balance) -> Result<Balance, DispatchError> {
+	crate::Pallet::<Test>::proportional(part, whole, of).map_err(Into::into)
+}
+
+#[test]
+fn proportional_share() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(proportional(1, 4, 100), Ok(25));
+		assert_eq!(proportional(4, 4, 100), Ok(100));
+		// Rounded down
+		assert_eq!(proportional(1, 3, 100), Ok(33));
+		// No part of nothing
+		assert_eq!(proportional(0, 0, 100), Ok(0));
+	})
+}
+
+#[test]
+fn proportional_small_share() {
+	new_test_ext().execute_with(|| {
+		// Dividing the part by the whole first would truncate these to zero
+		assert_eq!(proportional(1, 3, 300), Ok(100));
+		assert_eq!(proportional(1, 1_000_000_000_000, 10u128.pow(15)), Ok(1_000));
+		assert_eq!(proportional(1, u128::MAX, u128::MAX), Ok(1));
+	})
+}
+
+#[test]
+fn proportional_overflow() {
+	new_test_ext().execute_with(|| {
+		// The product exceeds 128 bits, but the share does not
+		assert_eq!(proportional(u128::MAX - 1, u128::MAX, u128::MAX), Ok(u128::MAX - 1));
+		// The share itself exceeds the balance type
+		assert_eq!(proportional(2, 1, u128::MAX), Err(Error::<Test>::Arithmetic.into()));
+		assert_eq!(proportional(1, 0, 100), Err(Error::<Test>::Arithmetic.into()));
+	})
+}
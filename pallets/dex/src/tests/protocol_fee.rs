@@ -0,0 +1,130 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{DispatchError, Perbill};
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn protocol_fee_buy() {
+	new_test_ext().execute_with(|| {
+		ProtocolFeeShare::set(Perbill::from_percent(20));
+
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// 10 QUOTE taker fee of which 20% are kept for the treasury
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+
+		// The protocol fee stays in the fee account until it is collected
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &TREASURY), 0);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 2));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 10);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_quote_fees, 8);
+	})
+}
+
+#[test]
+fn protocol_fee_sell() {
+	new_test_ext().execute_with(|| {
+		ProtocolFeeShare::set(Perbill::from_percent(20));
+
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// 10 BASE taker fee of which 20% are kept for the treasury
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 10_000, false, None));
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &TREASURY), 0);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (2, 0));
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 10);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_base_fees, 8);
+	})
+}
+
+#[test]
+fn protocol_fee_disabled() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+
+		// The treasury receives nothing without a protocol fee share
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &TREASURY), 0);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 0));
+	})
+}
+
+#[test]
+fn collect_protocol_fees() {
+	new_test_ext().execute_with(|| {
+		ProtocolFeeShare::set(Perbill::from_percent(20));
+
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// The protocol fees accumulate over multiple trades
+		assert_ok!(crate::Pallet::<Test>::buy(origin.clone(), market, 10_000, None));
+		assert_ok!(crate::Pallet::<Test>::buy(origin, market, 10_000, None));
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 4));
+
+		assert_ok!(crate::Pallet::<Test>::collect_protocol_fees(Origin::root(), market));
+
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &TREASURY), 4);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 16);
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().collected_quote_fees, 16);
+		assert_eq!(crate::Pallet::<Test>::protocol_fees(market), (0, 0));
+		System::assert_last_event(Event::Dex(crate::Event::ProtocolFeesCollected(market, 0, 4)));
+
+		// Collecting again transfers nothing
+		assert_ok!(crate::Pallet::<Test>::collect_protocol_fees(Origin::root(), market));
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &TREASURY), 4);
+	})
+}
+
+#[test]
+fn collect_protocol_fees_not_admin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			crate::Pallet::<Test>::collect_protocol_fees(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD)
+			),
+			DispatchError::BadOrigin
+		);
+	})
+}
@@ -0,0 +1,59 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+		Origin::signed(BOB),
+		MarketId::new(BTC, USD),
+		50_000,
+		0
+	));
+}
+
+#[test]
+fn providers_no_market() {
+	new_test_ext().execute_with(|| {
+		assert!(crate::Pallet::<Test>::providers(MarketId::new(BTC, USD)).is_empty());
+	})
+}
+
+#[test]
+fn providers() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		let mut providers = crate::Pallet::<Test>::providers(MarketId::new(BTC, USD));
+		providers.sort();
+		assert_eq!(providers, vec![(ALICE, (100_000, 100_000)), (BOB, (50_000, 0))]);
+	})
+}
+
+#[test]
+fn providers_paged() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		let first_page = crate::Pallet::<Test>::providers_paged(market, 0, 1);
+		let second_page = crate::Pallet::<Test>::providers_paged(market, 1, 1);
+		assert_eq!(first_page.len(), 1);
+		assert_eq!(second_page.len(), 1);
+		assert!(crate::Pallet::<Test>::providers_paged(market, 2, 1).is_empty());
+
+		// The pages together are all liquidity providers
+		let mut pages = [first_page, second_page].concat();
+		pages.sort();
+		let mut providers = crate::Pallet::<Test>::providers(market);
+		providers.sort();
+		assert_eq!(pages, providers);
+	})
+}
@@ -0,0 +1,100 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn register_market() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::register_market(Origin::signed(ALICE), BTC, USD));
+		System::assert_last_event(Event::Dex(crate::Event::MarketRegistered(ALICE, market)));
+
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 0);
+		assert_eq!(market_info.quote_balance, 0);
+		assert!(!market_info.active);
+		assert_eq!(crate::Pallet::<Test>::markets_for_asset(BTC), vec![market]);
+		assert_eq!(crate::Pallet::<Test>::market_origin(market), Some((ALICE, 1)));
+
+		// A registered market can't be registered or created again
+		assert_noop!(
+			crate::Pallet::<Test>::register_market(Origin::signed(ALICE), BTC, USD),
+			Error::<Test>::MarketExists
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(ALICE),
+				BTC,
+				USD,
+				1_000,
+				1_000,
+				None
+			),
+			Error::<Test>::MarketExists
+		);
+	})
+}
+
+#[test]
+fn register_market_inactive() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::register_market(Origin::signed(ALICE), BTC, USD));
+
+		assert_noop!(
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 1_000, None)
+				.map_err(|e| e.error),
+			Error::<Test>::MarketInactive
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 1_000, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::MarketInactive
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::swap(Origin::signed(ALICE), USD, BTC, 1_000, 0)
+				.map_err(|e| e.error),
+			Error::<Test>::MarketInactive
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::buy_to(Origin::signed(ALICE), market, 1_000, BOB),
+			Error::<Test>::MarketInactive
+		);
+
+		// Funding a single reserve is not enough to open the market
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			0
+		));
+		assert!(!crate::LiquidityPool::<Test>::get(market).unwrap().active);
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 1_000, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::MarketInactive
+		);
+	})
+}
+
+#[test]
+fn register_market_activated_by_deposit() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::register_market(Origin::signed(ALICE), BTC, USD));
+
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(ALICE),
+			market,
+			100_000,
+			100_000
+		));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert!(market_info.active);
+		assert_eq!(market_info.base_balance, 100_000);
+		assert_eq!(market_info.quote_balance, 100_000);
+
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().base_balance, 90_918);
+	})
+}
@@ -0,0 +1,63 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn reinvest_fees() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// Collects 10 USD as fee
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 10_000, None));
+		let before = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!((before.base_balance, before.quote_balance), (90_918, 109_990));
+		assert_eq!(before.collected_quote_fees, 10);
+
+		assert_ok!(crate::Pallet::<Test>::reinvest_fees(Origin::root(), market));
+		System::assert_last_event(Event::Dex(crate::Event::FeesReinvested(market, 0, 10)));
+
+		let after = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!((after.base_balance, after.quote_balance), (90_918, 110_000));
+		assert_eq!((after.collected_base_fees, after.collected_quote_fees), (0, 0));
+		assert!(
+			after.base_balance * after.quote_balance > before.base_balance * before.quote_balance
+		);
+
+		// The fees moved from the fee account into the pool account
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &DEX_PALLET_ACCOUNT), 110_000);
+	})
+}
+
+#[test]
+fn reinvest_fees_admin_only() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_noop!(
+			crate::Pallet::<Test>::reinvest_fees(Origin::root(), market),
+			Error::<Test>::MarketDoesNotExist
+		);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_noop!(
+			crate::Pallet::<Test>::reinvest_fees(Origin::signed(ALICE), market),
+			DispatchError::BadOrigin
+		);
+	})
+}
@@ -0,0 +1,61 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn remove_market_no_market() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_noop!(
+			crate::Pallet::<Test>::remove_market(origin, MarketId::new(BTC, USD)),
+			Error::<Test>::MarketDoesNotExist
+		);
+	})
+}
+
+#[test]
+fn remove_market_not_sole_provider() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// BOB never provided any liquidity
+		assert_noop!(
+			crate::Pallet::<Test>::remove_market(Origin::signed(BOB), market),
+			Error::<Test>::NotSoleProvider
+		);
+	})
+}
+
+#[test]
+fn remove_market() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::remove_market(origin, market));
+
+		// All storage of the market is gone
+		assert!(crate::LiquidityPool::<Test>::get(market).is_none());
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (0, 0));
+
+		// And ALICE got her liquidity back
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 1_000_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 1_000_000);
+	})
+}
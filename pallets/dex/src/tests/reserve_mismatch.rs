@@ -0,0 +1,78 @@
+use frame_support::{
+	assert_ok,
+	traits::{Get, Hooks},
+};
+
+use crate::{tests::*, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+fn reserve_mismatches() -> Vec<Event> {
+	System::events()
+		.into_iter()
+		.map(|record| record.event)
+		.filter(|event| matches!(event, Event::Dex(crate::Event::ReserveMismatch(..))))
+		.collect()
+}
+
+#[test]
+fn reserve_mismatch() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		// Sending assets to the pool account directly bypasses the recorded reserves
+		assert_ok!(Assets::transfer(Origin::signed(BOB), BTC, DEX_PALLET_ACCOUNT, 100));
+
+		// Only the payout block checks the reserves
+		run_to_block(9);
+		assert_eq!(reserve_mismatches(), vec![]);
+		run_to_block(10);
+
+		assert_eq!(
+			reserve_mismatches(),
+			vec![Event::Dex(crate::Event::ReserveMismatch(MarketId::new(BTC, USD), 100, 0))]
+		);
+		// Only reported, the recorded reserves are left untouched
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap().base_balance,
+			100_000
+		);
+	})
+}
+
+#[test]
+fn reserve_mismatch_none() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+
+		run_to_block(10);
+
+		assert_eq!(reserve_mismatches(), vec![]);
+	})
+}
+
+#[test]
+fn reserve_mismatch_weight() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+
+		// A read of the market and of the pool account balances of BTC and USD
+		assert_eq!(crate::Pallet::<Test>::check_reserves(), db_weight.reads(3));
+		assert_eq!(crate::Pallet::<Test>::on_initialize(11), 0);
+	})
+}
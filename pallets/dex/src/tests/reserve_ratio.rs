@@ -0,0 +1,65 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn create_market(base_amount: Balance, quote_amount: Balance) {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		base_amount,
+		quote_amount,
+		None
+	));
+}
+
+#[test]
+fn is_ratio_sane() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 10), None);
+
+		create_market(100_000, 200_000);
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 2), Some(true));
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 1), Some(false));
+	})
+}
+
+#[test]
+fn is_ratio_sane_extreme() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		// 10^5 BASE for a single unit of QUOTE
+		create_market(100_000, 1);
+
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 1_000), Some(false));
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 100_000), Some(true));
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, u128::MAX), Some(true));
+	})
+}
+
+#[test]
+fn max_reserve_ratio() {
+	new_test_ext().execute_with(|| {
+		MaxReserveRatio::set(Some(10));
+		let market = MarketId::new(BTC, USD);
+		create_market(100_000, 100_000);
+
+		// Would leave roughly 399_700 BASE for 25_019 QUOTE
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(ALICE), market, 300_000, false, None)
+				.map_err(|e| e.error),
+			Error::<Test>::ReserveRatioExceeded
+		);
+
+		// Leaves roughly 199_900 BASE for 50_025 QUOTE
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			market,
+			100_000,
+			false,
+			None
+		));
+		assert_eq!(crate::Pallet::<Test>::is_ratio_sane(market, 10), Some(true));
+	})
+}
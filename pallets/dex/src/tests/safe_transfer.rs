@@ -0,0 +1,76 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+#[test]
+fn create_market_pool_transfer_failure() {
+	new_test_ext().execute_with(|| {
+		// A frozen account can not send the asset
+		assert_ok!(Assets::freeze(Origin::signed(DEX_PALLET_ACCOUNT), BTC, ALICE));
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_market_pool(
+				Origin::signed(ALICE),
+				BTC,
+				USD,
+				100_000,
+				100_000,
+				None
+			),
+			Error::<Test>::Transfer
+		);
+	})
+}
+
+#[test]
+fn sell_transfer_failure() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		assert_ok!(Assets::freeze(Origin::signed(DEX_PALLET_ACCOUNT), BTC, ALICE));
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(origin, MarketId::new(BTC, USD), 10_000, false, None),
+			Error::<Test>::Transfer
+		);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_transfer_failure() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// The pool account itself can not send BTC anymore
+		assert_ok!(Assets::freeze(
+			Origin::signed(DEX_PALLET_ACCOUNT),
+			BTC,
+			crate::Pallet::<Test>::pool_account()
+		));
+
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				origin,
+				MarketId::new(BTC, USD),
+				10_000,
+				10_000,
+				false
+			),
+			Error::<Test>::Transfer
+		);
+	})
+}
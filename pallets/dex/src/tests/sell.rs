@@ -1,14 +1,18 @@
 use frame_support::{assert_noop, assert_ok};
 
-use crate::{tests::*, types::MarketInfo};
+use crate::{
+	tests::*,
+	types::{FeeAsset, MarketFlags, MarketInfo},
+	Error, MarketId,
+};
 
 #[test]
 fn sell_no_pool() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
-		let market = (BTC, USD);
+		let market = MarketId::new(BTC, USD);
 		assert_noop!(
-			crate::Pallet::<Test>::sell(origin, market, 100),
+			crate::Pallet::<Test>::sell(origin, market, 100, false, None).map_err(|e| e.error),
 			crate::Error::<Test>::MarketDoesNotExist
 		);
 	})
@@ -18,11 +22,18 @@ fn sell_no_pool() {
 fn sell_not_enough_balance() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
-		assert_ok!(crate::Pallet::<Test>::create_market_pool(origin.clone(), BTC, XMR, 100, 100));
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin.clone(),
+			BTC,
+			XMR,
+			100,
+			100,
+			None
+		));
 
-		let market = (BTC, XMR);
+		let market = MarketId::new(BTC, XMR);
 		assert_noop!(
-			crate::Pallet::<Test>::sell(origin, market, u128::MAX),
+			crate::Pallet::<Test>::sell(origin, market, u128::MAX, false, None),
 			crate::Error::<Test>::NotEnoughBalance
 		);
 	})
@@ -37,31 +48,37 @@ fn sell() {
 			BTC,
 			USD,
 			100_000,
-			100_000
+			100_000,
+			None
 		));
 
-		let market = (BTC, USD);
-		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 10_000));
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::sell(origin, market, 10_000, false, None));
 
 		assert_eq!(
 			crate::LiquidityPool::<Test>::get(market).unwrap(),
 			MarketInfo {
 				base_balance: 109_990,
-				quote_balance: 90_917,
+				quote_balance: 90_918,
 				collected_base_fees: 10,
 				collected_quote_fees: 0,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: 1,
+				creator: ALICE,
+				active: true,
 			}
 		);
 
 		// Check storage changes. Notice that the liquidity that ALICE has locked is also not here
 		// anymore
 		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 890_000);
-		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 909_083);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 909_082);
 
 		// Check pool_account balances
 		let pool_account = crate::Pallet::<Test>::pool_account();
 		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_account), 109_990);
-		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_account), 90_917);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_account), 90_918);
 
 		// Check pool_fee_account balances
 		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
@@ -69,3 +86,44 @@ fn sell() {
 		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
 	})
 }
+
+#[test]
+fn sell_entire_balance_allow_death() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 1_000_000, true, None));
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), 90_900);
+	})
+}
+
+#[test]
+fn sell_entire_balance_keep_alive() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Paying the taker fee would reap the account of BOB
+		assert_noop!(
+			crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 1_000_000, false, None),
+			Error::<Test>::Transfer
+		);
+	})
+}
@@ -0,0 +1,46 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+#[test]
+fn sole_provider_receives_all_fees() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Shift the reserves away from ALICEs provision in both directions
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			12_345,
+			false,
+			None
+		));
+		assert_ok!(crate::Pallet::<Test>::sell(Origin::signed(BOB), market, 7_777, false, None));
+		assert_ok!(crate::Pallet::<Test>::buy(Origin::signed(CHARLIE), market, 3_333, None));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_ne!(market_info.base_balance, 100_000);
+		assert_ne!(market_info.quote_balance, 100_000);
+		let (base_fees, quote_fees) =
+			(market_info.collected_base_fees, market_info.collected_quote_fees);
+		assert!(base_fees > 0 && quote_fees > 0);
+
+		let alice_btc = crate::Pallet::<Test>::balance(BTC, &ALICE);
+		let alice_usd = crate::Pallet::<Test>::balance(USD, &ALICE);
+
+		run_to_block(PayoutInterval::get());
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), alice_btc + base_fees);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), alice_usd + quote_fees);
+		let pool_fee_account = crate::Pallet::<Test>::pool_fee_account();
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_fee_account), 0);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_fee_account), 0);
+	})
+}
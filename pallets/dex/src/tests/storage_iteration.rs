@@ -0,0 +1,74 @@
+use frame_support::assert_ok;
+
+use crate::{tests::*, MarketId};
+
+fn setup_markets() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		XMR,
+		USD,
+		50_000,
+		20_000,
+		None
+	));
+	assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+		Origin::signed(BOB),
+		MarketId::new(BTC, USD),
+		10_000,
+		0
+	));
+}
+
+#[test]
+fn storage_iteration_decodes_keys() {
+	new_test_ext().execute_with(|| {
+		setup_markets();
+
+		// The keys are decoded from the hashed storage keys
+		let mut markets: Vec<MarketId<AssetId>> =
+			crate::LiquidityPool::<Test>::iter_keys().collect();
+		markets.sort();
+		assert_eq!(markets, vec![MarketId::new(BTC, USD), MarketId::new(XMR, USD)]);
+
+		let mut provisions: Vec<(MarketId<AssetId>, AccountId, (Balance, Balance))> =
+			crate::LiqProvisionPool::<Test>::iter().collect();
+		provisions.sort();
+		assert_eq!(
+			provisions,
+			vec![
+				(MarketId::new(BTC, USD), ALICE, (100_000, 100_000)),
+				(MarketId::new(BTC, USD), BOB, (10_000, 0)),
+				(MarketId::new(XMR, USD), ALICE, (50_000, 20_000)),
+			]
+		);
+	})
+}
+
+#[test]
+fn storage_prefix_iteration() {
+	new_test_ext().execute_with(|| {
+		setup_markets();
+
+		// Only the providers of the given market share its prefix
+		let mut providers: Vec<AccountId> =
+			crate::LiqProvisionPool::<Test>::iter_key_prefix(MarketId::new(BTC, USD)).collect();
+		providers.sort();
+		assert_eq!(providers, vec![ALICE, BOB]);
+
+		let providers: Vec<(AccountId, (Balance, Balance))> =
+			crate::LiqProvisionPool::<Test>::iter_prefix(MarketId::new(XMR, USD)).collect();
+		assert_eq!(providers, vec![(ALICE, (50_000, 20_000))]);
+		assert_eq!(
+			crate::LiqProvisionPool::<Test>::iter_prefix(MarketId::new(BTC, XMR)).count(),
+			0
+		);
+	})
+}
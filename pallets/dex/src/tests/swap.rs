@@ -0,0 +1,96 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+/// The state a trade leaves behind: (reserves, BTC and USD balance of ALICE, last event)
+fn trade_outcome() -> ((u128, u128), u128, u128, Event) {
+	let market_info = crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap();
+	(
+		(market_info.base_balance, market_info.quote_balance),
+		crate::Pallet::<Test>::balance(BTC, &ALICE),
+		crate::Pallet::<Test>::balance(USD, &ALICE),
+		System::events().pop().unwrap().event,
+	)
+}
+
+#[test]
+fn swap_base_for_quote_matches_sell() {
+	let sold = new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			false,
+			None
+		));
+		trade_outcome()
+	});
+	let swapped = new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, USD, 10_000, 0));
+		trade_outcome()
+	});
+
+	assert_eq!(swapped, sold);
+}
+
+#[test]
+fn swap_quote_for_base_matches_buy() {
+	let bought = new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::buy(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			10_000,
+			None
+		));
+		trade_outcome()
+	});
+	let swapped = new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(ALICE), USD, BTC, 10_000, 0));
+		trade_outcome()
+	});
+
+	assert_eq!(swapped, bought);
+}
+
+#[test]
+fn swap_min_out() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// Selling 10_000 BTC yields 9_082 USD
+		assert_noop!(
+			crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, USD, 10_000, 9_083)
+				.map_err(|e| e.error),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, USD, 10_000, 9_082));
+	})
+}
+
+#[test]
+fn swap_no_market() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		assert_noop!(
+			crate::Pallet::<Test>::swap(Origin::signed(ALICE), BTC, XMR, 10_000, 0)
+				.map_err(|e| e.error),
+			Error::<Test>::MarketDoesNotExist
+		);
+	})
+}
@@ -0,0 +1,85 @@
+use frame_support::assert_ok;
+
+use crate::{
+	tests::*,
+	types::{FeeAsset, MarketFlags, MarketInfo},
+	MarketId,
+};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+}
+
+#[test]
+fn buy_to() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::buy_to(Origin::signed(ALICE), market, 10_000, BOB));
+
+		// ALICE pays, BOB receives
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 890_000);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 900_000);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 1_009_082);
+
+		// The pool and fees are the same as for a regular buy
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(market).unwrap(),
+			MarketInfo {
+				base_balance: 90_918,
+				quote_balance: 109_990,
+				collected_base_fees: 0,
+				collected_quote_fees: 10,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: 1,
+				creator: ALICE,
+				active: true,
+			}
+		);
+	})
+}
+
+#[test]
+fn sell_to() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::sell_to(
+			Origin::signed(ALICE),
+			market,
+			10_000,
+			CHARLIE,
+			false
+		));
+
+		// ALICE pays, CHARLIE receives
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 890_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 900_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &CHARLIE), 9_082);
+
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(market).unwrap(),
+			MarketInfo {
+				base_balance: 109_990,
+				quote_balance: 90_918,
+				collected_base_fees: 10,
+				collected_quote_fees: 0,
+				fee_asset: FeeAsset::Input,
+				flags: MarketFlags::default(),
+				created_at: 1,
+				creator: ALICE,
+				active: true,
+			}
+		);
+	})
+}
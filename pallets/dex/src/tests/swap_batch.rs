@@ -0,0 +1,97 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{
+	tests::*,
+	types::{OrderType, SwapOp},
+	Error, MarketId,
+};
+
+fn setup_markets() {
+	for base in [BTC, XMR] {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			base,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+	}
+}
+
+fn swap_op(
+	market: MarketId<AssetId>,
+	order_type: OrderType,
+	amount_in: Balance,
+	min_out: Balance,
+) -> SwapOp<Test> {
+	SwapOp { market, order_type, amount_in, min_out }
+}
+
+#[test]
+fn swap_batch() {
+	new_test_ext().execute_with(|| {
+		setup_markets();
+
+		assert_ok!(crate::Pallet::<Test>::swap_batch(
+			Origin::signed(ALICE),
+			vec![
+				swap_op(MarketId::new(BTC, USD), OrderType::Buy, 10_000, 9_082),
+				swap_op(MarketId::new(XMR, USD), OrderType::Sell, 10_000, 9_082),
+			]
+		));
+
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 909_082);
+		assert_eq!(crate::Pallet::<Test>::balance(XMR, &ALICE), 890_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 800_000 - 10_000 + 9_082);
+		System::assert_has_event(Event::Dex(crate::Event::Bought(
+			ALICE,
+			MarketId::new(BTC, USD),
+			10_000,
+			9_082,
+		)));
+		System::assert_last_event(Event::Dex(crate::Event::Sold(
+			ALICE,
+			MarketId::new(XMR, USD),
+			10_000,
+			9_082,
+		)));
+	})
+}
+
+#[test]
+fn swap_batch_last_leg_fails() {
+	new_test_ext().execute_with(|| {
+		setup_markets();
+
+		// The first leg succeeds, but the whole batch is rolled back
+		assert_noop!(
+			crate::Pallet::<Test>::swap_batch(
+				Origin::signed(ALICE),
+				vec![
+					swap_op(MarketId::new(BTC, USD), OrderType::Buy, 10_000, 9_082),
+					swap_op(MarketId::new(XMR, USD), OrderType::Sell, 10_000, 9_083),
+				]
+			),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 900_000);
+		assert_eq!(
+			crate::LiquidityPool::<Test>::get(MarketId::new(BTC, USD)).unwrap().base_balance,
+			100_000
+		);
+	})
+}
+
+#[test]
+fn swap_batch_too_large() {
+	new_test_ext().execute_with(|| {
+		setup_markets();
+
+		let ops = vec![swap_op(MarketId::new(BTC, USD), OrderType::Buy, 1_000, 0); 4];
+		assert_noop!(
+			crate::Pallet::<Test>::swap_batch(Origin::signed(ALICE), ops),
+			Error::<Test>::BatchTooLarge
+		);
+	})
+}
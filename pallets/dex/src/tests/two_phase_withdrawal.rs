@@ -0,0 +1,141 @@
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Perbill;
+
+use crate::{tests::*, Error, MarketId, PendingWithdrawal};
+
+fn setup_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	// BOB provides half of the BASE reserve
+	assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+		Origin::signed(BOB),
+		MarketId::new(BTC, USD),
+		100_000,
+		0
+	));
+}
+
+#[test]
+fn two_phase_withdrawal() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::request_withdrawal(
+			Origin::signed(BOB),
+			market,
+			Perbill::from_percent(50)
+		));
+		System::assert_last_event(Event::Dex(crate::Event::WithdrawalRequested(
+			BOB, market, 50_000, 0, 6,
+		)));
+		assert_eq!(
+			crate::Pallet::<Test>::pending_withdrawal(market, BOB),
+			Some(PendingWithdrawal { base_amount: 50_000, quote_amount: 0, executable_at: 6 })
+		);
+		// The claim left the pool and BOBs provision right away
+		assert_eq!(crate::LiquidityPool::<Test>::get(market).unwrap().base_balance, 150_000);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, BOB), (50_000, 0));
+		assert!(crate::Pallet::<Test>::pool_health(market).unwrap().reserves_match_balances);
+
+		// The WithdrawalDelay has not passed yet
+		run_to_block(5);
+		assert_noop!(
+			crate::Pallet::<Test>::execute_withdrawal(Origin::signed(BOB), market),
+			Error::<Test>::WithdrawalNotReady
+		);
+
+		run_to_block(6);
+		assert_ok!(crate::Pallet::<Test>::execute_withdrawal(Origin::signed(BOB), market));
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 1_000_000 - 50_000);
+		assert_eq!(crate::Pallet::<Test>::pending_withdrawal(market, BOB), None);
+		System::assert_last_event(Event::Dex(crate::Event::LiquidityWithdrawn(
+			BOB, market, 50_000, 0,
+		)));
+
+		// A withdrawal can only be executed once
+		assert_noop!(
+			crate::Pallet::<Test>::execute_withdrawal(Origin::signed(BOB), market),
+			Error::<Test>::NoPendingWithdrawal
+		);
+	})
+}
+
+#[test]
+fn two_phase_withdrawal_guaranteed_amount() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::request_withdrawal(
+			Origin::signed(BOB),
+			market,
+			Perbill::from_percent(50)
+		));
+		// Trading after the request does not change the claim
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			market,
+			10_000,
+			false,
+			None
+		));
+
+		run_to_block(6);
+		assert_ok!(crate::Pallet::<Test>::execute_withdrawal(Origin::signed(BOB), market));
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 1_000_000 - 50_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), 0);
+	})
+}
+
+#[test]
+fn two_phase_withdrawal_one_at_a_time() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::request_withdrawal(
+			Origin::signed(BOB),
+			market,
+			Perbill::from_percent(10)
+		));
+		assert_noop!(
+			crate::Pallet::<Test>::request_withdrawal(
+				Origin::signed(BOB),
+				market,
+				Perbill::from_percent(10)
+			),
+			Error::<Test>::WithdrawalPending
+		);
+	})
+}
+
+#[test]
+fn two_phase_withdrawal_would_drain_pool() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		// The sole provider has to use withdraw_liquidity to remove the market
+		assert_noop!(
+			crate::Pallet::<Test>::request_withdrawal(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				Perbill::one()
+			),
+			Error::<Test>::WouldDrainPool
+		);
+	})
+}
@@ -0,0 +1,105 @@
+use frame_support::{
+	assert_ok,
+	traits::Get,
+	weights::{GetDispatchInfo, Weight},
+};
+
+use crate::{tests::*, Error, MarketId};
+
+fn rejected_trade_weight() -> Weight {
+	// Only the market has been read
+	10_000 + <Test as frame_system::Config>::DbWeight::get().reads(1)
+}
+
+#[test]
+fn rejected_buy_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		let declared_weight =
+			crate::Call::<Test>::buy { market, quote_amount: 1_000, oracle_price: None }
+				.get_dispatch_info()
+				.weight;
+
+		let err =
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), market, 1_000, None).unwrap_err();
+
+		assert_eq!(err.error, Error::<Test>::MarketDoesNotExist.into());
+		assert_eq!(err.post_info.actual_weight, Some(rejected_trade_weight()));
+		assert!(rejected_trade_weight() < declared_weight);
+	})
+}
+
+#[test]
+fn rejected_sell_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		MinTradeAmount::set(5_000);
+
+		let err = crate::Pallet::<Test>::sell(
+			Origin::signed(ALICE),
+			MarketId::new(BTC, USD),
+			1_000,
+			false,
+			None,
+		)
+		.unwrap_err();
+
+		assert_eq!(err.error, Error::<Test>::TradeTooSmall.into());
+		assert_eq!(err.post_info.actual_weight, Some(rejected_trade_weight()));
+	})
+}
+
+#[test]
+fn accepted_buy_pays_declared_weight() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+
+		let post_info =
+			crate::Pallet::<Test>::buy(Origin::signed(ALICE), MarketId::new(BTC, USD), 1_000, None)
+				.unwrap();
+
+		// Without an actual weight the declared weight is charged
+		assert_eq!(post_info.actual_weight, None);
+	})
+}
+
+#[test]
+fn trade_weight() {
+	// The market, FeeExempt, TradedVolume, FeeRemainders, ProtocolFees,
+	// both asset details and five balances are read, all of them but the asset details written
+	let trade_weight =
+		10_000 + <Test as frame_system::Config>::DbWeight::get().reads_writes(12, 10);
+	let market = MarketId::new(BTC, USD);
+
+	assert_eq!(
+		crate::Call::<Test>::buy { market, quote_amount: 1_000, oracle_price: None }
+			.get_dispatch_info()
+			.weight,
+		trade_weight
+	);
+	assert_eq!(
+		crate::Call::<Test>::sell_to {
+			market,
+			base_amount: 1_000,
+			recipient: BOB,
+			allow_death: false
+		}
+		.get_dispatch_info()
+		.weight,
+		trade_weight
+	);
+}
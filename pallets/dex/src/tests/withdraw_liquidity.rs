@@ -1,15 +1,16 @@
 use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Perbill;
 
-use crate::{tests::*, Error};
+use crate::{tests::*, Error, MarketId};
 
 #[test]
 fn withdraw_liquidity_no_market() {
 	new_test_ext().execute_with(|| {
 		let origin = Origin::signed(ALICE);
 
-		let market = (BTC, USD);
+		let market = MarketId::new(BTC, USD);
 		assert_noop!(
-			crate::Pallet::<Test>::withdraw_liquidity(origin, market, 100, 100),
+			crate::Pallet::<Test>::withdraw_liquidity(origin, market, 100, 100, false),
 			Error::<Test>::MarketDoesNotExist
 		);
 	})
@@ -21,20 +22,21 @@ fn withdraw_liquidity_not_enough_balance() {
 		let origin_alice = Origin::signed(ALICE);
 		let base_asset = BTC;
 		let quote_asset = USD;
-		let market = (base_asset, quote_asset);
+		let market = MarketId::new(base_asset, quote_asset);
 
 		assert_ok!(crate::Pallet::<Test>::create_market_pool(
 			origin_alice,
 			base_asset,
 			quote_asset,
 			100,
-			100
+			100,
+			None
 		));
 
 		let origin_bob = Origin::signed(BOB);
 		// This will obviously not work as BOB has not yet deposited anything into the pool
 		assert_noop!(
-			crate::Pallet::<Test>::withdraw_liquidity(origin_bob, market, 100, 100),
+			crate::Pallet::<Test>::withdraw_liquidity(origin_bob, market, 100, 100, false),
 			Error::<Test>::NotEnoughBalance
 		);
 	})
@@ -46,17 +48,24 @@ fn withdraw_liquidity() {
 		let origin_alice = Origin::signed(ALICE);
 		let base_asset = BTC;
 		let quote_asset = USD;
-		let market = (base_asset, quote_asset);
+		let market = MarketId::new(base_asset, quote_asset);
 
 		assert_ok!(crate::Pallet::<Test>::create_market_pool(
 			origin_alice.clone(),
 			base_asset,
 			quote_asset,
 			100_000,
-			100_000
+			100_000,
+			None
 		));
 
-		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(origin_alice, market, 50_000, 50_000));
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			origin_alice,
+			market,
+			50_000,
+			50_000,
+			false
+		));
 
 		// check balances
 		assert_eq!(crate::Pallet::<Test>::balance(base_asset, &ALICE), 950_000);
@@ -67,5 +76,283 @@ fn withdraw_liquidity() {
 	})
 }
 
-// TODO: there should be a test to ensure that withdrawing liquidity does not destroy the pool and
-// set the balances to zero
+#[test]
+fn withdraw_liquidity_keeps_pool_functioning() {
+	new_test_ext().execute_with(|| {
+		let origin_alice = Origin::signed(ALICE);
+		let base_asset = BTC;
+		let quote_asset = USD;
+		let market = MarketId::new(base_asset, quote_asset);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin_alice.clone(),
+			base_asset,
+			quote_asset,
+			100_000,
+			100_000,
+			None
+		));
+
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity(
+			origin_alice.clone(),
+			market,
+			50_000,
+			50_000,
+			false
+		));
+
+		// The reserves are reduced but not destroyed
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 50_000);
+		assert_eq!(market_info.quote_balance, 50_000);
+
+		// And the pool can still be traded in
+		assert_ok!(crate::Pallet::<Test>::buy(origin_alice, market, 1_000, None));
+	})
+}
+
+#[test]
+fn withdraw_liquidity_would_drain_pool() {
+	new_test_ext().execute_with(|| {
+		let origin_alice = Origin::signed(ALICE);
+		let base_asset = BTC;
+		let quote_asset = USD;
+		let market = MarketId::new(base_asset, quote_asset);
+
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			origin_alice.clone(),
+			base_asset,
+			quote_asset,
+			100_000,
+			100_000,
+			None
+		));
+
+		// Withdrawing everything has to go through remove_market
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				origin_alice.clone(),
+				market,
+				100_000,
+				0,
+				false
+			),
+			Error::<Test>::WouldDrainPool
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				origin_alice,
+				market,
+				100_000,
+				100_000,
+				false
+			),
+			Error::<Test>::WouldDrainPool
+		);
+	})
+}
+
+fn setup_traded_market() {
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		100_000,
+		100_000,
+		None
+	));
+	// Shifts the reserves to 109_990 BTC and 90_918 USD
+	assert_ok!(crate::Pallet::<Test>::sell(
+		Origin::signed(BOB),
+		MarketId::new(BTC, USD),
+		10_000,
+		false,
+		None
+	));
+}
+
+#[test]
+fn withdraw_liquidity_with_slippage_at_minimum() {
+	new_test_ext().execute_with(|| {
+		setup_traded_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity_with_slippage(
+			Origin::signed(ALICE),
+			market,
+			Perbill::from_percent(50),
+			54_995,
+			45_459,
+			false
+		));
+
+		// ALICE receives half of the shifted reserves
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 954_995);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 945_459);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (50_000, 50_000));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 54_995);
+		assert_eq!(market_info.quote_balance, 45_459);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_with_slippage_below_minimum() {
+	new_test_ext().execute_with(|| {
+		setup_traded_market();
+		let market = MarketId::new(BTC, USD);
+
+		// Signed before the trade, expecting half of the initial reserves
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity_with_slippage(
+				Origin::signed(ALICE),
+				market,
+				Perbill::from_percent(50),
+				50_000,
+				50_000,
+				false
+			),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity_with_slippage(
+				Origin::signed(ALICE),
+				market,
+				Perbill::from_percent(50),
+				54_995,
+				45_460,
+				false
+			),
+			Error::<Test>::SlippageExceeded
+		);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_exceeding_reserves() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// The recorded provision exceeds what the market holds
+		crate::LiqProvisionPool::<Test>::insert(market, ALICE, (200_000, 200_000));
+
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				Origin::signed(ALICE),
+				market,
+				150_000,
+				50_000,
+				false
+			),
+			Error::<Test>::InsufficientLiquidity
+		);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_exceeding_pool_balance() {
+	new_test_ext().execute_with(|| {
+		let market = MarketId::new(BTC, USD);
+		assert_ok!(crate::Pallet::<Test>::create_market_pool(
+			Origin::signed(ALICE),
+			BTC,
+			USD,
+			100_000,
+			100_000,
+			None
+		));
+		// The recorded reserves and provision exceed what the pool account holds
+		crate::LiquidityPool::<Test>::mutate(market, |market_info| {
+			let market_info = market_info.as_mut().unwrap();
+			market_info.base_balance = 300_000;
+			market_info.quote_balance = 300_000;
+		});
+		crate::LiqProvisionPool::<Test>::insert(market, ALICE, (300_000, 300_000));
+
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity(
+				Origin::signed(ALICE),
+				market,
+				150_000,
+				150_000,
+				false
+			),
+			Error::<Test>::InsufficientLiquidity
+		);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_proportional_half() {
+	new_test_ext().execute_with(|| {
+		setup_traded_market();
+		let market = MarketId::new(BTC, USD);
+
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity_proportional(
+			Origin::signed(ALICE),
+			market,
+			Perbill::from_percent(50)
+		));
+
+		// ALICE receives half of the shifted reserves
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &ALICE), 954_995);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 945_459);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, ALICE), (50_000, 50_000));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 54_995);
+		assert_eq!(market_info.quote_balance, 45_459);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_proportional_all() {
+	new_test_ext().execute_with(|| {
+		setup_traded_market();
+		let market = MarketId::new(BTC, USD);
+		// BOB provides 10_000 of 110_000 BASE and 5_000 of 105_000 QUOTE
+		assert_ok!(crate::Pallet::<Test>::deposit_liquidity(
+			Origin::signed(BOB),
+			market,
+			10_000,
+			5_000
+		));
+
+		assert_ok!(crate::Pallet::<Test>::withdraw_liquidity_proportional(
+			Origin::signed(BOB),
+			market,
+			Perbill::one()
+		));
+
+		// The claims on the 119_990 BTC and 95_918 USD reserves are rounded down
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 990_908);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &BOB), 8_649);
+		assert_eq!(crate::LiqProvisionPool::<Test>::get(market, BOB), (0, 0));
+		let market_info = crate::LiquidityPool::<Test>::get(market).unwrap();
+		assert_eq!(market_info.base_balance, 109_082);
+		assert_eq!(market_info.quote_balance, 91_351);
+	})
+}
+
+#[test]
+fn withdraw_liquidity_proportional_would_drain_pool() {
+	new_test_ext().execute_with(|| {
+		setup_traded_market();
+
+		// As the sole provider, withdrawing everything has to go through remove_market
+		assert_noop!(
+			crate::Pallet::<Test>::withdraw_liquidity_proportional(
+				Origin::signed(ALICE),
+				MarketId::new(BTC, USD),
+				Perbill::one()
+			),
+			Error::<Test>::WouldDrainPool
+		);
+	})
+}
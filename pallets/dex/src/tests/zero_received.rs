@@ -0,0 +1,71 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error, MarketId};
+
+fn setup_market() {
+	// A huge BASE reserve makes a single BASE unit worth less than a QUOTE unit
+	assert_ok!(crate::Pallet::<Test>::create_market_pool(
+		Origin::signed(ALICE),
+		BTC,
+		USD,
+		900_000,
+		1_000,
+		None
+	));
+}
+
+#[test]
+fn zero_received_sell() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(
+				Origin::signed(CHARLIE),
+				MarketId::new(BTC, USD),
+				1,
+				false,
+				None
+			)
+			.map_err(|e| e.error),
+			Error::<Test>::ZeroReceived
+		);
+	})
+}
+
+#[test]
+fn zero_received_fee_in_output() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		SellFeeInQuote::set(true);
+
+		assert_noop!(
+			crate::Pallet::<Test>::sell(
+				Origin::signed(CHARLIE),
+				MarketId::new(BTC, USD),
+				1,
+				false,
+				None
+			)
+			.map_err(|e| e.error),
+			Error::<Test>::ZeroReceived
+		);
+	})
+}
+
+#[test]
+fn zero_received_large_enough_input() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// Enough BASE for a single QUOTE unit is accepted
+		assert_ok!(crate::Pallet::<Test>::sell(
+			Origin::signed(CHARLIE),
+			MarketId::new(BTC, USD),
+			10_000,
+			false,
+			None
+		));
+		assert!(crate::Pallet::<Test>::balance(USD, &CHARLIE) > 0);
+	})
+}
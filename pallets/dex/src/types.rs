@@ -2,28 +2,198 @@
 #![allow(type_alias_bounds)]
 
 use crate::Config;
-use codec::{Decode, Encode, MaxEncodedLen};
-use frame_support::{traits::tokens::fungibles::Inspect, RuntimeDebugNoBound};
+use codec::{Decode, Encode, EncodeLike, MaxEncodedLen};
+use frame_support::{traits::tokens::fungibles::Inspect, RuntimeDebug, RuntimeDebugNoBound};
 use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::Perbill;
 
 /// The type identifying a market, which consists of Base and Quote asset
 /// e.g.: BTCUSD means BTC is the base asset and is quoted in USD
-pub type Market<T: Config> = (AssetIdOf<T>, AssetIdOf<T>);
+pub type Market<T: Config> = MarketId<AssetIdOf<T>>;
+
+/// Identifies a market by its BASE and QUOTE asset.
+/// Encodes exactly like the (BASE, QUOTE) tuple which identified markets before,
+/// so the storage keys and event topics of existing markets remain valid
+#[derive(
+	RuntimeDebug,
+	Clone,
+	Copy,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct MarketId<AssetId> {
+	/// The asset being traded
+	pub base: AssetId,
+	/// The asset the BASE asset is priced in
+	pub quote: AssetId,
+}
+
+impl<AssetId> MarketId<AssetId> {
+	/// Creates the market trading the base asset, priced in the quote asset
+	pub fn new(base: AssetId, quote: AssetId) -> Self {
+		Self { base, quote }
+	}
+
+	/// The market of the same assets in the opposite direction
+	pub fn inverse(self) -> Self {
+		Self::new(self.quote, self.base)
+	}
+}
+
+impl<AssetId: Copy> MarketId<AssetId> {
+	/// The asset of one side of the market
+	pub fn asset(&self, side: BaseOrQuote) -> AssetId {
+		side.pick((self.base, self.quote))
+	}
+}
+
+impl<AssetId: Ord> MarketId<AssetId> {
+	/// Creates the market of two assets in their canonical direction,
+	/// with the smaller asset as BASE asset, regardless of the order they are given in
+	pub fn canonical(a: AssetId, b: AssetId) -> Self {
+		if a <= b {
+			Self::new(a, b)
+		} else {
+			Self::new(b, a)
+		}
+	}
+
+	/// Whether the market is in its canonical direction
+	pub fn is_canonical(&self) -> bool {
+		self.base <= self.quote
+	}
+}
+
+impl<AssetId> From<(AssetId, AssetId)> for MarketId<AssetId> {
+	fn from((base, quote): (AssetId, AssetId)) -> Self {
+		Self::new(base, quote)
+	}
+}
+
+impl<AssetId> From<MarketId<AssetId>> for (AssetId, AssetId) {
+	fn from(market: MarketId<AssetId>) -> Self {
+		(market.base, market.quote)
+	}
+}
+
+impl<AssetId: Encode> EncodeLike<(AssetId, AssetId)> for MarketId<AssetId> {}
+
+impl<AssetId: Encode> EncodeLike<MarketId<AssetId>> for (AssetId, AssetId) {}
 
 /// Can either be the Base or Quote asset
-#[derive(RuntimeDebugNoBound, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[derive(RuntimeDebugNoBound, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
 pub enum BaseOrQuote {
+	/// The BASE asset of a market
 	Base,
+	/// The QUOTE asset of a market
 	Quote,
 }
 
+impl BaseOrQuote {
+	/// Picks the value of this side from a (BASE, QUOTE) pair,
+	/// e.g. the assets of a market or the provision of a liquidity provider
+	pub fn pick<V>(self, (base, quote): (V, V)) -> V {
+		match self {
+			Self::Base => base,
+			Self::Quote => quote,
+		}
+	}
+}
+
 /// Enumerates over buy and sell actions
-#[derive(RuntimeDebugNoBound, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[derive(RuntimeDebugNoBound, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum OrderType {
+	/// Exchange the QUOTE asset for the BASE asset
 	Buy,
+	/// Exchange the BASE asset for the QUOTE asset
 	Sell,
 }
 
+impl OrderType {
+	/// The sides of a market a trade puts into and takes out of the pool
+	///
+	/// # Returns:
+	/// (side put into the pool, side taken out of the pool)
+	pub fn sides(self) -> (BaseOrQuote, BaseOrQuote) {
+		match self {
+			Self::Buy => (BaseOrQuote::Quote, BaseOrQuote::Base),
+			Self::Sell => (BaseOrQuote::Base, BaseOrQuote::Quote),
+		}
+	}
+}
+
+/// A single swap of a swap_batch
+#[derive(RuntimeDebugNoBound, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct SwapOp<T: Config> {
+	/// The market to trade in
+	pub market: Market<T>,
+
+	/// Whether the BASE asset is bought or sold
+	pub order_type: OrderType,
+
+	/// The amount of QUOTE asset spent when buying, or BASE asset sold when selling
+	pub amount_in: BalanceOf<T>,
+
+	/// The minimum amount to receive, otherwise the whole batch fails
+	pub min_out: BalanceOf<T>,
+}
+
+/// The asset in which the taker fee of a market is collected
+#[derive(RuntimeDebug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum FeeAsset {
+	/// The asset the taker puts into the pool, QUOTE when buying and BASE when selling
+	Input,
+	/// Always the BASE asset
+	Base,
+	/// Always the QUOTE asset
+	Quote,
+}
+
+/// The operations which are enabled in a market
+#[derive(RuntimeDebug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct MarketFlags {
+	/// Whether liquidity may be deposited
+	pub allow_deposit: bool,
+
+	/// Whether liquidity may be withdrawn
+	pub allow_withdraw: bool,
+
+	/// Whether the market may be traded in
+	pub allow_trade: bool,
+}
+
+impl Default for MarketFlags {
+	fn default() -> Self {
+		Self { allow_deposit: true, allow_withdraw: true, allow_trade: true }
+	}
+}
+
+/// A price a user expects, together with the relative deviation from it which is still acceptable
+#[derive(RuntimeDebug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct ExpectedPrice<Balance> {
+	/// The numerator of the price, denominated in QUOTE per BASE
+	pub numerator: Balance,
+
+	/// The denominator of the price
+	pub denominator: Balance,
+
+	/// The relative deviation from the price which is still acceptable
+	pub tolerance: Perbill,
+}
+
 /// The balance type used in this crate
 pub type BalanceOf<T> =
 	<<T as crate::Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
@@ -47,4 +217,98 @@ pub struct MarketInfo<T: Config> {
 
 	/// The fees collected in this pool, in QUOTE asset, which will be payed out periodically
 	pub collected_quote_fees: BalanceOf<T>,
+
+	/// The asset in which the taker fee is collected
+	pub fee_asset: FeeAsset,
+
+	/// The operations which are enabled in this market
+	pub flags: MarketFlags,
+
+	/// The block in which this market was created
+	pub created_at: T::BlockNumber,
+
+	/// The account which created this market
+	pub creator: T::AccountId,
+
+	/// Whether the market may be traded in, which a registered market
+	/// only becomes once it received liquidity
+	pub active: bool,
+}
+
+impl<T: Config> MarketInfo<T> {
+	/// The reserve of one side of the pool
+	pub fn balance(&self, side: BaseOrQuote) -> BalanceOf<T> {
+		side.pick((self.base_balance, self.quote_balance))
+	}
+
+	/// The reserve of one side of the pool, to update it
+	pub fn balance_mut(&mut self, side: BaseOrQuote) -> &mut BalanceOf<T> {
+		side.pick((&mut self.base_balance, &mut self.quote_balance))
+	}
+
+	/// The fees collected in the asset of one side of the pool
+	pub fn collected_fees(&self, side: BaseOrQuote) -> BalanceOf<T> {
+		side.pick((self.collected_base_fees, self.collected_quote_fees))
+	}
+
+	/// The fees collected in the asset of one side of the pool, to update them
+	pub fn collected_fees_mut(&mut self, side: BaseOrQuote) -> &mut BalanceOf<T> {
+		side.pick((&mut self.collected_base_fees, &mut self.collected_quote_fees))
+	}
+}
+
+/// A withdrawal of liquidity which has been requested, but not yet executed.
+/// The amounts have already been taken out of the reserves of the market
+#[derive(RuntimeDebugNoBound, Clone, Eq, PartialEq, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct PendingWithdrawal<T: Config> {
+	/// The amount of BASE asset the liquidity provider receives
+	pub base_amount: BalanceOf<T>,
+
+	/// The amount of QUOTE asset the liquidity provider receives
+	pub quote_amount: BalanceOf<T>,
+
+	/// The block from which on the withdrawal can be executed
+	pub executable_at: T::BlockNumber,
+}
+
+/// All information about a market, as returned by the runtime API.
+/// Mirrors MarketInfo without depending on the pallet Config
+#[derive(RuntimeDebug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct MarketDetails<Balance> {
+	/// The balance of the BASE asset in this pool
+	pub base_balance: Balance,
+
+	/// The balance of QUOTE asset in this pool
+	pub quote_balance: Balance,
+
+	/// The fees collected in this pool, in BASE asset, which are not yet payed out
+	pub collected_base_fees: Balance,
+
+	/// The fees collected in this pool, in QUOTE asset, which are not yet payed out
+	pub collected_quote_fees: Balance,
+
+	/// The asset in which the taker fee is collected
+	pub fee_asset: FeeAsset,
+
+	/// The operations which are enabled in the market
+	pub flags: MarketFlags,
+}
+
+/// Health report of a market, used for monitoring
+#[derive(RuntimeDebug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PoolHealth<Balance> {
+	/// Whether both reserves of the market are non zero
+	pub reserves_non_zero: bool,
+
+	/// Whether the pool account holds exactly the recorded reserves of both assets
+	pub reserves_match_balances: bool,
+
+	/// The amount of BASE asset in the pool account which is not accounted for by any market
+	pub base_surplus: Balance,
+
+	/// The amount of QUOTE asset in the pool account which is not accounted for by any market
+	pub quote_surplus: Balance,
 }
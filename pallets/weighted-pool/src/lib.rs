@@ -0,0 +1,445 @@
+//! Weighted Pool Pallet
+//!
+//! This pallet provides pools of more than two assets, each with a weight,
+//! Similar to Balancer.
+//!
+//! # Overview:
+//! A weighted pool keeps the product of its balances, each raised to the weight of its asset,
+//! constant, so any asset of the pool can be exchanged for any other one.
+//! With two assets of equal weight this is the constant product of the DEX pallet.
+//! The DEX pallet keys every market, liquidity provision and payout by a (BASE, QUOTE) pair,
+//! which is why weighted pools live in this separate pallet
+//! rather than generalizing the markets of the DEX pallet
+//!
+//! # Interface:
+//! create_pool: Allows the user to create a weighted pool with some initial balance of each asset
+//! swap: Allows the user to exchange one asset of a pool for another one of the same pool
+//!
+//! # Not supported yet:
+//! Depositing and withdrawing liquidity, taker fees and their payout,
+//! benchmarked weights and a runtime API.
+//! Until they are, the pallet is not part of the runtime
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+use frame_support::{
+	ensure,
+	inherent::Vec,
+	traits::{
+		tokens::fungibles::{Inspect, Transfer},
+		Get,
+	},
+	transactional, BoundedVec, PalletId,
+};
+pub use pallet::*;
+use sp_core::U256;
+use sp_runtime::traits::{AccountIdConversion, One, Zero};
+
+/// The precision of the fixed point numbers the weighted invariant is evaluated in
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+#[cfg(test)]
+mod tests;
+
+/// The balance type used in this crate
+pub type BalanceOf<T> =
+	<<T as Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// The asset id type used in this crate
+pub type AssetIdOf<T> =
+	<<T as Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+
+/// Identifies a weighted pool
+pub type PoolId = u32;
+
+/// The assets of a pool, as (asset, balance, weight).
+/// The weights are relative to each other, so only their ratios matter
+pub type PoolAssets<T> =
+	BoundedVec<(AssetIdOf<T>, BalanceOf<T>, u32), <T as Config>::MaxPoolAssets>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{pallet_prelude::*, Blake2_128Concat};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The ubiqutous event type
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The pallet id, used for deriving the account holding the assets of all pools
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The type that enables currency transfers
+		type Currencies: Transfer<Self::AccountId, Balance = u128, AssetId = u8>;
+
+		/// The maximum number of assets in a single pool
+		#[pallet::constant]
+		type MaxPoolAssets: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Stores the assets of the weighted pools
+	///
+	/// Maps PoolId => [(AssetId, Balance, Weight)]
+	#[pallet::storage]
+	#[pallet::getter(fn pool)]
+	pub type Pools<T: Config> = StorageMap<_, Blake2_128Concat, PoolId, PoolAssets<T>, OptionQuery>;
+
+	/// The PoolId the next created pool receives
+	#[pallet::storage]
+	#[pallet::getter(fn next_pool_id)]
+	pub type NextPoolId<T: Config> = StorageValue<_, PoolId, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A weighted pool has been created
+		///
+		/// # Fields:
+		/// 0: Who created the pool
+		/// 1: The pool identifier
+		PoolCreated(T::AccountId, PoolId),
+
+		/// An asset of a pool has been exchanged for another one
+		///
+		/// # Fields:
+		/// 0: The trader
+		/// 1: The pool identifier
+		/// 2: The asset put into the pool
+		/// 3: The amount put into the pool
+		/// 4: The asset taken out of the pool
+		/// 5: The amount taken out of the pool
+		Swapped(T::AccountId, PoolId, AssetIdOf<T>, BalanceOf<T>, AssetIdOf<T>, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A pool needs at least two assets
+		TooFewAssets,
+
+		/// A pool cannot hold more than MaxPoolAssets assets
+		TooManyAssets,
+
+		/// An asset appears more than once in a pool
+		DuplicateAsset,
+
+		/// Every asset of a pool needs a weight and an initial balance
+		ZeroAmount,
+
+		/// The pool the user specified does not exist
+		PoolDoesNotExist,
+
+		/// The asset is not part of the pool
+		AssetNotInPool,
+
+		/// An asset cannot be exchanged for itself
+		SameAsset,
+
+		/// The user does not have enough balance
+		NotEnoughBalance,
+
+		/// A single trade may put at most half the balance of the asset into the pool
+		TradeTooLarge,
+
+		/// The output is below the minimum the user accepts
+		SlippageExceeded,
+
+		/// The trade is so small that it would receive nothing
+		ZeroReceived,
+
+		/// Transferring an asset failed
+		Transfer,
+
+		/// Some arithmetic error occurred
+		Arithmetic,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Creates a new weighted pool of several assets.
+		/// The user provides the initial balance of every asset,
+		/// which together with the weights sets the initial prices
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// assets: The (asset, initial balance, weight) of every asset of the pool
+		///
+		/// # Weight:
+		/// Requires base weight + 1 read and 2 writes for the pool,
+		/// plus 3 reads and 2 writes to check and transfer the balance of each asset
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+			1 + 3 * assets.len() as u64,
+			2 + 2 * assets.len() as u64,
+		))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn create_pool(
+			origin: OriginFor<T>,
+			assets: Vec<(AssetIdOf<T>, BalanceOf<T>, u32)>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(assets.len() >= 2, Error::<T>::TooFewAssets);
+			let assets: PoolAssets<T> = assets.try_into().map_err(|_| Error::<T>::TooManyAssets)?;
+			for (i, (asset, balance, weight)) in assets.iter().enumerate() {
+				ensure!(!balance.is_zero() && !weight.is_zero(), Error::<T>::ZeroAmount);
+				ensure!(
+					assets.iter().skip(i + 1).all(|(other, _, _)| other != asset),
+					Error::<T>::DuplicateAsset
+				);
+				ensure!(Self::balance(*asset, &who) >= *balance, Error::<T>::NotEnoughBalance);
+			}
+
+			let pool_account = Self::pool_account();
+			for (asset, balance, _) in assets.iter() {
+				Self::safe_transfer(*asset, &who, &pool_account, *balance, true)?;
+			}
+
+			let pool = NextPoolId::<T>::get();
+			NextPoolId::<T>::put(pool.checked_add(1).ok_or(Error::<T>::Arithmetic)?);
+			Pools::<T>::insert(pool, assets);
+
+			Self::deposit_event(Event::PoolCreated(who, pool));
+
+			Ok(())
+		}
+
+		/// Exchanges one asset of a weighted pool for another one of the same pool
+		///
+		/// # Arguments:
+		/// origin: The obiquitous origin of a transaction
+		/// pool: The pool to trade in
+		/// asset_in: The asset to put into the pool
+		/// asset_out: The asset to take out of the pool
+		/// amount_in: The amount of asset_in to spend
+		/// min_amount_out: The minimum amount of asset_out the user accepts
+		///
+		/// # Weight:
+		/// Requires base weight + 7 reads and 5 writes, as it reads the pool,
+		/// both asset details and the balances of the trader and the pool account in both assets,
+		/// and writes the pool and the same balances
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(7, 5))]
+		#[transactional] // This Dispatchable is atomic
+		pub fn swap(
+			origin: OriginFor<T>,
+			pool: PoolId,
+			asset_in: AssetIdOf<T>,
+			asset_out: AssetIdOf<T>,
+			amount_in: BalanceOf<T>,
+			min_amount_out: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(asset_in != asset_out, Error::<T>::SameAsset);
+			let mut assets = Pools::<T>::get(pool).ok_or(Error::<T>::PoolDoesNotExist)?;
+			let find = |asset: AssetIdOf<T>| {
+				assets
+					.iter()
+					.find(|(pool_asset, _, _)| *pool_asset == asset)
+					.map(|(_, balance, weight)| (*balance, *weight))
+					.ok_or(Error::<T>::AssetNotInPool)
+			};
+			let (balance_in, weight_in) = find(asset_in)?;
+			let (balance_out, weight_out) = find(asset_out)?;
+
+			ensure!(Self::balance(asset_in, &who) >= amount_in, Error::<T>::NotEnoughBalance);
+			let amount_out =
+				Self::weighted_output(balance_in, weight_in, balance_out, weight_out, amount_in)?;
+			ensure!(amount_in.is_zero() || !amount_out.is_zero(), Error::<T>::ZeroReceived);
+			ensure!(amount_out >= min_amount_out, Error::<T>::SlippageExceeded);
+
+			let pool_account = Self::pool_account();
+			Self::safe_transfer(asset_in, &who, &pool_account, amount_in, true)?;
+			Self::safe_transfer(asset_out, &pool_account, &who, amount_out, true)?;
+
+			for (asset, balance, _) in assets.iter_mut() {
+				if *asset == asset_in {
+					*balance = balance_in.checked_add(amount_in).ok_or(Error::<T>::Arithmetic)?;
+				} else if *asset == asset_out {
+					*balance = balance_out.checked_sub(amount_out).ok_or(Error::<T>::Arithmetic)?;
+				}
+			}
+			Pools::<T>::insert(pool, assets);
+
+			Self::deposit_event(Event::Swapped(
+				who, pool, asset_in, amount_in, asset_out, amount_out,
+			));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The account holding the assets of all pools
+	pub fn pool_account() -> T::AccountId {
+		T::PalletId::get().into_account_truncating()
+	}
+
+	/// Helper function to get the account balance easily
+	///
+	/// # Arguments:
+	/// asset_id: The asset were trying to query
+	/// who: The account for which the balance should be retrived
+	///
+	/// # Returns:
+	/// The balance of a user for a given asset
+	fn balance(asset_id: AssetIdOf<T>, who: &T::AccountId) -> BalanceOf<T> {
+		<<T as Config>::Currencies as Inspect<<T as frame_system::Config>::AccountId>>::balance(
+			asset_id, who,
+		)
+	}
+
+	/// Transfers an asset between two accounts,
+	/// mapping every failure of T::Currencies to Error::Transfer
+	///
+	/// # Arguments:
+	/// asset: The asset to transfer
+	/// from: The account sending the asset
+	/// to: The account receiving the asset
+	/// amount: The amount to transfer
+	/// keep_alive: Whether the sending account must be kept alive
+	///
+	/// # Returns:
+	/// If Ok, the transfer succeeded
+	/// Else Error::Transfer
+	fn safe_transfer(
+		asset: AssetIdOf<T>,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: BalanceOf<T>,
+		keep_alive: bool,
+	) -> Result<(), Error<T>> {
+		<T as Config>::Currencies::transfer(asset, from, to, amount, keep_alive)
+			.map(|_| ())
+			.map_err(|_| Error::<T>::Transfer)
+	}
+
+	/// Applies the weighted constant product formula, without any fees:
+	/// amount_out =
+	/// 	balance_out * (1 - (balance_in / (balance_in + amount_in))^(weight_in / weight_out))
+	///
+	/// # Arguments:
+	/// balance_in: The pools balance of the asset being put in
+	/// weight_in: The weight of the asset being put in
+	/// balance_out: The pools balance of the asset being taken out
+	/// weight_out: The weight of the asset being taken out
+	/// amount_in: The amount being put into the pool
+	///
+	/// # Returns:
+	/// If Ok, the amount taken out of the pool, rounded down so the invariant never decreases,
+	/// and at most balance_out - 1, so no rounding can ever empty the pool
+	/// Else TradeTooLarge if amount_in exceeds half of balance_in, or some arithmetic error
+	fn weighted_output(
+		balance_in: BalanceOf<T>,
+		weight_in: u32,
+		balance_out: BalanceOf<T>,
+		weight_out: u32,
+		amount_in: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, Error<T>> {
+		if amount_in.is_zero() {
+			return Ok(Zero::zero());
+		}
+		// Keeps the base of the power above 2/3, where its series converges quickly
+		ensure!(amount_in <= balance_in / 2, Error::<T>::TradeTooLarge);
+
+		let one = U256::from(ONE);
+		// Rounded up, like the power, so the output is rounded down
+		let base = Self::div_up(
+			U256::from(balance_in) * one,
+			U256::from(balance_in) + U256::from(amount_in),
+		)
+		.ok_or(Error::<T>::Arithmetic)?;
+		let power = Self::pow_up(base, weight_in, weight_out).ok_or(Error::<T>::Arithmetic)?;
+
+		let amount_out: BalanceOf<T> = one
+			.checked_sub(power)
+			.and_then(|share| share.checked_mul(U256::from(balance_out)))
+			.and_then(|amount_out| amount_out.checked_div(one))
+			.and_then(|amount_out| amount_out.try_into().ok())
+			.ok_or(Error::<T>::Arithmetic)?;
+
+		Ok(amount_out.min(balance_out.saturating_sub(One::one())))
+	}
+
+	/// Raises a fixed point number of at most one to a fractional power, rounding up
+	///
+	/// # Arguments:
+	/// base: The base, with ONE as its precision, which is at most ONE
+	/// exp_num: The numerator of the exponent
+	/// exp_den: The denominator of the exponent
+	///
+	/// # Returns:
+	/// If Some, base^(exp_num / exp_den) with ONE as its precision
+	/// None on a zero exp_den or arithmetic overflow
+	fn pow_up(base: U256, exp_num: u32, exp_den: u32) -> Option<U256> {
+		let one = U256::from(ONE);
+		let (whole, fraction) = (exp_num.checked_div(exp_den)?, exp_num % exp_den);
+
+		// The whole part of the exponent by repeated squaring
+		let (mut power, mut square, mut exp) = (one, base, whole);
+		while exp > 0 {
+			if exp & 1 == 1 {
+				power = Self::mul_up(power, square)?;
+			}
+			exp >>= 1;
+			if exp > 0 {
+				square = Self::mul_up(square, square)?;
+			}
+		}
+		if fraction == 0 {
+			return Some(power);
+		}
+
+		// The fractional part a by the binomial series of (1 - x)^a with x = 1 - base.
+		// For 0 < a < 1 every term after the first one is negative,
+		// the k-th one being the previous one times x * (k - 1 - a) / k, or a * x for k = 1.
+		// The terms are rounded down, so the power is rounded up
+		let x = one.checked_sub(base)?;
+		let (exp_num, exp_den) = (U256::from(fraction), U256::from(exp_den));
+		let (mut term, mut sum, mut k) = (one, U256::zero(), U256::one());
+		loop {
+			// |a - (k - 1)| in units of 1 / exp_den
+			let distance = match (k - U256::one()).checked_mul(exp_den)?.checked_sub(exp_num) {
+				Some(distance) => distance,
+				// Only for k = 1, as a < 1
+				None => exp_num,
+			};
+			term = term
+				.checked_mul(x)?
+				.checked_div(one)?
+				.checked_mul(distance)?
+				.checked_div(k.checked_mul(exp_den)?)?;
+			// Each term shrinks by at least the factor x, so the series ends within a few dozen
+			if term.is_zero() {
+				break;
+			}
+			sum = sum.checked_add(term)?;
+			k = k.checked_add(U256::one())?;
+		}
+
+		Self::mul_up(power, one.checked_sub(sum)?)
+	}
+
+	/// Multiplies two fixed point numbers with ONE as their precision, rounding up
+	fn mul_up(a: U256, b: U256) -> Option<U256> {
+		Self::div_up(a.checked_mul(b)?, U256::from(ONE))
+	}
+
+	/// Divides two numbers, rounding up
+	fn div_up(a: U256, b: U256) -> Option<U256> {
+		let quotient = a.checked_div(b)?;
+
+		if quotient * b == a {
+			Some(quotient)
+		} else {
+			quotient.checked_add(U256::one())
+		}
+	}
+}
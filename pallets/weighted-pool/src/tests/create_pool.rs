@@ -0,0 +1,78 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error};
+
+#[test]
+fn create_pool() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_pool(
+			Origin::signed(ALICE),
+			vec![(BTC, 100_000, 50), (XMR, 100_000, 25), (USD, 200_000, 25)]
+		));
+		System::assert_last_event(Event::WeightedPool(crate::Event::PoolCreated(ALICE, 0)));
+
+		assert_eq!(
+			crate::Pallet::<Test>::pool(0).unwrap().into_inner(),
+			vec![(BTC, 100_000, 50), (XMR, 100_000, 25), (USD, 200_000, 25)]
+		);
+		assert_eq!(crate::Pallet::<Test>::next_pool_id(), 1);
+
+		let pool_account = crate::Pallet::<Test>::pool_account();
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &pool_account), 100_000);
+		assert_eq!(crate::Pallet::<Test>::balance(XMR, &pool_account), 100_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &pool_account), 200_000);
+		assert_eq!(crate::Pallet::<Test>::balance(USD, &ALICE), 800_000);
+	})
+}
+
+#[test]
+fn create_pool_asset_count() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(origin.clone(), vec![(BTC, 100_000, 1)]),
+			Error::<Test>::TooFewAssets
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(
+				origin,
+				vec![(BTC, 100_000, 1), (XMR, 100_000, 1), (USD, 100_000, 1), (DOT, 100_000, 1)]
+			),
+			Error::<Test>::TooManyAssets
+		);
+	})
+}
+
+#[test]
+fn create_pool_invalid_assets() {
+	new_test_ext().execute_with(|| {
+		let origin = Origin::signed(ALICE);
+
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(
+				origin.clone(),
+				vec![(BTC, 100_000, 1), (XMR, 100_000, 1), (BTC, 100_000, 1)]
+			),
+			Error::<Test>::DuplicateAsset
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(
+				origin.clone(),
+				vec![(BTC, 100_000, 1), (XMR, 100_000, 0)]
+			),
+			Error::<Test>::ZeroAmount
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(origin, vec![(BTC, 0, 1), (XMR, 100_000, 1)]),
+			Error::<Test>::ZeroAmount
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::create_pool(
+				Origin::signed(BOB),
+				vec![(BTC, 100_000, 1), (USD, 100_000, 1)]
+			),
+			Error::<Test>::NotEnoughBalance
+		);
+	})
+}
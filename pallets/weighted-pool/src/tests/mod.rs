@@ -0,0 +1,5 @@
+mod create_pool;
+mod mock;
+mod swap;
+
+pub use mock::*;
@@ -0,0 +1,157 @@
+use frame_support::{assert_noop, assert_ok};
+
+use crate::{tests::*, Error};
+
+// BTC weighs as much as XMR and USD together, with twice the USD balance of XMR,
+// so a BTC trades for one XMR or two USD
+fn create_pool() {
+	assert_ok!(crate::Pallet::<Test>::create_pool(
+		Origin::signed(ALICE),
+		vec![(BTC, 100_000, 50), (XMR, 100_000, 25), (USD, 200_000, 25)]
+	));
+}
+
+fn pool_balances() -> Vec<Balance> {
+	crate::Pallet::<Test>::pool(0)
+		.unwrap()
+		.iter()
+		.map(|(_, balance, _)| *balance)
+		.collect()
+}
+
+#[test]
+fn swap_into_heavier_asset() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+
+		// 100_000 * (1 - (100_000 / 110_000)^(25 / 50)) = 4_653.74
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, XMR, BTC, 10_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, XMR, 10_000, BTC, 4_653,
+		)));
+
+		assert_eq!(pool_balances(), vec![100_000 - 4_653, 110_000, 200_000]);
+		assert_eq!(crate::Pallet::<Test>::balance(BTC, &BOB), 1_000_000 + 4_653);
+		assert_eq!(crate::Pallet::<Test>::balance(XMR, &BOB), 1_000_000 - 10_000);
+	})
+}
+
+#[test]
+fn swap_into_lighter_asset() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+
+		// 200_000 * (1 - (100_000 / 110_000)^(50 / 25)) = 34_710.74
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, BTC, USD, 10_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, BTC, 10_000, USD, 34_710,
+		)));
+
+		assert_eq!(pool_balances(), vec![110_000, 100_000, 200_000 - 34_710]);
+	})
+}
+
+#[test]
+fn swap_equal_weights() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+
+		// Assets of equal weight follow the constant product: 200_000 * 10_000 / 110_000 = 18_181.8
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, XMR, USD, 10_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, XMR, 10_000, USD, 18_181,
+		)));
+		// The BTC of the pool is not involved
+		assert_eq!(pool_balances(), vec![100_000, 110_000, 200_000 - 18_181]);
+	})
+}
+
+#[test]
+fn swap_two_assets_like_constant_product() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(crate::Pallet::<Test>::create_pool(
+			Origin::signed(ALICE),
+			vec![(BTC, 100_000, 1), (XMR, 100_000, 1)]
+		));
+
+		// The same output as a buy without fee in a constant product pool
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, XMR, BTC, 10_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, XMR, 10_000, BTC, 9_090,
+		)));
+	})
+}
+
+#[test]
+fn swap_consecutive() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, XMR, BTC, 10_000, 0));
+		// The second swap trades against the BTC balance left by the first one
+		assert_ok!(crate::Pallet::<Test>::swap(Origin::signed(BOB), 0, BTC, USD, 10_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, BTC, 10_000, USD, 36_167,
+		)));
+		assert_eq!(pool_balances(), vec![100_000 - 4_653 + 10_000, 110_000, 200_000 - 36_167]);
+	})
+}
+
+#[test]
+fn swap_largest_trade() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+		let origin = Origin::signed(BOB);
+
+		// At most half the XMR balance may be put in at once
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin.clone(), 0, XMR, BTC, 50_001, 0),
+			Error::<Test>::TradeTooLarge
+		);
+		// 100_000 * (1 - (100_000 / 150_000)^(25 / 50)) = 18_350.34
+		assert_ok!(crate::Pallet::<Test>::swap(origin, 0, XMR, BTC, 50_000, 0));
+		System::assert_last_event(Event::WeightedPool(crate::Event::Swapped(
+			BOB, 0, XMR, 50_000, BTC, 18_350,
+		)));
+	})
+}
+
+#[test]
+fn swap_min_amount_out() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+		let origin = Origin::signed(BOB);
+
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin.clone(), 0, XMR, BTC, 10_000, 4_654),
+			Error::<Test>::SlippageExceeded
+		);
+		assert_ok!(crate::Pallet::<Test>::swap(origin, 0, XMR, BTC, 10_000, 4_653));
+	})
+}
+
+#[test]
+fn swap_invalid() {
+	new_test_ext().execute_with(|| {
+		create_pool();
+		let origin = Origin::signed(BOB);
+
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin.clone(), 1, XMR, BTC, 10_000, 0),
+			Error::<Test>::PoolDoesNotExist
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin.clone(), 0, XMR, XMR, 10_000, 0),
+			Error::<Test>::SameAsset
+		);
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin.clone(), 0, DOT, BTC, 10_000, 0),
+			Error::<Test>::AssetNotInPool
+		);
+		// BOB holds no USD
+		assert_noop!(
+			crate::Pallet::<Test>::swap(origin, 0, USD, BTC, 10_000, 0),
+			Error::<Test>::NotEnoughBalance
+		);
+	})
+}
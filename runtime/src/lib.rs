@@ -7,7 +7,7 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use frame_support::PalletId;
-use frame_system::EnsureRoot;
+use frame_system::{EnsureRoot, EnsureSigned};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -16,7 +16,10 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, Verify,
+	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
@@ -101,9 +104,9 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -292,15 +295,96 @@ impl pallet_assets::Config for Runtime {
 parameter_types! {
 	// 10 Basis points taker fee, which is lower vs uniswap but may attract more taker flow
 	pub TakerFee: (u32, u32) = (1, 1_000);
+	// The taker fee is not capped
+	pub const MaxAbsoluteFee: Balance = Balance::MAX;
+	// Every trader pays the same taker fee, regardless of the volume
+	pub FeeTiers: Vec<(Balance, u32)> = Vec::new();
+	// New markets charge the taker fee right away
+	pub const FeeGracePeriod: BlockNumber = 0;
 	// Only 8 bytes available, so t is missing at the end
 	pub DexPalletId: PalletId = PalletId(*b"dexpalle");
+	// Trades are not capped relative to the pools reserves
+	pub MaxTradeFraction: Option<Perbill> = None;
+	// The price impact of a trade is not capped
+	pub const GlobalMaxSlippage: Perbill = Perbill::one();
+	// A buy or sell without a minimum output may move the price by at most 5%
+	pub const DefaultMaxSlippage: Perbill = Perbill::from_percent(5);
+	// Smaller trades would not pay any taker fee anyway
+	pub const MinTradeAmount: Balance = 1_000;
+	// Reserves which could not even be traded against once are removed with the last withdrawal
+	pub const DustThreshold: Balance = 1_000;
+	pub const EmitTradeEvents: bool = true;
+	// Sells collect the fee in the BASE asset they put into the pool
+	pub const SellFeeInQuote: bool = false;
+	pub const MaxMarketsPerAsset: u32 = 64;
+	// The whole taker fee goes to the liquidity providers for now
+	pub const ProtocolFeeShare: Perbill = Perbill::from_percent(0);
+	pub DexTreasuryAccount: AccountId = DexPalletId::get().into_sub_account_truncating(b"treasury");
+	// Reward the liquidity providers every 10 blocks
+	pub const PayoutInterval: BlockNumber = 10;
+	// Fees always wait for the payout interval
+	pub const MaxUndistributedFees: Balance = Balance::MAX;
+	// Withdrawals wait for a whole payout interval, so they can't be timed around a payout
+	pub const WithdrawalDelay: BlockNumber = 10;
+	pub const BlocksPerYear: BlockNumber = 365 * DAYS;
+	pub const MarketNameLimit: u32 = 32;
+	pub const MaxSwapBatchSize: u32 = 8;
+	// Any pair of assets may be created as market
+	pub const RestrictPairs: bool = false;
+	// The number of liquidity providers refunded at most when force-closing a market
+	pub const MaxForceCloseProviders: u32 = 256;
+	// As many liquidity providers per market as force-closing it can refund
+	pub const MaxProvidersPerMarket: u32 = 256;
+	// The number of markets paid out at most in a single payout,
+	// which keeps a payout of markets with MaxProvidersPerMarket providers below half a block
+	pub const MaxPayoutMarkets: u32 = 4;
+	// Liquidity has to stay for a full payout interval before sharing in the fees
+	pub const MinRewardAge: BlockNumber = 10;
+	// The creator of a market earns a small slice of its fees, the providers the rest
+	pub const CreatorRewardShare: Perbill = Perbill::from_percent(5);
+	pub const ProviderRewardShare: Perbill = Perbill::from_percent(95);
+	// Fees owed to nobody are swept to the treasury once they are worth a transfer
+	pub const FeeSweepThreshold: Option<Balance> = Some(1_000);
+	// Assets with very different decimals legitimately have extreme reserve ratios
+	pub const MaxReserveRatio: Option<u128> = None;
 }
 
 impl pallet_dex::Config for Runtime {
 	type Event = Event;
 	type TakerFee = TakerFee;
+	type MaxAbsoluteFee = MaxAbsoluteFee;
+	type FeeTiers = FeeTiers;
+	type FeeGracePeriod = FeeGracePeriod;
 	type PalletId = DexPalletId;
 	type Currencies = Assets;
+	type MaxTradeFraction = MaxTradeFraction;
+	type GlobalMaxSlippage = GlobalMaxSlippage;
+	type DefaultMaxSlippage = DefaultMaxSlippage;
+	type MinTradeAmount = MinTradeAmount;
+	type DustThreshold = DustThreshold;
+	type EmitTradeEvents = EmitTradeEvents;
+	type SellFeeInQuote = SellFeeInQuote;
+	type MaxMarketsPerAsset = MaxMarketsPerAsset;
+	type ProtocolFeeShare = ProtocolFeeShare;
+	type TreasuryAccount = DexTreasuryAccount;
+	type PayoutInterval = PayoutInterval;
+	type MaxUndistributedFees = MaxUndistributedFees;
+	type WithdrawalDelay = WithdrawalDelay;
+	type BlocksPerYear = BlocksPerYear;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	// Anyone can create a market
+	type CreatePoolOrigin = EnsureSigned<AccountId>;
+	type NameLimit = MarketNameLimit;
+	type MaxBatchSize = MaxSwapBatchSize;
+	type RestrictPairs = RestrictPairs;
+	type MaxForceCloseProviders = MaxForceCloseProviders;
+	type MaxProvidersPerMarket = MaxProvidersPerMarket;
+	type MaxPayoutMarkets = MaxPayoutMarkets;
+	type MinRewardAge = MinRewardAge;
+	type CreatorRewardShare = CreatorRewardShare;
+	type ProviderRewardShare = ProviderRewardShare;
+	type FeeSweepThreshold = FeeSweepThreshold;
+	type MaxReserveRatio = MaxReserveRatio;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -499,19 +583,118 @@ impl_runtime_apis! {
 		}
 	}
 
-	impl pallet_dex_runtime_api::DexRuntimeApi<Block> for Runtime {
+	impl pallet_dex_runtime_api::DexRuntimeApi<Block, AccountId> for Runtime {
 		fn current_price(market: (u8, u8)) -> (u128, u128) {
-			match pallet_dex::LiquidityPool::<Runtime>::get(market) {
-				Some(market_info) => {
-					let price = market_info.quote_balance.checked_div(market_info.base_balance).or(Some(0)).expect("Already ored the output; qed");
+			Dex::current_price(market.into()).unwrap_or((0, 0))
+		}
+
+		fn current_prices(markets: Vec<(u8, u8)>) -> Vec<Option<(u128, u128)>> {
+			markets.into_iter().map(|market| Dex::current_price(market.into())).collect()
+		}
+
+		fn price_any_direction(asset: u8, denomination: u8) -> Option<(u128, u128)> {
+			Dex::price_any_direction(asset, denomination)
+		}
+
+		fn is_ratio_sane(market: (u8, u8), max_ratio: u128) -> Option<bool> {
+			Dex::is_ratio_sane(market.into(), max_ratio)
+		}
+
+		fn price_after_deposit(
+			market: (u8, u8),
+			base_add: u128,
+			quote_add: u128,
+		) -> Option<(u128, u128)> {
+			Dex::price_after_deposit(market.into(), base_add, quote_add)
+		}
+
+		fn amount_to_reach_price(
+			market: (u8, u8),
+			target_price_num: u128,
+			target_price_den: u128,
+		) -> Option<(pallet_dex::OrderType, u128)> {
+			Dex::amount_to_reach_price(market.into(), target_price_num, target_price_den)
+		}
+
+		fn positions_of(account: AccountId) -> Vec<((u8, u8), (u128, u128))> {
+			Dex::positions_of(&account)
+				.into_iter()
+				.map(|(market, provision)| (market.into(), provision))
+				.collect()
+		}
+
+		fn providers(market: (u8, u8)) -> Vec<(AccountId, (u128, u128))> {
+			Dex::providers(market.into())
+		}
+
+		fn providers_paged(
+			market: (u8, u8),
+			offset: u32,
+			limit: u32,
+		) -> Vec<(AccountId, (u128, u128))> {
+			Dex::providers_paged(market.into(), offset, limit)
+		}
+
+		fn markets_for_asset(asset: u8) -> Vec<(u8, u8)> {
+			Dex::markets_for_asset(asset).into_iter().map(Into::into).collect()
+		}
 
-					// TODO: The price precision should come from the market configuration in the future
-					const DENOM: u128 = 10_000;
+		fn pool_health(market: (u8, u8)) -> Option<pallet_dex::PoolHealth<u128>> {
+			Dex::pool_health(market.into())
+		}
+
+		fn market_info(market: (u8, u8)) -> Option<pallet_dex::MarketDetails<u128>> {
+			Dex::market_info(market.into())
+		}
+
+		fn pending_payout_total(market: (u8, u8)) -> Option<(u128, u128)> {
+			Dex::pending_payout_total(market.into())
+		}
+
+		fn estimated_apr(market: (u8, u8)) -> Option<Perbill> {
+			Dex::estimated_apr(market.into())
+		}
+
+		fn market_metadata(market: (u8, u8)) -> Option<Vec<u8>> {
+			Dex::market_metadata(market).map(|name| name.into_inner())
+		}
+
+		fn min_out_for_slippage(
+			market: (u8, u8),
+			account: AccountId,
+			order_type: pallet_dex::OrderType,
+			amount_in: u128,
+			slippage: Perbill,
+		) -> Option<u128> {
+			Dex::min_out_for_slippage(market.into(), &account, order_type, amount_in, slippage)
+		}
+
+		fn amount_in_for_exact_out(
+			market: (u8, u8),
+			order_type: pallet_dex::OrderType,
+			amount_out: u128,
+		) -> Option<u128> {
+			Dex::amount_in_for_exact_out(market.into(), order_type, amount_out)
+		}
+
+		fn impermanent_loss(market: (u8, u8), account: AccountId) -> Option<Perbill> {
+			Dex::impermanent_loss(market.into(), &account)
+		}
+
+		fn lifetime_fees(market: (u8, u8), account: AccountId) -> (u128, u128) {
+			Dex::lifetime_fees(market, account)
+		}
+
+		fn arbitrage_spread(market_a: (u8, u8), market_b: (u8, u8)) -> Option<Perbill> {
+			Dex::arbitrage_spread(market_a.into(), market_b.into())
+		}
+
+		fn pool_accounts() -> (AccountId, AccountId) {
+			Dex::pool_accounts()
+		}
 
-					(price.saturating_mul(DENOM), DENOM)
-				},
-				None => (0, 0)
-			}
+		fn market_origin(market: (u8, u8)) -> Option<(AccountId, BlockNumber)> {
+			Dex::market_origin(market.into())
 		}
 	}
 